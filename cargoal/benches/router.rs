@@ -0,0 +1,58 @@
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::routing::Router;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const RESOURCES: [&str; 10] = [
+    "users", "posts", "comments", "orders", "items", "invoices", "teams", "projects", "tags",
+    "files",
+];
+
+fn ok_handler(_req: Request) -> Response {
+    Response::new(StatusCode::OK, "OK")
+}
+
+/// Build a Router with a few hundred routes, like a medium sized application
+fn build_router() -> Router {
+    let mut router = Router::new();
+    for version in ["v1", "v2", "v3"] {
+        for resource in RESOURCES {
+            let base = format!("/{}/{}", version, resource);
+            let routes = [
+                (base.clone(), HttpMethod::GET),
+                (base.clone(), HttpMethod::POST),
+                (format!("{}/search", base), HttpMethod::GET),
+                (format!("{}/:id", base), HttpMethod::GET),
+                (format!("{}/:id", base), HttpMethod::PUT),
+                (format!("{}/:id", base), HttpMethod::DELETE),
+                (format!("{}/:id/history", base), HttpMethod::GET),
+                (format!("{}/:id/attachments/*path", base), HttpMethod::GET),
+            ];
+            for (path, method) in routes {
+                router.insert(None, &path, method, ok_handler).unwrap();
+            }
+        }
+    }
+    router
+}
+
+fn bench_router(c: &mut Criterion) {
+    let router = build_router();
+    let mut group = c.benchmark_group("find_route");
+
+    let lookups = [
+        ("static", "/v3/files/search"),
+        ("param", "/v3/files/42/history"),
+        ("wildcard", "/v3/files/42/attachments/2024/01/report.pdf"),
+        ("not_found", "/v3/files/42/unknown"),
+    ];
+    for (name, path) in lookups {
+        group.bench_function(name, |b| {
+            b.iter(|| router.find_route(black_box(path), &HttpMethod::GET, None))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_router);
+criterion_main!(benches);
@@ -17,7 +17,7 @@ type DatabaseTable = (String, Vec<(String, String, bool)>);
 /// - MySql (same as MariaDB)
 /// - Sqlite
 #[derive(Debug)]
-enum DatabasePool {
+pub(crate) enum DatabasePool {
     Postgres(Pool<Postgres>),
     MySql(Pool<MySql>),
     Sqlite(Pool<Sqlite>),
@@ -79,6 +79,14 @@ impl Database {
         })
     }
 
+    /// Get the connection pool of the Database
+    ///
+    /// ## Returns
+    /// - &DatabasePool
+    pub(crate) fn pool(&self) -> &DatabasePool {
+        &self.pool
+    }
+
     /// Close the Database
     pub async fn close(&self) {
         match &*self.pool {
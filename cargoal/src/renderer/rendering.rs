@@ -1,4 +1,6 @@
-use minijinja::{Environment, Value};
+use crate::routes::routing::UrlGenerator;
+use minijinja::value::Kwargs;
+use minijinja::{Environment, Error, ErrorKind, State, Value};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -6,6 +8,12 @@ use std::path::PathBuf;
 /// Define the Context type
 pub type Context = HashMap<String, Value>;
 
+/// Context variable holding the CSRF token of the request, read by `csrf_token()`
+pub(crate) const CSRF_TOKEN_VAR: &str = "__csrf_token";
+
+/// Context variable holding the CSP nonce of the request, read by `csp_nonce()`
+pub(crate) const CSP_NONCE_VAR: &str = "__csp_nonce";
+
 /// Define the TemplateRenderer struct
 /// ## Fields
 /// - env: Environment<'static>
@@ -33,11 +41,66 @@ impl TemplateRenderer {
             }
         });
 
+        // The CSRF token is added to the context by the routes when the Csrf middleware
+        // gave one to the request
+        env.add_function("csrf_token", |state: &State| -> Result<Value, Error> {
+            state
+                .lookup(CSRF_TOKEN_VAR)
+                .filter(|token| !token.is_undefined())
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidOperation,
+                        "csrf_token() requires the Csrf middleware",
+                    )
+                })
+        });
+
+        // Same for the nonce of the Content-Security-Policy set by SecurityHeaders
+        env.add_function("csp_nonce", |state: &State| -> Result<Value, Error> {
+            state
+                .lookup(CSP_NONCE_VAR)
+                .filter(|nonce| !nonce.is_undefined())
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidOperation,
+                        "csp_nonce() requires a SecurityHeaders policy containing {nonce}",
+                    )
+                })
+        });
+
         Self::load_templates(&mut env, &template_dirs);
 
         Self { env }
     }
 
+    /// Expose the URL of the named routes to the templates
+    /// `{{ url_for("user.show", id=42) }}`
+    /// ## Args
+    /// - self
+    /// - urls: UrlGenerator
+    /// ## Returns
+    /// - TemplateRenderer
+    pub(crate) fn with_url_generator(mut self, urls: UrlGenerator) -> Self {
+        self.env.add_function(
+            "url_for",
+            move |name: String, kwargs: Kwargs| -> Result<Value, Error> {
+                let params = kwargs
+                    .args()
+                    .map(|key| {
+                        kwargs
+                            .get::<Value>(key)
+                            .map(|value| (key.to_string(), value.to_string()))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                // The URL is percent-encoded, it cannot contain HTML special characters
+                urls.url_for(&name, params)
+                    .map(Value::from_safe_string)
+                    .map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))
+            },
+        );
+        self
+    }
+
     /// Load templates into the Environment
     /// ## Args
     /// - env: &mut Environment<'static>
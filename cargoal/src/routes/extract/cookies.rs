@@ -0,0 +1,146 @@
+use super::from_request::{rejection, FromRequest};
+use super::state::State;
+use crate::routes::http::cookie::Cookie;
+use crate::routes::http::key::Key;
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Get the Key of the Server for a Request
+/// ## Args
+/// - req: &Request
+/// ## Returns
+/// - Option<Arc<Key>> (None when no secret key is configured)
+fn secret_key(req: &Request) -> Option<Arc<Key>> {
+    State::<Key>::from_request(req).ok().map(|State(key)| key)
+}
+
+/// Build the Response sent when a jar is used without a secret key
+/// ## Returns
+/// - Response
+fn missing_secret_key() -> Response {
+    rejection(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "No secret key is configured".to_string(),
+    )
+}
+
+/// Define the SignedJar extractor
+/// Reads and writes cookies the client can see but not modify, signed with the Key
+/// of the Server (`ServerHandle::with_secret_key`)
+/// ## Fields
+/// - key: Arc<Key>
+/// - cookies: HashMap<String, String> (the raw cookies of the Request)
+#[derive(Debug, Clone)]
+pub struct SignedJar {
+    key: Arc<Key>,
+    cookies: HashMap<String, String>,
+}
+
+/// Implement the SignedJar struct
+impl SignedJar {
+    /// Create a SignedJar holding the cookies of a Request
+    /// ## Args
+    /// - key: Arc<Key>
+    /// - req: &Request
+    /// ## Returns
+    /// - SignedJar
+    pub fn new(key: Arc<Key>, req: &Request) -> Self {
+        Self {
+            key,
+            cookies: req.cookies(),
+        }
+    }
+
+    /// Get the value of a signed cookie
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Option<String> (None if the cookie is missing or was modified)
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.cookies
+            .get(name)
+            .and_then(|signed| self.key.verify(name, signed))
+    }
+
+    /// Sign a cookie, to send it with `Response::set_cookie`
+    /// ## Args
+    /// - self
+    /// - cookie: Cookie
+    /// ## Returns
+    /// - Cookie
+    pub fn sign(&self, cookie: Cookie) -> Cookie {
+        let value = self.key.sign(cookie.name(), cookie.value());
+        cookie.with_value(&value)
+    }
+}
+
+/// Implement FromRequest for SignedJar
+impl FromRequest for SignedJar {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        let key = secret_key(req).ok_or_else(missing_secret_key)?;
+        Ok(Self::new(key, req))
+    }
+}
+
+/// Define the PrivateJar extractor
+/// Reads and writes cookies the client can neither read nor modify, encrypted with the
+/// Key of the Server (`ServerHandle::with_secret_key`)
+/// ## Fields
+/// - key: Arc<Key>
+/// - cookies: HashMap<String, String> (the raw cookies of the Request)
+#[derive(Debug, Clone)]
+pub struct PrivateJar {
+    key: Arc<Key>,
+    cookies: HashMap<String, String>,
+}
+
+/// Implement the PrivateJar struct
+impl PrivateJar {
+    /// Create a PrivateJar holding the cookies of a Request
+    /// ## Args
+    /// - key: Arc<Key>
+    /// - req: &Request
+    /// ## Returns
+    /// - PrivateJar
+    pub fn new(key: Arc<Key>, req: &Request) -> Self {
+        Self {
+            key,
+            cookies: req.cookies(),
+        }
+    }
+
+    /// Get the decrypted value of a private cookie
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Option<String> (None if the cookie is missing or was modified)
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.cookies
+            .get(name)
+            .and_then(|encrypted| self.key.decrypt(name, encrypted))
+    }
+
+    /// Encrypt a cookie, to send it with `Response::set_cookie`
+    /// ## Args
+    /// - self
+    /// - cookie: Cookie
+    /// ## Returns
+    /// - Cookie
+    pub fn encrypt(&self, cookie: Cookie) -> Cookie {
+        let value = self.key.encrypt(cookie.name(), cookie.value());
+        cookie.with_value(&value)
+    }
+}
+
+/// Implement FromRequest for PrivateJar
+impl FromRequest for PrivateJar {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        let key = secret_key(req).ok_or_else(missing_secret_key)?;
+        Ok(Self::new(key, req))
+    }
+}
@@ -0,0 +1,35 @@
+use super::from_request::{has_content_type, rejection, FromRequest};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use serde::de::DeserializeOwned;
+
+/// Define the Form extractor
+/// Deserializes an `application/x-www-form-urlencoded` request body into `T`
+/// ## Fields
+/// - 0: T
+#[derive(Debug, Clone, PartialEq)]
+pub struct Form<T>(pub T);
+
+/// Implement FromRequest for Form
+/// - 415 when the Content-Type is not a url-encoded form
+/// - 422 when the form does not match `T`
+impl<T: DeserializeOwned> FromRequest for Form<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        if !has_content_type(req, "application/x-www-form-urlencoded") {
+            return Err(rejection(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Expected a request with `Content-Type: application/x-www-form-urlencoded`"
+                    .to_string(),
+            ));
+        }
+
+        let body = req.body.as_deref().unwrap_or_default();
+        serde_urlencoded::from_bytes(body).map(Form).map_err(|err| {
+            rejection(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Invalid form body: {}", err),
+            )
+        })
+    }
+}
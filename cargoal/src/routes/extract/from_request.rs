@@ -0,0 +1,45 @@
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+
+/// Define the FromRequest trait
+/// Implemented by the types that handlers can take as arguments (extractors)
+pub trait FromRequest: Sized {
+    /// Extract the value from the Request
+    /// ## Args
+    /// - req: &Request
+    /// ## Returns
+    /// - Result<Self, Response> (the Response is sent back when the extraction fails)
+    // Rejections are rare, boxing them would burden every extractor
+    #[allow(clippy::result_large_err)]
+    fn from_request(req: &Request) -> Result<Self, Response>;
+}
+
+/// Implement FromRequest for optional extractors, which never fail
+impl<T: FromRequest> FromRequest for Option<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        Ok(T::from_request(req).ok())
+    }
+}
+
+/// Build the Response sent when an extractor rejects a Request
+/// ## Args
+/// - status_code: StatusCode
+/// - message: String
+/// ## Returns
+/// - Response
+pub(crate) fn rejection(status_code: StatusCode, message: String) -> Response {
+    Response::new(status_code, Some(message)).with_header("Content-Type", "text/plain")
+}
+
+/// Check if the Content-Type of a Request matches a MIME type (parameters are ignored)
+/// ## Args
+/// - req: &Request
+/// - mime: &str
+/// ## Returns
+/// - bool
+pub(crate) fn has_content_type(req: &Request, mime: &str) -> bool {
+    req.header("content-type")
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case(mime))
+}
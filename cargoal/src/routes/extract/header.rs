@@ -0,0 +1,91 @@
+use super::from_request::{rejection, FromRequest};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+
+/// Define the TypedHeader trait
+/// Implemented by the types that can be extracted with the Header extractor
+pub trait TypedHeader: Sized {
+    /// Name of the header
+    const NAME: &'static str;
+
+    /// Decode the value of the header
+    /// ## Args
+    /// - value: &str
+    /// ## Returns
+    /// - Option<Self> (None if the value is invalid)
+    fn decode(value: &str) -> Option<Self>;
+}
+
+/// Define the Header extractor
+/// Extracts and decodes the header `T::NAME`, answering 400 when it is missing or invalid
+/// ## Fields
+/// - 0: T
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header<T>(pub T);
+
+/// Implement FromRequest for Header
+impl<T: TypedHeader> FromRequest for Header<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        let value = req.header(T::NAME).ok_or_else(|| {
+            rejection(
+                StatusCode::BAD_REQUEST,
+                format!("Missing header '{}'", T::NAME),
+            )
+        })?;
+
+        T::decode(value).map(Header).ok_or_else(|| {
+            rejection(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid header '{}'", T::NAME),
+            )
+        })
+    }
+}
+
+/// Declare typed headers holding the raw header value
+macro_rules! string_headers {
+    ($(($name:ident, $header:expr);)+) => {
+        $(
+            #[doc = concat!("The `", $header, "` header")]
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct $name(pub String);
+
+            impl TypedHeader for $name {
+                const NAME: &'static str = $header;
+
+                fn decode(value: &str) -> Option<Self> {
+                    Some($name(value.to_string()))
+                }
+            }
+        )+
+    };
+}
+
+string_headers! {
+    (UserAgent, "User-Agent");
+    (ContentType, "Content-Type");
+    (Host, "Host");
+}
+
+/// The credentials of the `Authorization` header
+/// ## Fields
+/// - scheme: String (e.g. `Bearer`)
+/// - credentials: String
+#[derive(Debug, Clone, PartialEq)]
+pub struct Authorization {
+    pub scheme: String,
+    pub credentials: String,
+}
+
+impl TypedHeader for Authorization {
+    const NAME: &'static str = "Authorization";
+
+    fn decode(value: &str) -> Option<Self> {
+        let (scheme, credentials) = value.trim().split_once(' ')?;
+        Some(Authorization {
+            scheme: scheme.to_string(),
+            credentials: credentials.trim().to_string(),
+        })
+    }
+}
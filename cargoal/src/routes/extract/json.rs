@@ -0,0 +1,42 @@
+use super::from_request::{has_content_type, rejection, FromRequest};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+
+/// Define the Json extractor
+/// Deserializes a JSON request body into `T`
+/// ## Fields
+/// - 0: T
+#[derive(Debug, Clone, PartialEq)]
+pub struct Json<T>(pub T);
+
+/// Implement FromRequest for Json
+/// - 415 when the Content-Type is not JSON
+/// - 400 when the body is not valid JSON
+/// - 422 when the JSON does not match `T`
+impl<T: DeserializeOwned> FromRequest for Json<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        let is_json = has_content_type(req, "application/json")
+            || req
+                .header("content-type")
+                .and_then(|value| value.split(';').next())
+                .is_some_and(|value| value.trim().ends_with("+json"));
+        if !is_json {
+            return Err(rejection(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Expected a request with `Content-Type: application/json`".to_string(),
+            ));
+        }
+
+        let body = req.body.as_deref().unwrap_or_default();
+        serde_json::from_slice(body).map(Json).map_err(|err| {
+            let status_code = match err.classify() {
+                Category::Data => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            rejection(status_code, format!("Invalid JSON body: {}", err))
+        })
+    }
+}
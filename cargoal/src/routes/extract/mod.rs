@@ -0,0 +1,17 @@
+pub(crate) mod cookies;
+pub(crate) mod form;
+pub(crate) mod from_request;
+pub(crate) mod header;
+pub(crate) mod json;
+pub(crate) mod path;
+pub(crate) mod query;
+pub(crate) mod state;
+
+pub use cookies::{PrivateJar, SignedJar};
+pub use form::Form;
+pub use from_request::FromRequest;
+pub use header::{Authorization, ContentType, Header, Host, TypedHeader, UserAgent};
+pub use json::Json;
+pub use path::Path;
+pub use query::Query;
+pub use state::{Extension, State};
@@ -0,0 +1,322 @@
+use super::from_request::{rejection, FromRequest};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use serde::de::value::Error;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Error as _, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+/// Define the Path extractor
+/// Deserializes the path parameters of the route (`/users/:id`) into `T`: a struct
+/// whose fields are named after the parameters, a tuple of the parameters in the order
+/// of the path, or a single value when the route has one parameter
+/// ## Fields
+/// - 0: T
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path<T>(pub T);
+
+/// Implement FromRequest for Path, answering 400 when the parameters do not match `T`
+impl<T: DeserializeOwned> FromRequest for Path<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        let params: Vec<(&str, String)> = req
+            .path_params
+            .iter()
+            .map(|(key, value)| (key.as_str(), percent_decode(value)))
+            .collect();
+
+        T::deserialize(ParamsDeserializer { params: &params })
+            .map(Path)
+            .map_err(|err| {
+                rejection(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid path parameters: {}", err),
+                )
+            })
+    }
+}
+
+/// Define the ParamsDeserializer struct
+/// Deserializes all the path parameters: as a map, a sequence or a single value
+/// ## Fields
+/// - params: &[(&str, String)] (decoded, in the order of the path)
+struct ParamsDeserializer<'a> {
+    params: &'a [(&'a str, String)],
+}
+
+/// Implement the ParamsDeserializer struct
+impl<'a> ParamsDeserializer<'a> {
+    /// Get the deserializer of the only parameter
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Result<ValueDeserializer, Error> (Err if there is not exactly one parameter)
+    fn single(&self) -> Result<ValueDeserializer<'a>, Error> {
+        match self.params {
+            [(_, value)] => Ok(ValueDeserializer(value)),
+            params => Err(Error::custom(format!(
+                "expected 1 parameter, found {}",
+                params.len()
+            ))),
+        }
+    }
+}
+
+/// Forward the deserialization of single values to the only parameter
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+/// Implement the Deserializer trait for ParamsDeserializer
+impl<'de> de::Deserializer<'de> for ParamsDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(ParamsAccess {
+            params: self.params.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(ParamsAccess {
+            params: self.params.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        if self.params.len() != len {
+            return Err(Error::custom(format!(
+                "expected {} parameters, found {}",
+                len,
+                self.params.len()
+            )));
+        }
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.single()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_single! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_option
+        deserialize_unit deserialize_identifier deserialize_ignored_any
+    }
+}
+
+/// Define the ParamsAccess struct
+/// Visits the path parameters as the entries of a map or the elements of a sequence
+/// ## Fields
+/// - params: std::slice::Iter<(&str, String)>
+/// - value: Option<(&str, &str)> (name and value of the entry whose key was visited)
+struct ParamsAccess<'a> {
+    params: std::slice::Iter<'a, (&'a str, String)>,
+    value: Option<(&'a str, &'a str)>,
+}
+
+/// Implement the MapAccess trait for ParamsAccess
+impl<'de> MapAccess<'de> for ParamsAccess<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.params.next() {
+            Some((name, value)) => {
+                self.value = Some((name, value));
+                seed.deserialize((*name).into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (name, value) = self
+            .value
+            .take()
+            .ok_or_else(|| Error::custom("a value was requested before its key"))?;
+        seed.deserialize(ValueDeserializer(value))
+            .map_err(|err| Error::custom(format!("{}: {}", name, err)))
+    }
+}
+
+/// Implement the SeqAccess trait for ParamsAccess
+impl<'de> SeqAccess<'de> for ParamsAccess<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.params.next() {
+            Some((name, value)) => seed
+                .deserialize(ValueDeserializer(value))
+                .map(Some)
+                .map_err(|err| Error::custom(format!("{}: {}", name, err))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Define the ValueDeserializer struct
+/// Deserializes the value of one path parameter, parsing the numbers and booleans
+/// ## Fields
+/// - 0: &str
+struct ValueDeserializer<'a>(&'a str);
+
+/// Parse the value of a parameter for the deserialization of a type
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident: $ty:ty),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse::<$ty>() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(Error::custom(format!(
+                        "cannot parse `{}` as {}",
+                        self.0,
+                        stringify!($ty)
+                    ))),
+                }
+            }
+        )*
+    };
+}
+
+/// Implement the Deserializer trait for ValueDeserializer
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Decode the percent-encoded bytes of a path segment
+/// ## Args
+/// - value: &str
+/// ## Returns
+/// - String
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
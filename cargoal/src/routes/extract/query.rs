@@ -0,0 +1,26 @@
+use super::from_request::{rejection, FromRequest};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use serde::de::DeserializeOwned;
+
+/// Define the Query extractor
+/// Deserializes the query string of the Request into `T`
+/// ## Fields
+/// - 0: T
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query<T>(pub T);
+
+/// Implement FromRequest for Query, answering 400 when the query string does not match `T`
+impl<T: DeserializeOwned> FromRequest for Query<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        serde_urlencoded::from_str(req.query.as_deref().unwrap_or_default())
+            .map(Query)
+            .map_err(|err| {
+                rejection(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid query string: {}", err),
+                )
+            })
+    }
+}
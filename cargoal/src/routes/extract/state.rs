@@ -0,0 +1,123 @@
+use super::from_request::{rejection, FromRequest};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Define the AppState struct
+/// The values shared by every handler of a Server (`ServerHandle::with_state`)
+/// ## Fields
+/// - map: HashMap<TypeId, Arc<dyn Any + Send + Sync>>
+#[derive(Clone, Default)]
+pub(crate) struct AppState {
+    map: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+/// Implement the AppState struct
+impl AppState {
+    /// Insert a value, replacing the previous value of the same type
+    /// ## Args
+    /// - self
+    /// - value: T
+    pub(crate) fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.map.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Check if there is no value
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub(crate) fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Get the value of a type
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<Arc<T>>
+    pub(crate) fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| Arc::clone(value).downcast().ok())
+    }
+}
+
+/// Define the GroupState struct
+/// The values shared by the handlers of a group (`GroupBuilder::with_state`), looked up
+/// before the ones of the Server
+/// ## Fields
+/// - 0: Arc<AppState>
+pub(crate) struct GroupState(pub(crate) Arc<AppState>);
+
+/// Define the State extractor
+/// Gives a handler the value of type `T` registered with `ServerHandle::with_state`,
+/// answering 500 when the Server has no such value
+/// ## Fields
+/// - 0: Arc<T>
+pub struct State<T>(pub Arc<T>);
+
+/// Implement FromRequest for State
+impl<T: Send + Sync + 'static> FromRequest for State<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        let group_state = req.extensions.get::<GroupState>();
+        group_state
+            .and_then(|GroupState(state)| state.get::<T>())
+            .or_else(|| {
+                req.extensions
+                    .get::<Arc<AppState>>()
+                    .and_then(|state| state.get::<T>())
+            })
+            .map(State)
+            .ok_or_else(|| {
+                rejection(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("No state of type '{}' is configured", type_name::<T>()),
+                )
+            })
+    }
+}
+
+/// Implement the Clone trait for State (without requiring `T: Clone`)
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        State(Arc::clone(&self.0))
+    }
+}
+
+/// Implement the Deref trait to use a State as its value
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Define the Extension extractor
+/// Gives a handler a clone of the value of type `T` that a middleware inserted into
+/// `Request::extensions`, answering 500 when there is none
+/// ## Fields
+/// - 0: T
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extension<T>(pub T);
+
+/// Implement FromRequest for Extension
+impl<T: Clone + Send + Sync + 'static> FromRequest for Extension<T> {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        req.extensions
+            .get::<T>()
+            .cloned()
+            .map(Extension)
+            .ok_or_else(|| {
+                rejection(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("No request extension of type '{}'", type_name::<T>()),
+                )
+            })
+    }
+}
@@ -0,0 +1,102 @@
+use futures_core::Stream;
+use std::fmt;
+use std::pin::Pin;
+
+/// Define the BodyStream type
+/// A stream of body chunks, sent with `Transfer-Encoding: chunked`
+pub type BodyStream = Pin<Box<dyn Stream<Item = std::io::Result<Vec<u8>>> + Send>>;
+
+/// Define the Body enum
+/// ## Variants
+/// - Empty: no body
+/// - Bytes(Vec<u8>): a body fully held in memory
+/// - Stream(BodyStream): a body produced chunk by chunk
+pub enum Body {
+    Empty,
+    Bytes(Vec<u8>),
+    Stream(BodyStream),
+}
+
+/// Implement the Body enum
+impl Body {
+    /// Create a streaming Body
+    /// ## Args
+    /// - stream: S
+    /// ## Where
+    /// - S: Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static
+    /// ## Returns
+    /// - Body
+    pub fn from_stream<S>(stream: S) -> Self
+    where
+        S: Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static,
+    {
+        Body::Stream(Box::pin(stream))
+    }
+
+    /// Get the bytes of a Body held in memory
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<&[u8]> (None for streaming bodies)
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Body::Empty => Some(&[]),
+            Body::Bytes(bytes) => Some(bytes),
+            Body::Stream(_) => None,
+        }
+    }
+
+    /// Check if the Body is empty
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Body::Empty => true,
+            Body::Bytes(bytes) => bytes.is_empty(),
+            Body::Stream(_) => false,
+        }
+    }
+}
+
+/// Implement the Debug trait for Body
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Body::Empty => write!(f, "Body::Empty"),
+            Body::Bytes(bytes) => write!(f, "Body::Bytes({} bytes)", bytes.len()),
+            Body::Stream(_) => write!(f, "Body::Stream"),
+        }
+    }
+}
+
+impl From<Option<String>> for Body {
+    fn from(body: Option<String>) -> Self {
+        body.map_or(Body::Empty, Body::from)
+    }
+}
+
+impl From<String> for Body {
+    fn from(body: String) -> Self {
+        Body::Bytes(body.into_bytes())
+    }
+}
+
+impl From<&str> for Body {
+    fn from(body: &str) -> Self {
+        Body::Bytes(body.as_bytes().to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(body: Vec<u8>) -> Self {
+        Body::Bytes(body)
+    }
+}
+
+impl From<&[u8]> for Body {
+    fn from(body: &[u8]) -> Self {
+        Body::Bytes(body.to_vec())
+    }
+}
@@ -0,0 +1,283 @@
+use super::date::UtcDateTime;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Define the SameSite enum
+/// ## Variants
+/// - Strict: the cookie is only sent with same-site requests
+/// - Lax: the cookie is also sent when following a link to the site
+/// - None: the cookie is sent with cross-site requests (requires `Secure`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+/// Implement the Display trait for SameSite
+impl fmt::Display for SameSite {
+    /// Format the SameSite attribute
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        };
+        f.write_str(value)
+    }
+}
+
+/// Define the Cookie struct
+/// A cookie sent to the client with `Response::set_cookie`
+/// ## Fields
+/// - name: String
+/// - value: String
+/// - path: Option<String>
+/// - domain: Option<String>
+/// - expires: Option<SystemTime>
+/// - max_age: Option<Duration>
+/// - secure: bool
+/// - http_only: bool
+/// - same_site: Option<SameSite>
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    expires: Option<SystemTime>,
+    max_age: Option<Duration>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+/// Implement the Cookie struct
+impl Cookie {
+    /// Create a new session Cookie, without attributes
+    /// The value is sent as given: encode the values holding `;`, `,`, spaces or quotes
+    /// ## Args
+    /// - name: &str
+    /// - value: &str
+    /// ## Returns
+    /// - Cookie
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            path: None,
+            domain: None,
+            expires: None,
+            max_age: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// Get the name of the Cookie
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &str
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the value of the Cookie
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &str
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replace the value of the Cookie
+    /// ## Args
+    /// - self
+    /// - value: &str
+    /// ## Returns
+    /// - Cookie
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = value.to_string();
+        self
+    }
+
+    /// Restrict the Cookie to the paths under a path
+    /// ## Args
+    /// - self
+    /// - path: &str (e.g. `/`)
+    /// ## Returns
+    /// - Cookie
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    /// Send the Cookie to a domain and its subdomains
+    /// ## Args
+    /// - self
+    /// - domain: &str (e.g. `example.com`)
+    /// ## Returns
+    /// - Cookie
+    pub fn with_domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+
+    /// Set the time after which the client deletes the Cookie
+    /// ## Args
+    /// - self
+    /// - expires: SystemTime
+    /// ## Returns
+    /// - Cookie
+    pub fn with_expires(mut self, expires: SystemTime) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Set how long the client keeps the Cookie (takes precedence over Expires)
+    /// ## Args
+    /// - self
+    /// - max_age: Duration
+    /// ## Returns
+    /// - Cookie
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Only send the Cookie over HTTPS
+    /// ## Args
+    /// - self
+    /// - secure: bool
+    /// ## Returns
+    /// - Cookie
+    pub fn with_secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Hide the Cookie from the scripts of the page
+    /// ## Args
+    /// - self
+    /// - http_only: bool
+    /// ## Returns
+    /// - Cookie
+    pub fn with_http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    /// Set when the Cookie is sent with cross-site requests
+    /// ## Args
+    /// - self
+    /// - same_site: SameSite
+    /// ## Returns
+    /// - Cookie
+    pub fn with_same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Turn the Cookie into one deleting it on the client
+    /// The path and the domain are kept, they must be the ones the Cookie was set with
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Cookie
+    pub(crate) fn into_removal(mut self) -> Self {
+        self.value.clear();
+        self.max_age = Some(Duration::ZERO);
+        self.expires = Some(UNIX_EPOCH);
+        self
+    }
+
+    /// Check if two cookies are stored in the same place on the client
+    /// ## Args
+    /// - self
+    /// - other: &Cookie
+    /// ## Returns
+    /// - bool
+    pub(crate) fn same_slot(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.path == other.path && self.domain == other.domain
+    }
+}
+
+/// Implement the Display trait for Cookie
+/// Formats the value of a Set-Cookie header
+impl fmt::Display for Cookie {
+    /// Format the Cookie
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // CR, LF and `;` would end the header or the cookie
+        let clean = |value: &str| value.replace(['\r', '\n', ';'], "");
+
+        write!(f, "{}={}", clean(&self.name), clean(&self.value))?;
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", clean(path))?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={}", clean(domain))?;
+        }
+        if let Some(expires) = self.expires {
+            write!(
+                f,
+                "; Expires={}",
+                UtcDateTime::from_system_time(expires).to_http_date()
+            )?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse the value of a Cookie header
+/// The first value of a name is kept, as clients send the most specific cookie first
+/// ## Args
+/// - header: &str (e.g. `theme=dark; lang=en`)
+/// ## Returns
+/// - HashMap<String, String>
+pub(crate) fn parse_cookie_header(header: &str) -> HashMap<String, String> {
+    let mut cookies = HashMap::new();
+    for pair in header.split(';') {
+        let Some((name, value)) = pair.split_once('=') else {
+            continue;
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        cookies
+            .entry(name.to_string())
+            .or_insert_with(|| value.to_string());
+    }
+    cookies
+}
@@ -0,0 +1,114 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Define the UtcDateTime struct
+/// A point in time broken down into its UTC calendar fields
+/// ## Fields
+/// - year: i64
+/// - month: u32 (1 to 12)
+/// - day: u32 (1 to 31)
+/// - hour: u32
+/// - minute: u32
+/// - second: u32
+/// - millisecond: u32
+/// - weekday: u32 (0 for Sunday to 6 for Saturday)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct UtcDateTime {
+    pub(crate) year: i64,
+    pub(crate) month: u32,
+    pub(crate) day: u32,
+    pub(crate) hour: u32,
+    pub(crate) minute: u32,
+    pub(crate) second: u32,
+    pub(crate) millisecond: u32,
+    pub(crate) weekday: u32,
+}
+
+/// Implement the UtcDateTime struct
+impl UtcDateTime {
+    /// Break down a SystemTime, the times before the Unix epoch are clamped to it
+    /// ## Args
+    /// - time: SystemTime
+    /// ## Returns
+    /// - UtcDateTime
+    pub(crate) fn from_system_time(time: SystemTime) -> Self {
+        let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = elapsed.as_secs() as i64;
+        let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400) as u32);
+
+        // Civil date from the number of days since 1970-01-01 (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3_600,
+            minute: secs_of_day % 3_600 / 60,
+            second: secs_of_day % 60,
+            millisecond: elapsed.subsec_millis(),
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7) as u32,
+        }
+    }
+
+    /// Format the time as in the Common Log Format
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - String (e.g. `10/Oct/2000:13:55:36 +0000`)
+    pub(crate) fn to_clf(self) -> String {
+        format!(
+            "{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000",
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+
+    /// Format the time as RFC 3339
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - String (e.g. `2000-10-10T13:55:36.000Z`)
+    pub(crate) fn to_rfc3339(self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second, self.millisecond
+        )
+    }
+
+    /// Format the time as an HTTP date (IMF-fixdate)
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - String (e.g. `Tue, 10 Oct 2000 13:55:36 GMT`)
+    pub(crate) fn to_http_date(self) -> String {
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            WEEKDAYS[self.weekday as usize],
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+}
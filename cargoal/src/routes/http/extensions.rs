@@ -0,0 +1,84 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Define the Extensions struct
+/// A map holding at most one value per type, used to attach request-scoped data
+/// (authenticated user, request id, ...) to a Request
+/// ## Fields
+/// - map: HashMap<TypeId, Box<dyn Any + Send + Sync>>
+#[derive(Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+/// Implement the Extensions struct
+impl Extensions {
+    /// Create an empty Extensions map
+    /// ## Returns
+    /// - Extensions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, replacing the previous value of the same type
+    /// ## Args
+    /// - self
+    /// - value: T
+    /// ## Returns
+    /// - Option<T> (the previous value)
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast().ok().map(|previous| *previous))
+    }
+
+    /// Get a reference to the value of a type
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<&T>
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Get a mutable reference to the value of a type
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<&mut T>
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    /// Remove the value of a type
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<T>
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok().map(|value| *value))
+    }
+
+    /// Check if there is a value of a type
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+}
+
+/// Implement the Debug trait for Extensions
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Extensions({} values)", self.map.len())
+    }
+}
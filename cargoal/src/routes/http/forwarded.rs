@@ -0,0 +1,276 @@
+use super::request::Request;
+use std::fmt;
+use std::net::IpAddr;
+
+/// Define the ProxyError enum
+/// ## Variants
+/// - InvalidAddress(String): a trusted proxy is neither an IP address nor a CIDR range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyError {
+    InvalidAddress(String),
+}
+
+/// Implement the Display trait for ProxyError
+impl fmt::Display for ProxyError {
+    /// Format the ProxyError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyError::InvalidAddress(address) => write!(
+                f,
+                "Invalid trusted proxy '{}': expected an IP address or a CIDR range",
+                address
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {}
+
+/// Define the Network struct
+/// ## Fields
+/// - address: IpAddr
+/// - prefix: u32 (number of leading bits compared)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Network {
+    address: IpAddr,
+    prefix: u32,
+}
+
+/// Implement the Network struct
+impl Network {
+    /// Parse an IP address or a CIDR range
+    /// ## Args
+    /// - value: &str (e.g. `10.0.0.1`, `10.0.0.0/8` or `fd00::/8`)
+    /// ## Returns
+    /// - Option<Network>
+    fn parse(value: &str) -> Option<Self> {
+        let (address, prefix) = match value.trim().split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix.parse::<u32>().ok()?)),
+            None => (value.trim(), None),
+        };
+        let address = address.parse::<IpAddr>().ok()?.to_canonical();
+        let bits = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(bits);
+        (prefix <= bits).then_some(Self { address, prefix })
+    }
+
+    /// Check if an IP address is in the Network
+    /// ## Args
+    /// - self
+    /// - ip: IpAddr (canonical)
+    /// ## Returns
+    /// - bool
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.address, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Define the Hop struct
+/// What a proxy tells about the connection it received
+/// ## Fields
+/// - address: Option<IpAddr> (None for the unknown and obfuscated addresses)
+/// - proto: Option<String>
+/// - host: Option<String>
+#[derive(Debug, Default)]
+struct Hop {
+    address: Option<IpAddr>,
+    proto: Option<String>,
+    host: Option<String>,
+}
+
+/// Define the TrustedProxies struct
+/// The proxies whose forwarding headers are believed. Without trusted proxies, the
+/// client of a Request is the peer of its connection
+/// ## Fields
+/// - networks: Vec<Network>
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TrustedProxies {
+    networks: Vec<Network>,
+}
+
+/// Implement the TrustedProxies struct
+impl TrustedProxies {
+    /// Parse the trusted proxies
+    /// ## Args
+    /// - proxies: &[&str] (IP addresses and CIDR ranges)
+    /// ## Returns
+    /// - Result<TrustedProxies, ProxyError>
+    pub(crate) fn parse(proxies: &[&str]) -> Result<Self, ProxyError> {
+        let networks = proxies
+            .iter()
+            .map(|proxy| {
+                Network::parse(proxy).ok_or_else(|| ProxyError::InvalidAddress(proxy.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { networks })
+    }
+
+    /// Check if an IP address is a trusted proxy
+    /// ## Args
+    /// - self
+    /// - ip: IpAddr
+    /// ## Returns
+    /// - bool
+    fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    /// Resolve the client, scheme and host of a Request sent by a trusted proxy
+    /// The `Forwarded` header is used when present, else `X-Forwarded-For`,
+    /// `X-Forwarded-Proto` and `X-Forwarded-Host`. The addresses are read from the
+    /// nearest proxy backwards: the client is the first address that is not a trusted
+    /// proxy, as the ones before it may have been made up by the client
+    /// ## Args
+    /// - self
+    /// - req: &mut Request
+    /// ## Side Effects
+    /// - Sets the client IP address, the scheme and the host of the Request
+    pub(crate) fn resolve(&self, req: &mut Request) {
+        let Some(peer) = req.remote_addr else {
+            return;
+        };
+        if !self.contains(peer.ip()) {
+            return;
+        }
+
+        let (hops, forwarded) = match req.header("forwarded") {
+            Some(value) => (parse_forwarded(value), true),
+            None => {
+                let hops = req
+                    .header("x-forwarded-for")
+                    .map(|value| {
+                        value
+                            .split(',')
+                            .map(|node| Hop {
+                                address: parse_node(node),
+                                ..Hop::default()
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (hops, false)
+            }
+        };
+
+        let mut client = None;
+        for hop in hops.iter().rev() {
+            let Some(address) = hop.address else {
+                break;
+            };
+            client = Some(hop);
+            if !self.contains(address) {
+                break;
+            }
+        }
+
+        // The X-Forwarded-Proto and -Host headers are set by the nearest proxy
+        let (proto, host) = if forwarded {
+            (
+                client.and_then(|hop| hop.proto.clone()),
+                client.and_then(|hop| hop.host.clone()),
+            )
+        } else {
+            (
+                last_value(req.header("x-forwarded-proto")),
+                last_value(req.header("x-forwarded-host")),
+            )
+        };
+
+        if let Some(address) = client.and_then(|hop| hop.address) {
+            req.client_ip = Some(address);
+        }
+        match proto.map(|proto| proto.to_ascii_lowercase()).as_deref() {
+            Some("http") => req.scheme = "http",
+            Some("https") => req.scheme = "https",
+            _ => {}
+        }
+        if let Some(host) = host.filter(|host| is_valid_host(host)) {
+            req.forwarded_host = Some(host);
+        }
+    }
+}
+
+/// Parse a `Forwarded` header (RFC 7239)
+/// `Forwarded: for=192.0.2.60;proto=https;host=example.com, for="[2001:db8::17]:4711"`
+/// ## Args
+/// - value: &str
+/// ## Returns
+/// - Vec<Hop> (from the client to the nearest proxy)
+fn parse_forwarded(value: &str) -> Vec<Hop> {
+    value
+        .split(',')
+        .map(|element| {
+            let mut hop = Hop::default();
+            for pair in element.split(';') {
+                let Some((name, value)) = pair.split_once('=') else {
+                    continue;
+                };
+                let value = value.trim().trim_matches('"');
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "for" => hop.address = parse_node(value),
+                    "proto" => hop.proto = Some(value.to_string()),
+                    "host" => hop.host = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+            hop
+        })
+        .collect()
+}
+
+/// Parse the address of a node, with an optional port
+/// ## Args
+/// - node: &str (e.g. `192.0.2.43`, `192.0.2.43:47011` or `[2001:db8::17]:4711`)
+/// ## Returns
+/// - Option<IpAddr> (None for `unknown` and the obfuscated identifiers)
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    let address = match node.strip_prefix('[') {
+        Some(rest) => rest.split_once(']')?.0,
+        None if node.matches(':').count() == 1 => node.split_once(':')?.0,
+        None => node,
+    };
+    address.parse::<IpAddr>().ok().map(|ip| ip.to_canonical())
+}
+
+/// Get the last value of a comma-separated header
+/// ## Args
+/// - value: Option<&str>
+/// ## Returns
+/// - Option<String>
+fn last_value(value: Option<&str>) -> Option<String> {
+    value?
+        .rsplit(',')
+        .next()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Check that a forwarded host is a host name or address with an optional port
+/// ## Args
+/// - host: &str
+/// ## Returns
+/// - bool
+fn is_valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+}
@@ -0,0 +1,195 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{aead, hkdf, hmac};
+use std::fmt;
+
+/// Minimum length of a secret, in bytes
+const MIN_SECRET_LEN: usize = 32;
+
+/// Length of the nonce of the encrypted values, in bytes
+const NONCE_LEN: usize = 12;
+
+/// Define the KeyError enum
+/// ## Variants
+/// - TooShort(usize): the secret has fewer than 32 bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    TooShort(usize),
+}
+
+/// Implement the Display trait for KeyError
+impl fmt::Display for KeyError {
+    /// Format the KeyError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::TooShort(len) => write!(
+                f,
+                "The secret key has {} bytes, at least {} are required",
+                len, MIN_SECRET_LEN
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+/// Define the Key struct
+/// The secret of the application, signing and encrypting the values sent to the clients
+/// (signed and private cookies). Set it with `ServerHandle::with_secret_key`
+/// ## Fields
+/// - signing: hmac::Key (HMAC-SHA256)
+/// - encryption: aead::LessSafeKey (AES-256-GCM)
+pub struct Key {
+    signing: hmac::Key,
+    encryption: aead::LessSafeKey,
+}
+
+/// Implement the Key struct
+impl Key {
+    /// Derive a Key from a secret
+    /// The signing and encryption keys are derived separately with HKDF-SHA256
+    /// ## Args
+    /// - secret: &[u8] (at least 32 random bytes, kept out of the source code)
+    /// ## Returns
+    /// - Result<Key, KeyError>
+    pub fn from_secret(secret: &[u8]) -> Result<Self, KeyError> {
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(KeyError::TooShort(secret.len()));
+        }
+
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, b"cargoal key derivation").extract(secret);
+        let signing = prk
+            .expand(&[b"signing"], hmac::HMAC_SHA256)
+            .map(hmac::Key::from)
+            .expect("HKDF output length is valid for HMAC-SHA256");
+        let encryption = prk
+            .expand(&[b"encryption"], &aead::AES_256_GCM)
+            .map(aead::UnboundKey::from)
+            .map(aead::LessSafeKey::new)
+            .expect("HKDF output length is valid for AES-256-GCM");
+
+        Ok(Self {
+            signing,
+            encryption,
+        })
+    }
+
+    /// Generate a random Key
+    /// The values signed or encrypted with it cannot be read after a restart
+    /// ## Returns
+    /// - Key
+    /// ## Panics
+    /// - If the system random number generator fails
+    pub fn generate() -> Self {
+        let mut secret = [0u8; 64];
+        SystemRandom::new()
+            .fill(&mut secret)
+            .expect("the system random number generator failed");
+        Self::from_secret(&secret).expect("the generated secret is long enough")
+    }
+
+    /// Sign a value, binding it to a name
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - value: &str
+    /// ## Returns
+    /// - String (`<value>.<signature>`)
+    pub(crate) fn sign(&self, name: &str, value: &str) -> String {
+        let tag = hmac::sign(&self.signing, &Self::signed_message(name, value));
+        format!("{}.{}", value, URL_SAFE_NO_PAD.encode(tag.as_ref()))
+    }
+
+    /// Verify a value signed with `sign`
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - signed: &str
+    /// ## Returns
+    /// - Option<String> (None if the value or its name was modified)
+    pub(crate) fn verify(&self, name: &str, signed: &str) -> Option<String> {
+        let (value, tag) = signed.rsplit_once('.')?;
+        let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+        hmac::verify(&self.signing, &Self::signed_message(name, value), &tag).ok()?;
+        Some(value.to_string())
+    }
+
+    /// Encrypt and authenticate a value, binding it to a name
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - value: &str
+    /// ## Returns
+    /// - String (the URL-safe base64 of the nonce, the ciphertext and the tag)
+    /// ## Panics
+    /// - If the system random number generator fails
+    pub(crate) fn encrypt(&self, name: &str, value: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .expect("the system random number generator failed");
+
+        let mut sealed = value.as_bytes().to_vec();
+        self.encryption
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(name.as_bytes()),
+                &mut sealed,
+            )
+            .expect("the value is small enough to be encrypted");
+
+        let mut output = nonce.to_vec();
+        output.extend_from_slice(&sealed);
+        URL_SAFE_NO_PAD.encode(output)
+    }
+
+    /// Decrypt a value encrypted with `encrypt`
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - encrypted: &str
+    /// ## Returns
+    /// - Option<String> (None if the value or its name was modified)
+    pub(crate) fn decrypt(&self, name: &str, encrypted: &str) -> Option<String> {
+        let mut data = URL_SAFE_NO_PAD.decode(encrypted).ok()?;
+        if data.len() < NONCE_LEN {
+            return None;
+        }
+        let mut sealed = data.split_off(NONCE_LEN);
+        let nonce = aead::Nonce::try_assume_unique_for_key(&data).ok()?;
+        let value = self
+            .encryption
+            .open_in_place(nonce, aead::Aad::from(name.as_bytes()), &mut sealed)
+            .ok()?;
+        String::from_utf8(value.to_vec()).ok()
+    }
+
+    /// Build the message authenticated by a signature
+    /// ## Args
+    /// - name: &str
+    /// - value: &str
+    /// ## Returns
+    /// - Vec<u8> (`<name>=<value>`)
+    fn signed_message(name: &str, value: &str) -> Vec<u8> {
+        format!("{}={}", name, value).into_bytes()
+    }
+}
+
+/// Implement the Debug trait for Key without showing the secret
+impl fmt::Debug for Key {
+    /// Format the Key
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
@@ -1,7 +1,23 @@
+pub(crate) mod body;
+pub(crate) mod cookie;
+pub(crate) mod date;
+pub(crate) mod extensions;
+pub(crate) mod forwarded;
+pub(crate) mod key;
 pub(crate) mod method;
+pub(crate) mod negotiation;
+pub(crate) mod parser;
 pub(crate) mod request;
 pub(crate) mod response;
+pub(crate) mod status;
 
+pub use body::{Body, BodyStream};
+pub use cookie::{Cookie, SameSite};
+pub use extensions::Extensions;
+pub use forwarded::ProxyError;
+pub use key::{Key, KeyError};
 pub use method::HttpMethod;
+pub use parser::ParseError;
 pub use request::Request;
 pub use response::Response;
+pub use status::{InvalidStatusCode, StatusCode};
@@ -0,0 +1,102 @@
+/// Define the MediaRange struct
+/// A media range of an `Accept` header with its quality value
+/// ## Fields
+/// - main_type: String
+/// - sub_type: String
+/// - quality: f32
+struct MediaRange {
+    main_type: String,
+    sub_type: String,
+    quality: f32,
+}
+
+/// Implement the MediaRange struct
+impl MediaRange {
+    /// Parse a media range (`type/subtype;q=0.8`)
+    /// ## Args
+    /// - value: &str
+    /// ## Returns
+    /// - Option<MediaRange> (None if the media range is malformed)
+    fn parse(value: &str) -> Option<MediaRange> {
+        let mut parts = value.split(';');
+        let (main_type, sub_type) = parts.next()?.trim().split_once('/')?;
+        if main_type.is_empty() || sub_type.is_empty() {
+            return None;
+        }
+
+        let quality = parts
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+            .map_or(Some(1.0), |(_, q)| q.trim().parse::<f32>().ok())?;
+
+        Some(MediaRange {
+            main_type: main_type.to_ascii_lowercase(),
+            sub_type: sub_type.to_ascii_lowercase(),
+            quality: quality.clamp(0.0, 1.0),
+        })
+    }
+
+    /// Get how specifically the media range matches a media type
+    /// ## Args
+    /// - self
+    /// - main_type: &str
+    /// - sub_type: &str
+    /// ## Returns
+    /// - Option<u8> (None if it does not match, 2 for an exact match, 1 for `type/*`, 0 for `*/*`)
+    fn specificity(&self, main_type: &str, sub_type: &str) -> Option<u8> {
+        match (self.main_type.as_str(), self.sub_type.as_str()) {
+            ("*", "*") => Some(0),
+            (m, "*") if m.eq_ignore_ascii_case(main_type) => Some(1),
+            (m, s) if m.eq_ignore_ascii_case(main_type) && s.eq_ignore_ascii_case(sub_type) => {
+                Some(2)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Get the quality an `Accept` header gives to a media type
+/// The most specific matching media range wins (RFC 9110 section 12.5.1)
+/// ## Args
+/// - ranges: &[MediaRange]
+/// - media_type: &str
+/// ## Returns
+/// - f32 (0.0 if the media type is not acceptable)
+fn quality_of(ranges: &[MediaRange], media_type: &str) -> f32 {
+    let (main_type, sub_type) = media_type.split_once('/').unwrap_or((media_type, ""));
+    ranges
+        .iter()
+        .filter_map(|range| {
+            range
+                .specificity(main_type, sub_type)
+                .map(|specificity| (specificity, range.quality))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+        .map_or(0.0, |(_, quality)| quality)
+}
+
+/// Choose the media type to answer with according to an `Accept` header
+/// ## Args
+/// - accept: Option<&str>
+/// - available: &[&'a str] (in order of preference of the server)
+/// ## Returns
+/// - Option<&'a str> (None if none of the media types is acceptable)
+pub(crate) fn negotiate<'a>(accept: Option<&str>, available: &[&'a str]) -> Option<&'a str> {
+    // A missing or unparsable header accepts everything
+    let ranges: Vec<MediaRange> = accept
+        .map(|accept| accept.split(',').filter_map(MediaRange::parse).collect())
+        .unwrap_or_default();
+    if ranges.is_empty() {
+        return available.first().copied();
+    }
+
+    let mut best: Option<(&'a str, f32)> = None;
+    for &media_type in available {
+        let quality = quality_of(&ranges, media_type);
+        if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+            best = Some((media_type, quality));
+        }
+    }
+
+    best.map(|(media_type, _)| media_type)
+}
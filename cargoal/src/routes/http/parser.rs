@@ -0,0 +1,412 @@
+use super::request::Request;
+use super::{HttpMethod, StatusCode};
+use std::collections::HashMap;
+use std::fmt;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+/// Maximum size of a single line of the request head (request line or header)
+const MAX_LINE_SIZE: usize = 8 * 1024;
+
+/// Maximum number of headers accepted in a request
+const MAX_HEADERS: usize = 100;
+
+/// Define the ParseError enum
+/// ## Variants
+/// - Io(std::io::Error): the connection failed while reading
+/// - UnexpectedEof: the connection was closed in the middle of a request
+/// - InvalidRequestLine: the request line is not `METHOD target HTTP/x.y`
+/// - UnsupportedVersion(String): the HTTP version is not HTTP/1.x
+/// - InvalidHeader: a header line is malformed
+/// - HeadTooLarge: a line of the head is too long or there are too many headers
+/// - InvalidContentLength: the Content-Length header is not a valid length
+/// - InvalidChunk: the chunked body is malformed
+/// - UnsupportedTransferEncoding(String): the Transfer-Encoding is not chunked
+/// - BodyTooLarge: the body exceeds the maximum body size
+#[derive(Debug)]
+pub enum ParseError {
+    Io(std::io::Error),
+    UnexpectedEof,
+    InvalidRequestLine,
+    UnsupportedVersion(String),
+    InvalidHeader,
+    HeadTooLarge,
+    InvalidContentLength,
+    InvalidChunk,
+    UnsupportedTransferEncoding(String),
+    BodyTooLarge,
+}
+
+/// Implement the ParseError enum
+impl ParseError {
+    /// Get the HTTP status code to answer with
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - StatusCode
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ParseError::HeadTooLarge => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+            ParseError::BodyTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ParseError::UnsupportedTransferEncoding(_) => StatusCode::NOT_IMPLEMENTED,
+            ParseError::UnsupportedVersion(_) => StatusCode::HTTP_VERSION_NOT_SUPPORTED,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Implement the Display trait for ParseError
+impl fmt::Display for ParseError {
+    /// Format the ParseError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(err) => write!(f, "I/O error: {}", err),
+            ParseError::UnexpectedEof => write!(f, "Connection closed mid-request"),
+            ParseError::InvalidRequestLine => write!(f, "Invalid request line"),
+            ParseError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported HTTP version: {}", version)
+            }
+            ParseError::InvalidHeader => write!(f, "Invalid header"),
+            ParseError::HeadTooLarge => write!(f, "Request header fields too large"),
+            ParseError::InvalidContentLength => write!(f, "Invalid Content-Length"),
+            ParseError::InvalidChunk => write!(f, "Invalid chunked body"),
+            ParseError::UnsupportedTransferEncoding(encoding) => {
+                write!(f, "Unsupported Transfer-Encoding: {}", encoding)
+            }
+            ParseError::BodyTooLarge => write!(f, "Payload too large"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Implement the From trait to convert I/O errors into ParseError
+impl From<std::io::Error> for ParseError {
+    fn from(err: std::io::Error) -> Self {
+        ParseError::Io(err)
+    }
+}
+
+/// Read a line terminated by LF, without its CRLF/LF terminator
+/// ## Args
+/// - reader: &mut R
+/// ## Returns
+/// - Result<Option<String>, ParseError> (None on EOF before any byte)
+async fn read_line<R>(reader: &mut R) -> Result<Option<String>, ParseError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = Vec::new();
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            if line.is_empty() {
+                return Ok(None);
+            }
+            return Err(ParseError::UnexpectedEof);
+        }
+
+        let (done, used) = match available.iter().position(|&b| b == b'\n') {
+            Some(pos) => (true, pos + 1),
+            None => (false, available.len()),
+        };
+        line.extend_from_slice(&available[..used]);
+        reader.consume(used);
+
+        if line.len() > MAX_LINE_SIZE {
+            return Err(ParseError::HeadTooLarge);
+        }
+        if done {
+            break;
+        }
+    }
+
+    line.pop();
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| ParseError::InvalidHeader)
+}
+
+/// Read a line that must be present (EOF is an error)
+/// ## Args
+/// - reader: &mut R
+/// ## Returns
+/// - Result<String, ParseError>
+async fn expect_line<R>(reader: &mut R) -> Result<String, ParseError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    read_line(reader).await?.ok_or(ParseError::UnexpectedEof)
+}
+
+/// Read an HTTP/1.x request from a buffered reader
+/// ## Args
+/// - reader: &mut R
+/// - max_body_size: usize
+/// ## Returns
+/// - Result<Option<Request>, ParseError> (None if the connection was closed before a new request)
+pub(crate) async fn read_request<R>(
+    reader: &mut R,
+    max_body_size: usize,
+) -> Result<Option<Request>, ParseError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    // Skip the empty lines allowed before a request line (RFC 9112 section 2.2)
+    let request_line = loop {
+        match read_line(reader).await? {
+            None => return Ok(None),
+            Some(line) if line.is_empty() => continue,
+            Some(line) => break line,
+        }
+    };
+
+    let mut parts = request_line.split(' ');
+    let (method, target, version) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version), None)
+            if !method.is_empty() && !target.is_empty() =>
+        {
+            (method, target, version)
+        }
+        _ => return Err(ParseError::InvalidRequestLine),
+    };
+    if !version.starts_with("HTTP/") {
+        return Err(ParseError::InvalidRequestLine);
+    }
+    if version != "HTTP/1.1" && version != "HTTP/1.0" {
+        return Err(ParseError::UnsupportedVersion(version.to_string()));
+    }
+
+    // The path and query parameters are extracted from the target
+    let mut request = Request::new(HttpMethod::from_str(method), target);
+    request.version = version.to_string();
+    request.headers = read_headers(reader).await?;
+    request.body = read_body(reader, &request.headers, max_body_size).await?;
+
+    Ok(Some(request))
+}
+
+/// Read the header fields of a request until the empty line
+/// ## Args
+/// - reader: &mut R
+/// ## Returns
+/// - Result<HashMap<String, String>, ParseError> (names are lowercase, repeated fields are joined)
+async fn read_headers<R>(reader: &mut R) -> Result<HashMap<String, String>, ParseError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut headers: HashMap<String, String> = HashMap::new();
+    let mut count = 0;
+
+    loop {
+        let line = expect_line(reader).await?;
+        if line.is_empty() {
+            return Ok(headers);
+        }
+
+        count += 1;
+        if count > MAX_HEADERS {
+            return Err(ParseError::HeadTooLarge);
+        }
+
+        // Obsolete line folding and whitespace before the colon are rejected
+        let (name, value) = line.split_once(':').ok_or(ParseError::InvalidHeader)?;
+        if name.is_empty() || name.starts_with([' ', '\t']) || name.ends_with([' ', '\t']) {
+            return Err(ParseError::InvalidHeader);
+        }
+
+        let name = name.to_ascii_lowercase();
+        let value = value.trim_matches([' ', '\t']);
+        match headers.get_mut(&name) {
+            Some(existing) => {
+                // The cookie pairs are separated with semicolons, other lists with commas
+                existing.push_str(if name == "cookie" { "; " } else { ", " });
+                existing.push_str(value);
+            }
+            None => {
+                headers.insert(name, value.to_string());
+            }
+        }
+    }
+}
+
+/// Read the body of a request according to its framing headers
+/// ## Args
+/// - reader: &mut R
+/// - headers: &HashMap<String, String>
+/// - max_body_size: usize
+/// ## Returns
+/// - Result<Option<Vec<u8>>, ParseError>
+async fn read_body<R>(
+    reader: &mut R,
+    headers: &HashMap<String, String>,
+    max_body_size: usize,
+) -> Result<Option<Vec<u8>>, ParseError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    if let Some(encoding) = headers.get("transfer-encoding") {
+        // A request with both framings could be used for request smuggling
+        if headers.contains_key("content-length") {
+            return Err(ParseError::InvalidContentLength);
+        }
+        if !encoding.eq_ignore_ascii_case("chunked") {
+            return Err(ParseError::UnsupportedTransferEncoding(encoding.clone()));
+        }
+        return read_chunked_body(reader, max_body_size).await.map(Some);
+    }
+
+    let length = match headers.get("content-length") {
+        Some(value) => parse_content_length(value)?,
+        None => return Ok(None),
+    };
+    if length > max_body_size {
+        return Err(ParseError::BodyTooLarge);
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.map_err(|err| {
+        if err.kind() == std::io::ErrorKind::UnexpectedEof {
+            ParseError::UnexpectedEof
+        } else {
+            ParseError::Io(err)
+        }
+    })?;
+
+    Ok(Some(body))
+}
+
+/// Parse a Content-Length value (repeated identical values are accepted)
+/// ## Args
+/// - value: &str
+/// ## Returns
+/// - Result<usize, ParseError>
+fn parse_content_length(value: &str) -> Result<usize, ParseError> {
+    let mut lengths = value.split(',').map(|v| {
+        let v = v.trim();
+        if v.is_empty() || !v.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseError::InvalidContentLength);
+        }
+        v.parse::<usize>()
+            .map_err(|_| ParseError::InvalidContentLength)
+    });
+
+    let first = lengths.next().ok_or(ParseError::InvalidContentLength)??;
+    for length in lengths {
+        if length? != first {
+            return Err(ParseError::InvalidContentLength);
+        }
+    }
+
+    Ok(first)
+}
+
+/// Read a body sent with `Transfer-Encoding: chunked`
+/// ## Args
+/// - reader: &mut R
+/// - max_body_size: usize
+/// ## Returns
+/// - Result<Vec<u8>, ParseError>
+async fn read_chunked_body<R>(reader: &mut R, max_body_size: usize) -> Result<Vec<u8>, ParseError>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut body = Vec::new();
+
+    loop {
+        let size_line = expect_line(reader).await?;
+        let size_str = size_line
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim_matches([' ', '\t']);
+        if size_str.is_empty() || !size_str.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseError::InvalidChunk);
+        }
+        let size = usize::from_str_radix(size_str, 16).map_err(|_| ParseError::InvalidChunk)?;
+
+        if size == 0 {
+            break;
+        }
+        if size > max_body_size.saturating_sub(body.len()) {
+            return Err(ParseError::BodyTooLarge);
+        }
+
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader
+            .read_exact(&mut body[start..])
+            .await
+            .map_err(|_| ParseError::UnexpectedEof)?;
+
+        if !expect_line(reader).await?.is_empty() {
+            return Err(ParseError::InvalidChunk);
+        }
+    }
+
+    // Discard the trailer fields
+    while !expect_line(reader).await?.is_empty() {}
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read a request from raw bytes
+    async fn parse(raw: &str, max_body_size: usize) -> Result<Option<Request>, ParseError> {
+        read_request(&mut raw.as_bytes(), max_body_size).await
+    }
+
+    #[tokio::test]
+    async fn test_chunked_body() {
+        let raw = "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                   5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\nTrailer: x\r\n\r\n";
+        let request = parse(raw, 1024).await.unwrap().unwrap();
+        assert_eq!(request.body.as_deref(), Some(&b"hello world"[..]));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_body_too_large() {
+        let raw = "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                   5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        assert!(matches!(
+            parse(raw, 10).await,
+            Err(ParseError::BodyTooLarge)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_chunk_size_overflow() {
+        // The size of the second chunk added to the body would overflow a usize
+        let raw = "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                   5\r\nhello\r\nffffffffffffffff\r\n world\r\n0\r\n\r\n";
+        assert!(matches!(
+            parse(raw, 1024).await,
+            Err(ParseError::BodyTooLarge)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_headers() {
+        let raw = "GET / HTTP/1.1\r\nAccept: text/html\r\nAccept: application/json\r\n\
+                   Cookie: a=1\r\nCookie: b=2; c=3\r\n\r\n";
+        let request = parse(raw, 1024).await.unwrap().unwrap();
+        assert_eq!(
+            request.header("accept"),
+            Some("text/html, application/json")
+        );
+        assert_eq!(request.header("cookie"), Some("a=1; b=2; c=3"));
+        assert_eq!(request.cookie("a").as_deref(), Some("1"));
+        assert_eq!(request.cookie("b").as_deref(), Some("2"));
+    }
+}
@@ -1,37 +1,224 @@
+use super::cookie::parse_cookie_header;
+use super::extensions::Extensions;
+use super::negotiation::negotiate;
 use super::HttpMethod;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
 
 /// Define the Request struct
 /// ## Fields
 /// - path: String
 /// - method: HttpMethod
-/// - body: Option<String>
-/// - params: std::collections::HashMap<String, String>
+/// - version: String
+/// - headers: std::collections::HashMap<String, String> (names are lowercase)
+/// - body: Option<Vec<u8>>
+/// - query: Option<String> (raw query string, without the `?`)
+/// - params: std::collections::HashMap<String, String> (query and path parameters)
+/// - path_params: Vec<(String, String)> (path parameters only, in the order of the path)
+/// - extensions: Extensions (request-scoped values set by the middlewares)
+/// - remote_addr: Option<SocketAddr> (address of the peer of the connection)
+/// - client_ip: Option<IpAddr> (address of the client, behind the trusted proxies)
+/// - scheme: &'static str (`http` or `https`, as used by the client)
+/// - forwarded_host: Option<String> (host requested from the trusted proxies)
 pub struct Request {
     pub path: String,
     pub method: HttpMethod,
-    pub body: Option<String>,
-    pub params: std::collections::HashMap<String, String>,
+    pub version: String,
+    pub headers: HashMap<String, String>,
+    pub body: Option<Vec<u8>>,
+    pub query: Option<String>,
+    pub params: HashMap<String, String>,
+    pub path_params: Vec<(String, String)>,
+    pub extensions: Extensions,
+    pub(crate) remote_addr: Option<SocketAddr>,
+    pub(crate) client_ip: Option<IpAddr>,
+    pub(crate) scheme: &'static str,
+    pub(crate) forwarded_host: Option<String>,
 }
 
-/// Parse a raw HTTP request string into a Request struct
+/// Implement the Request struct
+impl Request {
+    /// Create a new HTTP/1.1 Request without headers nor body
+    /// ## Args
+    /// - method: HttpMethod
+    /// - target: &str (path with an optional query string, e.g. `/search?q=rust`)
+    /// ## Returns
+    /// - Request
+    pub fn new(method: HttpMethod, target: &str) -> Self {
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (target, None),
+        };
+
+        Self {
+            path: path.to_string(),
+            method,
+            version: "HTTP/1.1".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            query: query.map(String::from),
+            params: parse_query(query),
+            path_params: Vec::new(),
+            extensions: Extensions::new(),
+            remote_addr: None,
+            client_ip: None,
+            scheme: "http",
+            forwarded_host: None,
+        }
+    }
+
+    /// Add a header to the Request
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - value: &str
+    /// ## Returns
+    /// - Request
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .insert(name.to_ascii_lowercase(), value.to_string());
+        self
+    }
+
+    /// Set the body of the Request
+    /// ## Args
+    /// - self
+    /// - body: B
+    /// ## Where
+    /// - B: Into<Vec<u8>>
+    /// ## Returns
+    /// - Request
+    pub fn with_body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Set the address of the peer of the connection (e.g. to test a handler using it)
+    /// ## Args
+    /// - self
+    /// - addr: SocketAddr
+    /// ## Returns
+    /// - Request
+    pub fn with_remote_addr(mut self, addr: SocketAddr) -> Self {
+        self.remote_addr = Some(addr);
+        self.client_ip = Some(addr.ip().to_canonical());
+        self
+    }
+
+    /// Get the value of a header (case-insensitive)
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Option<&str>
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Get the address of the peer of the connection: the client, or the last proxy
+    /// when the application is behind proxies (see `client_ip`)
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<SocketAddr> (None for the Requests not read from a connection)
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Get the IP address of the client, read from the forwarding headers when the
+    /// peer is a trusted proxy (`ServerHandle::with_trusted_proxies`)
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<IpAddr> (None for the Requests not read from a connection)
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+
+    /// Get the scheme used by the client, read from the forwarding headers when the
+    /// peer is a trusted proxy
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &str (`http` or `https`)
+    pub fn scheme(&self) -> &str {
+        self.scheme
+    }
+
+    /// Get the host requested by the client, read from the forwarding headers when
+    /// the peer is a trusted proxy, else from the Host header
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<&str> (with the port, if any)
+    pub fn host(&self) -> Option<&str> {
+        self.forwarded_host
+            .as_deref()
+            .or_else(|| self.header("host"))
+    }
+
+    /// Get the cookies sent with the Request
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - HashMap<String, String> (empty if there is no Cookie header)
+    pub fn cookies(&self) -> HashMap<String, String> {
+        self.header("cookie")
+            .map(parse_cookie_header)
+            .unwrap_or_default()
+    }
+
+    /// Get the value of a cookie sent with the Request
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Option<String>
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.cookies().remove(name)
+    }
+
+    /// Get the body of the Request as UTF-8 text
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<&str> (None if there is no body or if it is not valid UTF-8)
+    pub fn text(&self) -> Option<&str> {
+        self.body
+            .as_deref()
+            .and_then(|body| std::str::from_utf8(body).ok())
+    }
+
+    /// Choose the media type to answer with according to the Accept header
+    /// ## Args
+    /// - self
+    /// - available: &[&'a str] (media types in order of preference, e.g. `["application/json", "text/html"]`)
+    /// ## Returns
+    /// - Option<&'a str> (None if the client accepts none of them)
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        negotiate(self.header("accept"), available)
+    }
+
+    /// Check if the client accepts a media type
+    /// ## Args
+    /// - self
+    /// - media_type: &str
+    /// ## Returns
+    /// - bool
+    pub fn accepts(&self, media_type: &str) -> bool {
+        self.negotiate(&[media_type]).is_some()
+    }
+}
+
+/// Parse the query string of a request target into a map
 /// ## Args
-/// - request: &str
+/// - query: Option<&str>
 /// ## Returns
-/// - Request
-pub(crate) fn parse_request(request: &str) -> Request {
-    let lines: Vec<&str> = request.lines().collect();
-    let first_line = lines[0];
-    let parts: Vec<&str> = first_line.split_whitespace().collect();
-
-    // Extract the path and query parameters
-    let (path, query) = if let Some(pos) = parts[1].find('?') {
-        (&parts[1][..pos], Some(&parts[1][pos + 1..]))
-    } else {
-        (parts[1], None)
-    };
-
-    // Parse the query parameters
-    let query_params = query
+/// - HashMap<String, String>
+pub(crate) fn parse_query(query: Option<&str>) -> HashMap<String, String> {
+    query
         .map(|q| {
             q.split('&')
                 .filter_map(|pair| {
@@ -44,22 +231,5 @@ pub(crate) fn parse_request(request: &str) -> Request {
                 })
                 .collect()
         })
-        .unwrap_or_default();
-
-    println!("Parsed path: {}", path);
-    println!("Parsed query params: {:?}", query_params);
-
-    // Extract the body of the request
-    let body = if lines.len() > 1 {
-        Some(lines[lines.len() - 1].to_string())
-    } else {
-        None
-    };
-
-    Request {
-        path: path.to_string(),
-        method: HttpMethod::from_str(parts[0]),
-        body,
-        params: query_params,
-    }
+        .unwrap_or_default()
 }
@@ -1,29 +1,122 @@
+use super::{Body, Cookie, StatusCode};
+use futures_core::Stream;
+use serde::Serialize;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
 /// Define the Response struct
 /// ## Fields
-/// - status_code: u16
+/// - status_code: StatusCode
+/// - reason_phrase: Option<String> (overrides the canonical reason phrase)
 /// - headers: std::collections::HashMap<String, String>
-/// - body: Option<String>
+/// - cookies: Vec<Cookie> (each one sent in its own Set-Cookie header)
+/// - body: Body
 pub struct Response {
-    pub status_code: u16,
+    pub status_code: StatusCode,
+    pub reason_phrase: Option<String>,
     pub headers: std::collections::HashMap<String, String>,
-    pub body: Option<String>,
+    pub cookies: Vec<Cookie>,
+    pub body: Body,
 }
 
 impl Response {
     /// Create a new Response
     /// ## Args
-    /// - status_code: u16
-    /// - body: Option<String>
+    /// - status_code: StatusCode
+    /// - body: B
+    /// ## Where
+    /// - B: Into<Body> (Option<String>, String, &str, Vec<u8>, &[u8] or Body)
     /// ## Returns
     /// - Response
-    pub fn new(status_code: u16, body: Option<String>) -> Self {
+    pub fn new<B: Into<Body>>(status_code: StatusCode, body: B) -> Self {
         Self {
             status_code,
+            reason_phrase: None,
             headers: std::collections::HashMap::new(),
-            body,
+            cookies: Vec::new(),
+            body: body.into(),
         }
     }
 
+    /// Create a new Response with a streaming body
+    /// The body is sent with `Transfer-Encoding: chunked` as the stream produces it
+    /// ## Args
+    /// - status_code: StatusCode
+    /// - stream: St
+    /// ## Where
+    /// - St: Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static
+    /// ## Returns
+    /// - Response
+    pub fn stream<St>(status_code: StatusCode, stream: St) -> Self
+    where
+        St: Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static,
+    {
+        Self::new(status_code, Body::from_stream(stream))
+    }
+
+    /// Create a new `200 OK` Response with a JSON body
+    /// ## Args
+    /// - value: T
+    /// ## Where
+    /// - T: Serialize
+    /// ## Returns
+    /// - Response (500 Internal Server Error if the value cannot be serialized)
+    pub fn json<T: Serialize>(value: T) -> Self {
+        match serde_json::to_vec(&value) {
+            Ok(body) => Self::new(StatusCode::OK, body),
+            Err(err) => Self::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                serde_json::json!({ "error": format!("Failed to serialize the response: {}", err) })
+                    .to_string(),
+            ),
+        }
+        .with_header("Content-Type", "application/json")
+    }
+
+    /// Set the status code of the Response
+    /// ## Args
+    /// - self
+    /// - status_code: StatusCode
+    /// ## Returns
+    /// - Response
+    pub fn with_status(mut self, status_code: StatusCode) -> Self {
+        self.status_code = status_code;
+        self
+    }
+
+    /// Get the body as UTF-8 text
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<&str> (None for streaming or non UTF-8 bodies)
+    pub fn text(&self) -> Option<&str> {
+        self.body
+            .as_bytes()
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+    }
+
+    /// Set a custom reason phrase for the status line
+    /// ## Args
+    /// - self
+    /// - reason: &str (CR and LF characters are removed)
+    /// ## Returns
+    /// - Response
+    pub fn with_reason_phrase(mut self, reason: &str) -> Self {
+        self.reason_phrase = Some(reason.replace(['\r', '\n'], ""));
+        self
+    }
+
+    /// Get the reason phrase sent in the status line
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &str
+    pub fn reason(&self) -> &str {
+        self.reason_phrase
+            .as_deref()
+            .or_else(|| self.status_code.canonical_reason())
+            .unwrap_or_default()
+    }
+
     /// Add a header to the Response
     /// ## Args
     /// - self
@@ -32,28 +125,148 @@ impl Response {
     /// ## Returns
     /// - Response
     pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.headers.retain(|k, _| !k.eq_ignore_ascii_case(key));
         self.headers.insert(key.to_string(), value.to_string());
         self
     }
+
+    /// Get the value of a header (case-insensitive)
+    /// ## Args
+    /// - self
+    /// - key: &str
+    /// ## Returns
+    /// - Option<&str>
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Send a cookie to the client
+    /// ## Args
+    /// - self
+    /// - cookie: Cookie (replaces a cookie set with the same name, path and domain)
+    /// ## Returns
+    /// - Response
+    pub fn set_cookie(mut self, cookie: Cookie) -> Self {
+        self.cookies.retain(|c| !c.same_slot(&cookie));
+        self.cookies.push(cookie);
+        self
+    }
+
+    /// Delete a cookie on the client
+    /// ## Args
+    /// - self
+    /// - cookie: Cookie (with the path and domain the cookie was set with)
+    /// ## Returns
+    /// - Response
+    pub fn remove_cookie(self, cookie: Cookie) -> Self {
+        self.set_cookie(cookie.into_removal())
+    }
+
+    /// Get a cookie sent by the Response
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Option<&Cookie>
+    pub fn cookie(&self, name: &str) -> Option<&Cookie> {
+        self.cookies.iter().find(|cookie| cookie.name() == name)
+    }
 }
 
-/// Format a Response into a raw HTTP response string
+/// Write a Response to a client
+/// Bodies held in memory are framed with Content-Length, streaming bodies are
+/// sent with `Transfer-Encoding: chunked` (or until the connection is closed
+/// when `chunked` is false, for HTTP/1.0 clients)
+/// Answers to HEAD requests keep the headers the body would have, without the body
 /// ## Args
+/// - writer: &mut W
 /// - response: Response
+/// - chunked: bool
+/// - include_body: bool
+/// ## Where
+/// - W: AsyncWrite + Unpin
 /// ## Returns
-/// - String
-pub(crate) fn format_response(response: Response) -> String {
-    let mut response_str = format!("HTTP/1.1 {} OK\r\n", response.status_code);
+/// - std::io::Result<()>
+pub(crate) async fn write_response<W>(
+    writer: &mut W,
+    response: Response,
+    chunked: bool,
+    include_body: bool,
+) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\n",
+        response.status_code.as_u16(),
+        response.reason()
+    );
 
-    for (key, value) in response.headers {
-        response_str.push_str(&format!("{}: {}\r\n", key, value));
+    for (key, value) in &response.headers {
+        if key.eq_ignore_ascii_case("content-length")
+            || key.eq_ignore_ascii_case("transfer-encoding")
+        {
+            continue;
+        }
+        head.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    for cookie in &response.cookies {
+        head.push_str(&format!("Set-Cookie: {}\r\n", cookie));
+    }
+
+    // 1xx, 204 and 304 responses never have a body
+    let status = response.status_code;
+    let bodyless = status.is_informational()
+        || status == StatusCode::NO_CONTENT
+        || status == StatusCode::NOT_MODIFIED;
+
+    let body = if bodyless { Body::Empty } else { response.body };
+    match &body {
+        _ if bodyless => {}
+        Body::Stream(_) if chunked => head.push_str("Transfer-Encoding: chunked\r\n"),
+        Body::Stream(_) => {}
+        Body::Empty => head.push_str("Content-Length: 0\r\n"),
+        Body::Bytes(bytes) => head.push_str(&format!("Content-Length: {}\r\n", bytes.len())),
     }
+    head.push_str("\r\n");
 
-    response_str.push_str("\r\n");
+    if !include_body {
+        return writer.write_all(head.as_bytes()).await;
+    }
 
-    if let Some(body) = response.body {
-        response_str.push_str(&body);
+    match body {
+        Body::Empty => writer.write_all(head.as_bytes()).await?,
+        Body::Bytes(bytes) => {
+            let mut raw = head.into_bytes();
+            raw.extend_from_slice(&bytes);
+            writer.write_all(&raw).await?;
+        }
+        Body::Stream(mut stream) => {
+            writer.write_all(head.as_bytes()).await?;
+            while let Some(chunk) = std::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                let chunk = chunk?;
+                if chunk.is_empty() {
+                    continue;
+                }
+                if chunked {
+                    writer
+                        .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                        .await?;
+                    writer.write_all(&chunk).await?;
+                    writer.write_all(b"\r\n").await?;
+                } else {
+                    writer.write_all(&chunk).await?;
+                }
+                writer.flush().await?;
+            }
+            if chunked {
+                writer.write_all(b"0\r\n\r\n").await?;
+            }
+        }
     }
 
-    response_str
+    writer.flush().await
 }
@@ -0,0 +1,204 @@
+use std::fmt;
+
+/// Define the StatusCode struct
+/// An HTTP status code, with constants for the registered codes
+/// ## Fields
+/// - 0: u16
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Copy)]
+pub struct StatusCode(u16);
+
+/// Declare the registered status codes with their canonical reason phrase
+macro_rules! status_codes {
+    ($(($code:expr, $name:ident, $phrase:expr);)+) => {
+        impl StatusCode {
+            $(
+                #[doc = concat!("`", stringify!($code), " ", $phrase, "`")]
+                pub const $name: StatusCode = StatusCode($code);
+            )+
+
+            /// Get the canonical reason phrase of the status code
+            /// ## Args
+            /// - self
+            /// ## Returns
+            /// - Option<&'static str> (None for unregistered codes)
+            pub fn canonical_reason(&self) -> Option<&'static str> {
+                match self.0 {
+                    $($code => Some($phrase),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+status_codes! {
+    (100, CONTINUE, "Continue");
+    (101, SWITCHING_PROTOCOLS, "Switching Protocols");
+    (200, OK, "OK");
+    (201, CREATED, "Created");
+    (202, ACCEPTED, "Accepted");
+    (203, NON_AUTHORITATIVE_INFORMATION, "Non-Authoritative Information");
+    (204, NO_CONTENT, "No Content");
+    (205, RESET_CONTENT, "Reset Content");
+    (206, PARTIAL_CONTENT, "Partial Content");
+    (300, MULTIPLE_CHOICES, "Multiple Choices");
+    (301, MOVED_PERMANENTLY, "Moved Permanently");
+    (302, FOUND, "Found");
+    (303, SEE_OTHER, "See Other");
+    (304, NOT_MODIFIED, "Not Modified");
+    (307, TEMPORARY_REDIRECT, "Temporary Redirect");
+    (308, PERMANENT_REDIRECT, "Permanent Redirect");
+    (400, BAD_REQUEST, "Bad Request");
+    (401, UNAUTHORIZED, "Unauthorized");
+    (402, PAYMENT_REQUIRED, "Payment Required");
+    (403, FORBIDDEN, "Forbidden");
+    (404, NOT_FOUND, "Not Found");
+    (405, METHOD_NOT_ALLOWED, "Method Not Allowed");
+    (406, NOT_ACCEPTABLE, "Not Acceptable");
+    (407, PROXY_AUTHENTICATION_REQUIRED, "Proxy Authentication Required");
+    (408, REQUEST_TIMEOUT, "Request Timeout");
+    (409, CONFLICT, "Conflict");
+    (410, GONE, "Gone");
+    (411, LENGTH_REQUIRED, "Length Required");
+    (412, PRECONDITION_FAILED, "Precondition Failed");
+    (413, PAYLOAD_TOO_LARGE, "Payload Too Large");
+    (414, URI_TOO_LONG, "URI Too Long");
+    (415, UNSUPPORTED_MEDIA_TYPE, "Unsupported Media Type");
+    (416, RANGE_NOT_SATISFIABLE, "Range Not Satisfiable");
+    (417, EXPECTATION_FAILED, "Expectation Failed");
+    (418, IM_A_TEAPOT, "I'm a teapot");
+    (421, MISDIRECTED_REQUEST, "Misdirected Request");
+    (422, UNPROCESSABLE_ENTITY, "Unprocessable Entity");
+    (425, TOO_EARLY, "Too Early");
+    (426, UPGRADE_REQUIRED, "Upgrade Required");
+    (428, PRECONDITION_REQUIRED, "Precondition Required");
+    (429, TOO_MANY_REQUESTS, "Too Many Requests");
+    (431, REQUEST_HEADER_FIELDS_TOO_LARGE, "Request Header Fields Too Large");
+    (451, UNAVAILABLE_FOR_LEGAL_REASONS, "Unavailable For Legal Reasons");
+    (500, INTERNAL_SERVER_ERROR, "Internal Server Error");
+    (501, NOT_IMPLEMENTED, "Not Implemented");
+    (502, BAD_GATEWAY, "Bad Gateway");
+    (503, SERVICE_UNAVAILABLE, "Service Unavailable");
+    (504, GATEWAY_TIMEOUT, "Gateway Timeout");
+    (505, HTTP_VERSION_NOT_SUPPORTED, "HTTP Version Not Supported");
+    (511, NETWORK_AUTHENTICATION_REQUIRED, "Network Authentication Required");
+}
+
+/// Implement the StatusCode struct
+impl StatusCode {
+    /// Create a StatusCode from a number
+    /// ## Args
+    /// - code: u16
+    /// ## Returns
+    /// - Option<StatusCode> (None if the code is not in 100..=999)
+    pub fn from_u16(code: u16) -> Option<StatusCode> {
+        (100..=999).contains(&code).then_some(StatusCode(code))
+    }
+
+    /// Get the numeric value of the status code
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - u16
+    pub fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    /// Check if the status code is informational (1xx)
+    /// ## Returns
+    /// - bool
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.0)
+    }
+
+    /// Check if the status code is a success (2xx)
+    /// ## Returns
+    /// - bool
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.0)
+    }
+
+    /// Check if the status code is a redirection (3xx)
+    /// ## Returns
+    /// - bool
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.0)
+    }
+
+    /// Check if the status code is a client error (4xx)
+    /// ## Returns
+    /// - bool
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.0)
+    }
+
+    /// Check if the status code is a server error (5xx)
+    /// ## Returns
+    /// - bool
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.0)
+    }
+}
+
+/// Define the InvalidStatusCode struct
+/// The error of a number that is not a status code
+/// ## Fields
+/// - 0: u16
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidStatusCode(pub u16);
+
+/// Implement the Display trait for InvalidStatusCode
+impl fmt::Display for InvalidStatusCode {
+    /// Format the InvalidStatusCode
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid status code {}: expected 100..=999", self.0)
+    }
+}
+
+impl std::error::Error for InvalidStatusCode {}
+
+/// Implement the TryFrom trait to build a StatusCode from a number
+impl TryFrom<u16> for StatusCode {
+    type Error = InvalidStatusCode;
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        StatusCode::from_u16(code).ok_or(InvalidStatusCode(code))
+    }
+}
+
+/// Implement the From trait to convert a StatusCode into a number
+impl From<StatusCode> for u16 {
+    fn from(status: StatusCode) -> Self {
+        status.0
+    }
+}
+
+/// Implement the comparison between a StatusCode and a number
+impl PartialEq<u16> for StatusCode {
+    fn eq(&self, other: &u16) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Implement the Display trait for StatusCode
+impl fmt::Display for StatusCode {
+    /// Format the StatusCode as `code reason`
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.0,
+            self.canonical_reason().unwrap_or_default()
+        )
+    }
+}
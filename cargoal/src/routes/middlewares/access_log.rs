@@ -0,0 +1,195 @@
+use crate::routes::http::body::Body;
+use crate::routes::http::date::UtcDateTime;
+use crate::routes::http::method::HttpMethod;
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::routing::{Middleware, Next};
+use std::io::Write;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Define the LogFormat enum
+/// ## Variants
+/// - Common: the Common Log Format
+///   (`host ident user [time] "request line" status bytes`)
+/// - Combined: the Common Log Format followed by `"referer" "user agent"`
+/// - Json: one JSON object per line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Common,
+    Combined,
+    Json,
+}
+
+/// Define the AccessLog struct
+/// A middleware writing one line per request once its Response is ready.
+/// Add it first with `ServerHandle::add_middleware` so that the Responses of the
+/// other middlewares are logged too
+/// ## Fields
+/// - format: LogFormat
+/// - writer: Arc<Mutex<Box<dyn Write + Send>>>
+#[derive(Clone)]
+pub struct AccessLog {
+    format: LogFormat,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+/// Define the Entry struct
+/// What is logged about a request
+/// ## Fields
+/// - time: SystemTime (when the request was received)
+/// - client_ip: Option<IpAddr>
+/// - method: HttpMethod
+/// - path: String
+/// - query: Option<String>
+/// - version: String
+/// - referer: Option<String>
+/// - user_agent: Option<String>
+struct Entry {
+    time: SystemTime,
+    client_ip: Option<IpAddr>,
+    method: HttpMethod,
+    path: String,
+    query: Option<String>,
+    version: String,
+    referer: Option<String>,
+    user_agent: Option<String>,
+}
+
+/// Implement the AccessLog struct
+impl AccessLog {
+    /// Create a new AccessLog instance writing to the standard output
+    /// ## Args
+    /// - format: LogFormat
+    /// ## Returns
+    /// - AccessLog
+    pub fn new(format: LogFormat) -> Self {
+        Self {
+            format,
+            writer: Arc::new(Mutex::new(Box::new(std::io::stdout()))),
+        }
+    }
+
+    /// Write the lines to another destination (a file, a buffer...)
+    /// The writes are blocking: use a buffered writer for slow destinations
+    /// ## Args
+    /// - self
+    /// - writer: W
+    /// ## Where
+    /// - W: Write + Send + 'static
+    /// ## Returns
+    /// - AccessLog
+    pub fn with_writer<W: Write + Send + 'static>(mut self, writer: W) -> Self {
+        self.writer = Arc::new(Mutex::new(Box::new(writer)));
+        self
+    }
+
+    /// Format the line of a request
+    /// ## Args
+    /// - self
+    /// - entry: &Entry
+    /// - response: &Response
+    /// - duration: Duration
+    /// ## Returns
+    /// - String
+    fn format_line(&self, entry: &Entry, response: &Response, duration: Duration) -> String {
+        let status = response.status_code.as_u16();
+        // The body of HEAD responses and the length of streams are not known here
+        let bytes = match (&entry.method, &response.body) {
+            (HttpMethod::HEAD, _) | (_, Body::Stream(_)) => None,
+            (_, body) => body.as_bytes().map(<[u8]>::len),
+        };
+        let target = match &entry.query {
+            Some(query) => format!("{}?{}", entry.path, query),
+            None => entry.path.clone(),
+        };
+
+        match self.format {
+            LogFormat::Common | LogFormat::Combined => {
+                let mut line = format!(
+                    "{} - - [{}] \"{}\" {} {}",
+                    entry
+                        .client_ip
+                        .map_or_else(|| "-".to_string(), |ip| ip.to_string()),
+                    UtcDateTime::from_system_time(entry.time).to_clf(),
+                    escape(&format!("{} {} {}", entry.method, target, entry.version)),
+                    status,
+                    bytes
+                        .filter(|bytes| *bytes > 0)
+                        .map_or_else(|| "-".to_string(), |bytes| bytes.to_string())
+                );
+                if self.format == LogFormat::Combined {
+                    let quoted = |value: &Option<String>| {
+                        value.as_deref().map_or_else(|| "-".to_string(), escape)
+                    };
+                    line.push_str(&format!(
+                        " \"{}\" \"{}\"",
+                        quoted(&entry.referer),
+                        quoted(&entry.user_agent)
+                    ));
+                }
+                line
+            }
+            LogFormat::Json => serde_json::json!({
+                "time": UtcDateTime::from_system_time(entry.time).to_rfc3339(),
+                "client_ip": entry.client_ip.map(|ip| ip.to_string()),
+                "method": entry.method.to_string(),
+                "path": entry.path,
+                "query": entry.query,
+                "version": entry.version,
+                "status": status,
+                "bytes": bytes,
+                "duration_ms": duration.as_secs_f64() * 1000.0,
+                "referer": entry.referer,
+                "user_agent": entry.user_agent,
+            })
+            .to_string(),
+        }
+    }
+}
+
+/// Implement the Middleware trait for AccessLog
+impl Middleware for AccessLog {
+    async fn handle(&self, req: Request, next: Next) -> Response {
+        let entry = Entry {
+            time: SystemTime::now(),
+            client_ip: req.client_ip(),
+            method: req.method.clone(),
+            path: req.path.clone(),
+            query: req.query.clone(),
+            version: req.version.clone(),
+            referer: req.header("referer").map(str::to_string),
+            user_agent: req.header("user-agent").map(str::to_string),
+        };
+
+        let response = next.run(req).await;
+        let duration = entry.time.elapsed().unwrap_or_default();
+
+        let line = self.format_line(&entry, &response, duration);
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+            tracing::warn!(error = %e, "Error writing the access log");
+        }
+
+        response
+    }
+}
+
+/// Escape a value written between double quotes
+/// ## Args
+/// - value: &str
+/// ## Returns
+/// - String
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,268 @@
+use crate::routes::http::method::HttpMethod;
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use crate::routes::routing::{Middleware, Next};
+use std::time::Duration;
+
+/// Define the AllowedOrigins enum
+/// ## Variants
+/// - Any: every origin
+/// - List(Vec<String>): only these origins (e.g. `https://example.com`)
+#[derive(Debug, Clone)]
+enum AllowedOrigins {
+    Any,
+    List(Vec<String>),
+}
+
+/// Define the Cors struct
+/// A middleware answering CORS preflight requests and adding the CORS headers to the
+/// responses of the allowed origins. Add it with `ServerHandle::add_middleware` so that
+/// preflight requests are answered for every route
+/// ## Fields
+/// - origins: AllowedOrigins
+/// - methods: Vec<HttpMethod>
+/// - headers: Vec<String>
+/// - credentials: bool
+/// - max_age: Option<Duration>
+#[derive(Debug, Clone)]
+pub struct Cors {
+    origins: AllowedOrigins,
+    methods: Vec<HttpMethod>,
+    headers: Vec<String>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+/// Implement the Cors struct
+impl Cors {
+    /// Create a new Cors instance allowing no origin, and the GET, HEAD and POST methods
+    /// ## Returns
+    /// - Cors
+    pub fn new() -> Self {
+        Self {
+            origins: AllowedOrigins::List(Vec::new()),
+            methods: vec![HttpMethod::GET, HttpMethod::HEAD, HttpMethod::POST],
+            headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// Allow an origin
+    /// ## Args
+    /// - self
+    /// - origin: &str (scheme, host and port, e.g. `https://example.com`)
+    /// ## Returns
+    /// - Cors
+    pub fn with_origin(mut self, origin: &str) -> Self {
+        match &mut self.origins {
+            AllowedOrigins::List(origins) => origins.push(origin.to_string()),
+            AllowedOrigins::Any => {}
+        }
+        self
+    }
+
+    /// Allow every origin
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Cors
+    pub fn with_any_origin(mut self) -> Self {
+        self.origins = AllowedOrigins::Any;
+        self
+    }
+
+    /// Set the methods allowed for cross-origin requests
+    /// ## Args
+    /// - self
+    /// - methods: Vec<HttpMethod>
+    /// ## Returns
+    /// - Cors
+    pub fn with_methods(mut self, methods: Vec<HttpMethod>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Set the request headers allowed for cross-origin requests
+    /// ## Args
+    /// - self
+    /// - headers: Vec<&str> (e.g. `vec!["Content-Type", "Authorization"]`)
+    /// ## Returns
+    /// - Cors
+    pub fn with_headers(mut self, headers: Vec<&str>) -> Self {
+        self.headers = headers.into_iter().map(str::to_string).collect();
+        self
+    }
+
+    /// Allow the cross-origin requests to send credentials (cookies, Authorization)
+    /// ## Args
+    /// - self
+    /// - credentials: bool
+    /// ## Returns
+    /// - Cors
+    pub fn with_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Set how long browsers can cache the answer to a preflight request
+    /// ## Args
+    /// - self
+    /// - max_age: Duration
+    /// ## Returns
+    /// - Cors
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Check if an origin is allowed
+    /// ## Args
+    /// - self
+    /// - origin: &str
+    /// ## Returns
+    /// - bool
+    fn allows_origin(&self, origin: &str) -> bool {
+        match &self.origins {
+            AllowedOrigins::Any => true,
+            AllowedOrigins::List(origins) => origins.iter().any(|allowed| allowed == origin),
+        }
+    }
+
+    /// Check if every header of an `Access-Control-Request-Headers` value is allowed
+    /// ## Args
+    /// - self
+    /// - requested: &str
+    /// ## Returns
+    /// - bool
+    fn allows_headers(&self, requested: &str) -> bool {
+        requested
+            .split(',')
+            .map(str::trim)
+            .filter(|header| !header.is_empty())
+            .all(|header| {
+                self.headers
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(header))
+            })
+    }
+
+    /// Add the headers shared by the preflight and the actual responses
+    /// ## Args
+    /// - self
+    /// - response: Response
+    /// - origin: &str
+    /// ## Returns
+    /// - Response
+    fn with_origin_headers(&self, response: Response, origin: &str) -> Response {
+        // `*` cannot be used with credentials, the origin is sent back instead
+        let allow_origin = match self.origins {
+            AllowedOrigins::Any if !self.credentials => "*",
+            _ => origin,
+        };
+        let response = response.with_header("Access-Control-Allow-Origin", allow_origin);
+        if self.credentials {
+            response.with_header("Access-Control-Allow-Credentials", "true")
+        } else {
+            response
+        }
+    }
+
+    /// Answer a preflight request
+    /// ## Args
+    /// - self
+    /// - req: &Request
+    /// - origin: &str
+    /// - method: &str (value of `Access-Control-Request-Method`)
+    /// ## Returns
+    /// - Response
+    fn preflight(&self, req: &Request, origin: &str, method: &str) -> Response {
+        let method_allowed = self
+            .methods
+            .iter()
+            .any(|allowed| allowed.to_string().eq_ignore_ascii_case(method));
+        let headers_allowed = req
+            .header("access-control-request-headers")
+            .is_none_or(|headers| self.allows_headers(headers));
+        if !self.allows_origin(origin) || !method_allowed || !headers_allowed {
+            return Response::new(StatusCode::FORBIDDEN, "CORS request not allowed")
+                .with_header("Content-Type", "text/plain");
+        }
+
+        let methods = self
+            .methods
+            .iter()
+            .map(|method| method.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut response = Response::new(StatusCode::NO_CONTENT, None)
+            .with_header("Access-Control-Allow-Methods", &methods);
+        if !self.headers.is_empty() {
+            response =
+                response.with_header("Access-Control-Allow-Headers", &self.headers.join(", "));
+        }
+        if let Some(max_age) = self.max_age {
+            response =
+                response.with_header("Access-Control-Max-Age", &max_age.as_secs().to_string());
+        }
+        self.with_origin_headers(response, origin)
+    }
+}
+
+/// Implement the Default trait for Cors
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implement the Middleware trait for Cors
+impl Middleware for Cors {
+    async fn handle(&self, req: Request, next: Next) -> Response {
+        let Some(origin) = req.header("origin").map(str::to_string) else {
+            return next.run(req).await;
+        };
+
+        let preflight_method = match req.method {
+            HttpMethod::OPTIONS => req.header("access-control-request-method"),
+            _ => None,
+        };
+        let response = match preflight_method {
+            Some(method) => self.preflight(&req, &origin, method),
+            None => {
+                let response = next.run(req).await;
+                if self.allows_origin(&origin) {
+                    self.with_origin_headers(response, &origin)
+                } else {
+                    response
+                }
+            }
+        };
+
+        // The responses depend on the origin, allowed or not, so caches must not share
+        // them between origins
+        add_vary(response, "Origin")
+    }
+}
+
+/// Add a value to the Vary header of a Response
+/// ## Args
+/// - response: Response
+/// - value: &str
+/// ## Returns
+/// - Response
+fn add_vary(response: Response, value: &str) -> Response {
+    let vary = match response.header("vary") {
+        Some(vary)
+            if vary
+                .split(',')
+                .any(|v| v.trim().eq_ignore_ascii_case(value)) =>
+        {
+            return response
+        }
+        Some(vary) => format!("{}, {}", vary, value),
+        None => value.to_string(),
+    };
+    response.with_header("Vary", &vary)
+}
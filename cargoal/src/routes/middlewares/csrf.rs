@@ -0,0 +1,230 @@
+use crate::routes::extract::from_request::has_content_type;
+use crate::routes::http::method::HttpMethod;
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use crate::routes::routing::{Middleware, Next};
+use crate::routes::session::store::generate_id;
+use crate::routes::session::Session;
+
+/// Key of the token in the session
+const SESSION_KEY: &str = "_csrf_token";
+
+/// Define the CsrfToken struct
+/// The CSRF token of the session, added to the request extensions by the Csrf
+/// middleware (`Extension<CsrfToken>`) and given to the templates by `csrf_token()`
+/// ## Fields
+/// - 0: String
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(pub String);
+
+/// Define the CsrfExempt struct
+/// A middleware lifting the protection of the Csrf middleware for the routes of a group
+/// (e.g. an API authenticated with tokens, on its own subdomain). Add it with
+/// `GroupBuilder::add_middleware`.
+/// The Csrf middleware runs before the routing, so the requests without a valid token
+/// are only rejected once the middlewares of their route have run, right before the handler
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsrfExempt;
+
+/// Implement the Middleware trait for CsrfExempt
+impl Middleware for CsrfExempt {
+    async fn handle(&self, mut req: Request, next: Next) -> Response {
+        req.extensions.insert(CsrfExempt);
+        next.run(req).await
+    }
+}
+
+/// Define the CsrfRejection struct
+/// Added to the request extensions by the Csrf middleware when the token is missing or
+/// invalid, until the route checks it with `csrf_rejection`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CsrfRejection;
+
+/// Get the answer to a request rejected by the Csrf middleware
+/// ## Args
+/// - req: &Request
+/// ## Returns
+/// - Option<Response> (403 Forbidden, None if the token is valid or the route is exempt)
+pub(crate) fn csrf_rejection(req: &Request) -> Option<Response> {
+    (req.extensions.contains::<CsrfRejection>() && !req.extensions.contains::<CsrfExempt>()).then(
+        || {
+            Response::new(
+                StatusCode::FORBIDDEN,
+                Some("Invalid CSRF token".to_string()),
+            )
+        },
+    )
+}
+
+/// Define the Csrf struct
+/// A middleware protecting the forms against cross-site request forgery. Every session
+/// gets a token, which the POST, PUT, PATCH and DELETE requests must send back in a form
+/// field or a header, otherwise they are answered with 403 Forbidden.
+/// Add it with `ServerHandle::add_middleware` after the Sessions middleware, and put
+/// `{{ csrf_token() }}` in the forms of the templates. Groups opt out with `CsrfExempt`
+/// ## Fields
+/// - exempt_paths: Vec<String> (the paths not protected, e.g. an API using tokens)
+/// - header_name: String
+/// - field_name: String
+#[derive(Debug, Clone)]
+pub struct Csrf {
+    exempt_paths: Vec<String>,
+    header_name: String,
+    field_name: String,
+}
+
+/// Implement the Csrf struct
+impl Csrf {
+    /// Create a new Csrf instance reading the token from the `X-CSRF-Token` header or
+    /// the `_csrf_token` form field
+    /// ## Returns
+    /// - Csrf
+    pub fn new() -> Self {
+        Self {
+            exempt_paths: Vec::new(),
+            header_name: "X-CSRF-Token".to_string(),
+            field_name: "_csrf_token".to_string(),
+        }
+    }
+
+    /// Stop protecting a path and the paths under it (e.g. the prefix of an API group)
+    /// ## Args
+    /// - self
+    /// - path: &str (e.g. `/api`)
+    /// ## Returns
+    /// - Csrf
+    pub fn with_exempt_path(mut self, path: &str) -> Self {
+        self.exempt_paths
+            .push(path.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Set the header holding the token
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Csrf
+    pub fn with_header_name(mut self, name: &str) -> Self {
+        self.header_name = name.to_string();
+        self
+    }
+
+    /// Set the form field holding the token
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Csrf
+    pub fn with_field_name(mut self, name: &str) -> Self {
+        self.field_name = name.to_string();
+        self
+    }
+
+    /// Check if a path is not protected
+    /// ## Args
+    /// - self
+    /// - path: &str
+    /// ## Returns
+    /// - bool
+    fn is_exempt(&self, path: &str) -> bool {
+        self.exempt_paths.iter().any(|exempt| {
+            path.strip_prefix(exempt.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    /// Get the token sent with a Request
+    /// ## Args
+    /// - self
+    /// - req: &Request
+    /// ## Returns
+    /// - Option<String> (from the header, else from the urlencoded form)
+    fn submitted_token(&self, req: &Request) -> Option<String> {
+        if let Some(token) = req.header(&self.header_name) {
+            return Some(token.to_string());
+        }
+        if !has_content_type(req, "application/x-www-form-urlencoded") {
+            return None;
+        }
+        let fields: Vec<(String, String)> =
+            serde_urlencoded::from_bytes(req.body.as_deref().unwrap_or_default()).ok()?;
+        fields
+            .into_iter()
+            .find(|(name, _)| *name == self.field_name)
+            .map(|(_, token)| token)
+    }
+}
+
+/// Implement the Default trait for Csrf
+impl Default for Csrf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implement the Middleware trait for Csrf
+impl Middleware for Csrf {
+    async fn handle(&self, mut req: Request, next: Next) -> Response {
+        if self.is_exempt(&req.path) {
+            return next.run(req).await;
+        }
+
+        let Some(session) = req.extensions.get::<Session>().cloned() else {
+            tracing::error!("The Csrf middleware requires the Sessions middleware");
+            return Response::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Some("Internal Server Error".to_string()),
+            );
+        };
+
+        let token = session.get::<String>(SESSION_KEY);
+        let unsafe_method = matches!(
+            req.method,
+            HttpMethod::POST | HttpMethod::PUT | HttpMethod::PATCH | HttpMethod::DELETE
+        );
+        if unsafe_method {
+            let valid = match (&token, self.submitted_token(&req)) {
+                (Some(token), Some(submitted)) => constant_time_eq(token, &submitted),
+                _ => false,
+            };
+            if !valid {
+                // No token is created for the request, the group of its route may be exempt
+                req.extensions.insert(CsrfRejection);
+                if let Some(token) = token {
+                    req.extensions.insert(CsrfToken(token));
+                }
+                return next.run(req).await;
+            }
+        }
+
+        let token = match token {
+            Some(token) => token,
+            None => {
+                let token = generate_id();
+                if let Err(e) = session.insert(SESSION_KEY, &token) {
+                    tracing::error!(error = %e, "Error storing the CSRF token");
+                }
+                token
+            }
+        };
+        req.extensions.insert(CsrfToken(token));
+
+        next.run(req).await
+    }
+}
+
+/// Compare two tokens in a time independent of where they differ
+/// ## Args
+/// - a: &str
+/// - b: &str
+/// ## Returns
+/// - bool
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,11 @@
+pub(crate) mod access_log;
+pub(crate) mod cors;
+pub(crate) mod csrf;
+pub(crate) mod security_headers;
+
+pub use access_log::{AccessLog, LogFormat};
+pub use cors::Cors;
+pub use csrf::{Csrf, CsrfExempt, CsrfToken};
+
+pub(crate) use csrf::csrf_rejection;
+pub use security_headers::{CspNonce, SecurityHeaders};
@@ -0,0 +1,208 @@
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::routing::{Middleware, Next};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::time::Duration;
+
+/// Placeholder of the Content-Security-Policy replaced by the nonce of the request
+const NONCE_PLACEHOLDER: &str = "{nonce}";
+
+/// Define the CspNonce struct
+/// The nonce of the Content-Security-Policy of the request, added to the request
+/// extensions by the SecurityHeaders middleware (`Extension<CspNonce>`) and given to the
+/// templates by `csp_nonce()`
+/// ## Fields
+/// - 0: String
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(pub String);
+
+/// Define the SecurityHeaders struct
+/// A middleware adding the security headers to every response: routes, templates,
+/// static files and errors. A header already set by a route is kept, so that a route
+/// can override a policy for itself.
+/// By default: `Strict-Transport-Security` (one year, subdomains included),
+/// `X-Content-Type-Options: nosniff`, `X-Frame-Options: DENY`,
+/// `Referrer-Policy: strict-origin-when-cross-origin` and
+/// `Cross-Origin-Opener-Policy: same-origin`
+/// ## Fields
+/// - headers: Vec<(String, String)> (in the order they were set)
+/// - content_security_policy: Option<String> (may contain `{nonce}`)
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    headers: Vec<(String, String)>,
+    content_security_policy: Option<String>,
+}
+
+/// Implement the SecurityHeaders struct
+impl SecurityHeaders {
+    /// Create a new SecurityHeaders instance with the default headers
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn new() -> Self {
+        Self {
+            headers: Vec::new(),
+            content_security_policy: None,
+        }
+        .with_hsts(Duration::from_secs(365 * 24 * 60 * 60), true, false)
+        .with_header("X-Content-Type-Options", "nosniff")
+        .with_frame_options("DENY")
+        .with_referrer_policy("strict-origin-when-cross-origin")
+        .with_cross_origin_opener_policy("same-origin")
+    }
+
+    /// Set a header added to every response, replacing its previous value
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - value: &str
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .retain(|(header, _)| !header.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Stop adding a header, e.g. `without_header("Strict-Transport-Security")` while
+    /// the application is not served over HTTPS
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn without_header(mut self, name: &str) -> Self {
+        self.headers
+            .retain(|(header, _)| !header.eq_ignore_ascii_case(name));
+        if name.eq_ignore_ascii_case("Content-Security-Policy") {
+            self.content_security_policy = None;
+        }
+        self
+    }
+
+    /// Set the `Strict-Transport-Security` header, asking browsers to only use HTTPS
+    /// ## Args
+    /// - self
+    /// - max_age: Duration
+    /// - include_subdomains: bool
+    /// - preload: bool (for the browsers preload lists)
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_hsts(self, max_age: Duration, include_subdomains: bool, preload: bool) -> Self {
+        let mut value = format!("max-age={}", max_age.as_secs());
+        if include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if preload {
+            value.push_str("; preload");
+        }
+        self.with_header("Strict-Transport-Security", &value)
+    }
+
+    /// Set the `Content-Security-Policy` header. Every `{nonce}` is replaced by a nonce
+    /// generated for each request, which the templates get with `csp_nonce()`
+    /// ## Args
+    /// - self
+    /// - policy: &str (e.g. `default-src 'self'; script-src 'self' 'nonce-{nonce}'`)
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_content_security_policy(mut self, policy: &str) -> Self {
+        self.content_security_policy = Some(policy.to_string());
+        self
+    }
+
+    /// Set the `X-Frame-Options` header
+    /// ## Args
+    /// - self
+    /// - value: &str (`DENY` or `SAMEORIGIN`)
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_frame_options(self, value: &str) -> Self {
+        self.with_header("X-Frame-Options", value)
+    }
+
+    /// Set the `Referrer-Policy` header
+    /// ## Args
+    /// - self
+    /// - policy: &str (e.g. `no-referrer`)
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_referrer_policy(self, policy: &str) -> Self {
+        self.with_header("Referrer-Policy", policy)
+    }
+
+    /// Set the `Permissions-Policy` header
+    /// ## Args
+    /// - self
+    /// - policy: &str (e.g. `camera=(), geolocation=(self)`)
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_permissions_policy(self, policy: &str) -> Self {
+        self.with_header("Permissions-Policy", policy)
+    }
+
+    /// Set the `Cross-Origin-Opener-Policy` header
+    /// ## Args
+    /// - self
+    /// - policy: &str (e.g. `same-origin-allow-popups`)
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_cross_origin_opener_policy(self, policy: &str) -> Self {
+        self.with_header("Cross-Origin-Opener-Policy", policy)
+    }
+
+    /// Set the `Cross-Origin-Embedder-Policy` header
+    /// ## Args
+    /// - self
+    /// - policy: &str (e.g. `require-corp`)
+    /// ## Returns
+    /// - SecurityHeaders
+    pub fn with_cross_origin_embedder_policy(self, policy: &str) -> Self {
+        self.with_header("Cross-Origin-Embedder-Policy", policy)
+    }
+}
+
+/// Implement the Default trait for SecurityHeaders
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Implement the Middleware trait for SecurityHeaders
+impl Middleware for SecurityHeaders {
+    async fn handle(&self, mut req: Request, next: Next) -> Response {
+        let policy = match &self.content_security_policy {
+            Some(policy) if policy.contains(NONCE_PLACEHOLDER) => {
+                let nonce = generate_nonce();
+                req.extensions.insert(CspNonce(nonce.clone()));
+                Some(policy.replace(NONCE_PLACEHOLDER, &nonce))
+            }
+            policy => policy.clone(),
+        };
+
+        let mut response = next.run(req).await;
+        let policy = policy.map(|policy| ("Content-Security-Policy".to_string(), policy));
+        for (name, value) in self.headers.iter().chain(policy.as_ref()) {
+            if response.header(name).is_none() {
+                response = response.with_header(name, value);
+            }
+        }
+        response
+    }
+}
+
+/// Generate the nonce of a request
+/// ## Returns
+/// - String (128 random bits, URL-safe base64)
+/// ## Panics
+/// - If the system random number generator fails
+fn generate_nonce() -> String {
+    let mut nonce = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .expect("the system random number generator failed");
+    URL_SAFE_NO_PAD.encode(nonce)
+}
@@ -1,3 +1,7 @@
+pub mod extract;
 pub mod http;
+pub mod middlewares;
+pub mod rate_limit;
 pub mod routing;
 pub mod server;
+pub mod session;
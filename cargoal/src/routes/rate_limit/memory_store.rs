@@ -0,0 +1,59 @@
+use super::quota::{Decision, LimitState, Quota};
+use super::store::{RateLimitError, RateLimitStore};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Define the MemoryStore struct
+/// Keeps the states of the clients in the memory of the process: each instance of the
+/// application limits the clients on its own
+/// ## Fields
+/// - states: Arc<Mutex<HashMap<String, LimitState>>> (by key)
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    states: Arc<Mutex<HashMap<String, LimitState>>>,
+}
+
+/// Implement the MemoryStore struct
+impl MemoryStore {
+    /// Create a new empty MemoryStore
+    /// ## Returns
+    /// - MemoryStore
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of states kept, expired ones included
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - usize
+    pub fn len(&self) -> usize {
+        self.states.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check if no state is kept
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Implement the RateLimitStore trait for MemoryStore
+impl RateLimitStore for MemoryStore {
+    async fn hit(&self, key: &str, quota: &Quota, now: u64) -> Result<Decision, RateLimitError> {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let (state, decision) = quota.apply(states.get(key), now);
+        states.insert(key.to_string(), state);
+        Ok(decision)
+    }
+
+    async fn cleanup(&self, now: u64) -> Result<u64, RateLimitError> {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let before = states.len();
+        states.retain(|_, state| !state.is_expired(now));
+        Ok((before - states.len()) as u64)
+    }
+}
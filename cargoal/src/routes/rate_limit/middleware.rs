@@ -0,0 +1,250 @@
+use super::memory_store::MemoryStore;
+use super::quota::{Decision, Quota};
+use super::store::{unix_now_micros, RateLimitStore};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use crate::routes::routing::{Middleware, Next};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Define the KeyFn type
+type KeyFn = Arc<dyn Fn(&Request) -> Option<String> + Send + Sync>;
+
+/// Define the KeySource enum
+/// What the clients are told apart by
+/// ## Variants
+/// - Ip: the IP address of the client
+/// - Header(String): the value of a header (e.g. an API key)
+/// - Custom(KeyFn): a function of the Request
+#[derive(Clone)]
+enum KeySource {
+    Ip,
+    Header(String),
+    Custom(KeyFn),
+}
+
+/// Define the RateLimit struct
+/// A middleware limiting how many requests each client can send. The responses get the
+/// `RateLimit-Limit`, `RateLimit-Remaining`, `RateLimit-Reset` and `RateLimit-Policy`
+/// headers, and the rejected requests are answered with 429 Too Many Requests and a
+/// `Retry-After` header. Add it globally with `ServerHandle::add_middleware`, to a group
+/// with `GroupBuilder::add_middleware` or to a route with `RouteBuilder::with_middleware`
+/// ## Fields
+/// - store: Arc<S>
+/// - quota: Quota
+/// - key: KeySource (the IP address of the client by default)
+/// - name: String (prefix of the keys in the store)
+/// ## Type Parameters
+/// - S: RateLimitStore
+pub struct RateLimit<S = MemoryStore> {
+    store: Arc<S>,
+    quota: Quota,
+    key: KeySource,
+    name: String,
+}
+
+/// Implement the Clone trait for RateLimit (without requiring `S: Clone`)
+impl<S> Clone for RateLimit<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            quota: self.quota,
+            key: self.key.clone(),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// Implement the RateLimit struct
+impl RateLimit {
+    /// Create a new RateLimit middleware limiting each IP address, in memory
+    /// ## Args
+    /// - quota: Quota (e.g. `Quota::sliding_window(100, Duration::from_secs(60))`)
+    /// ## Returns
+    /// - RateLimit
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            store: Arc::new(MemoryStore::new()),
+            quota,
+            key: KeySource::Ip,
+            name: "rate_limit".to_string(),
+        }
+    }
+}
+
+/// Implement the RateLimit struct
+impl<S: RateLimitStore> RateLimit<S> {
+    /// Keep the states of the clients in another store
+    /// ## Args
+    /// - self
+    /// - store: T
+    /// ## Returns
+    /// - RateLimit<T>
+    /// ## Where
+    /// - T: RateLimitStore
+    pub fn with_store<T: RateLimitStore>(self, store: T) -> RateLimit<T> {
+        RateLimit {
+            store: Arc::new(store),
+            quota: self.quota,
+            key: self.key,
+            name: self.name,
+        }
+    }
+
+    /// Set the prefix of the keys in the store, to share a store between limits
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - RateLimit<S>
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Limit each IP address (the default). Behind proxies, configure them with
+    /// `ServerHandle::with_trusted_proxies` so that the clients are not all limited as one
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - RateLimit<S>
+    pub fn by_ip(mut self) -> Self {
+        self.key = KeySource::Ip;
+        self
+    }
+
+    /// Limit each value of a header. The requests without the header are not limited
+    /// ## Args
+    /// - self
+    /// - name: &str (e.g. `X-API-Key`)
+    /// ## Returns
+    /// - RateLimit<S>
+    pub fn by_header(mut self, name: &str) -> Self {
+        self.key = KeySource::Header(name.to_string());
+        self
+    }
+
+    /// Limit each key returned by a function. The requests without a key are not limited
+    /// ## Args
+    /// - self
+    /// - key_fn: F
+    /// ## Returns
+    /// - RateLimit<S>
+    /// ## Where
+    /// - F: Fn(&Request) -> Option<String> + Send + Sync + 'static
+    pub fn by_key<F>(mut self, key_fn: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key = KeySource::Custom(Arc::new(key_fn));
+        self
+    }
+
+    /// Get the store of the states
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &S
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Delete the expired states of the store periodically
+    /// ## Args
+    /// - self
+    /// - interval: Duration
+    /// ## Returns
+    /// - JoinHandle<()> (abort it to stop the cleanup)
+    /// ## Panics
+    /// - If called outside of a Tokio runtime
+    pub fn spawn_cleanup(&self, interval: Duration) -> JoinHandle<()> {
+        let store = Arc::clone(&self.store);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match store.cleanup(unix_now_micros()).await {
+                    Ok(0) => {}
+                    Ok(deleted) => tracing::debug!("Deleted {} expired rate limit(s)", deleted),
+                    Err(e) => tracing::error!(error = %e, "Error deleting the expired rate limits"),
+                }
+            }
+        })
+    }
+
+    /// Get the key of the client sending a Request
+    /// ## Args
+    /// - self
+    /// - req: &Request
+    /// ## Returns
+    /// - Option<String>
+    fn key(&self, req: &Request) -> Option<String> {
+        let key = match &self.key {
+            KeySource::Ip => format!("ip:{}", req.client_ip()?),
+            KeySource::Header(name) => {
+                format!("header:{}:{}", name.to_ascii_lowercase(), req.header(name)?)
+            }
+            KeySource::Custom(key_fn) => format!("key:{}", key_fn(req)?),
+        };
+        Some(format!("{}:{}", self.name, key))
+    }
+
+    /// Add the `RateLimit-*` headers to a Response
+    /// ## Args
+    /// - self
+    /// - response: Response
+    /// - decision: &Decision
+    /// ## Returns
+    /// - Response
+    fn with_headers(&self, response: Response, decision: &Decision) -> Response {
+        response
+            .with_header("RateLimit-Limit", &decision.limit.to_string())
+            .with_header("RateLimit-Remaining", &decision.remaining.to_string())
+            .with_header("RateLimit-Reset", &seconds(decision.reset).to_string())
+            .with_header(
+                "RateLimit-Policy",
+                &format!("{};w={}", self.quota.limit(), seconds(self.quota.period())),
+            )
+    }
+}
+
+/// Implement the Middleware trait for RateLimit
+impl<S: RateLimitStore> Middleware for RateLimit<S> {
+    async fn handle(&self, req: Request, next: Next) -> Response {
+        let Some(key) = self.key(&req) else {
+            return next.run(req).await;
+        };
+
+        let decision = match self.store.hit(&key, &self.quota, unix_now_micros()).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                // An unavailable store does not take the application down
+                tracing::error!(error = %e, "Error counting a request against its rate limit");
+                return next.run(req).await;
+            }
+        };
+
+        let response = if decision.allowed {
+            next.run(req).await
+        } else {
+            let retry_after = decision.retry_after.unwrap_or_default();
+            Response::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                Some("Too Many Requests".to_string()),
+            )
+            .with_header("Retry-After", &seconds(retry_after).to_string())
+        };
+        self.with_headers(response, &decision)
+    }
+}
+
+/// Get a Duration in whole seconds, rounded up
+/// ## Args
+/// - duration: Duration
+/// ## Returns
+/// - u64
+fn seconds(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
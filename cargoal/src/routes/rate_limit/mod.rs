@@ -0,0 +1,9 @@
+pub(crate) mod memory_store;
+pub(crate) mod middleware;
+pub(crate) mod quota;
+pub(crate) mod store;
+
+pub use memory_store::MemoryStore;
+pub use middleware::RateLimit;
+pub use quota::{Decision, LimitState, Quota};
+pub use store::{RateLimitError, RateLimitStore};
@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Define the Algorithm enum
+/// ## Variants
+/// - TokenBucket: bursts up to the limit, then one request every `period / limit`
+/// - SlidingWindow: at most the limit over any window, estimated from the counts of
+///   the current and previous windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    TokenBucket,
+    SlidingWindow,
+}
+
+/// Define the Quota struct
+/// How many requests a client can send, and how they are counted
+/// ## Fields
+/// - algorithm: Algorithm
+/// - limit: u64
+/// - period: Duration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    algorithm: Algorithm,
+    limit: u64,
+    period: Duration,
+}
+
+/// Define the LimitState struct
+/// What a store keeps about a client. The times are Unix timestamps in microseconds
+/// ## Fields
+/// - timestamp: u64 (token bucket: when the bucket is full again; sliding window:
+///   start of the current window)
+/// - count: u64 (sliding window: requests in the current window)
+/// - previous_count: u64 (sliding window: requests in the previous window)
+/// - expires_at: u64 (from then on, the state is the one of a new client)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitState {
+    pub timestamp: u64,
+    pub count: u64,
+    pub previous_count: u64,
+    pub expires_at: u64,
+}
+
+/// Implement the LimitState struct
+impl LimitState {
+    /// Check if the state can be forgotten
+    /// ## Args
+    /// - self
+    /// - now: u64 (Unix timestamp, in microseconds)
+    /// ## Returns
+    /// - bool
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Define the Decision struct
+/// Whether a request is allowed, and what the `RateLimit-*` headers report
+/// ## Fields
+/// - allowed: bool
+/// - limit: u64
+/// - remaining: u64 (requests left right after this one)
+/// - reset: Duration (until the quota is fully available again, or until the end of
+///   the window)
+/// - retry_after: Option<Duration> (for the rejected requests, until a request is
+///   allowed again)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset: Duration,
+    pub retry_after: Option<Duration>,
+}
+
+/// Implement the Quota struct
+impl Quota {
+    /// Create a token bucket Quota: a client can send `capacity` requests at once, and
+    /// the bucket refills at `capacity` requests per `period`
+    /// ## Args
+    /// - capacity: u64
+    /// - period: Duration
+    /// ## Returns
+    /// - Quota
+    /// ## Panics
+    /// - If the capacity or the period is zero
+    pub fn token_bucket(capacity: u64, period: Duration) -> Self {
+        Self::new(Algorithm::TokenBucket, capacity, period)
+    }
+
+    /// Create a sliding window Quota: a client can send `limit` requests per `window`
+    /// ## Args
+    /// - limit: u64
+    /// - window: Duration
+    /// ## Returns
+    /// - Quota
+    /// ## Panics
+    /// - If the limit or the window is zero
+    pub fn sliding_window(limit: u64, window: Duration) -> Self {
+        Self::new(Algorithm::SlidingWindow, limit, window)
+    }
+
+    /// Create a Quota
+    /// ## Args
+    /// - algorithm: Algorithm
+    /// - limit: u64
+    /// - period: Duration
+    /// ## Returns
+    /// - Quota
+    /// ## Panics
+    /// - If the limit or the period is zero
+    fn new(algorithm: Algorithm, limit: u64, period: Duration) -> Self {
+        assert!(limit > 0, "a rate limit must allow at least one request");
+        assert!(
+            period.as_micros() > 0,
+            "the period of a rate limit cannot be zero"
+        );
+        Self {
+            algorithm,
+            limit,
+            period,
+        }
+    }
+
+    /// Get the number of requests allowed per period
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - u64
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Get the period of the Quota
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Duration
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Count a request of a client
+    /// The stores call it with the state they keep for the client, and keep the
+    /// returned state
+    /// ## Args
+    /// - self
+    /// - state: Option<&LimitState> (None for a new client)
+    /// - now: u64 (Unix timestamp, in microseconds)
+    /// ## Returns
+    /// - (LimitState, Decision)
+    pub fn apply(&self, state: Option<&LimitState>, now: u64) -> (LimitState, Decision) {
+        let state = state.filter(|state| !state.is_expired(now));
+        match self.algorithm {
+            Algorithm::TokenBucket => self.apply_token_bucket(state, now),
+            Algorithm::SlidingWindow => self.apply_sliding_window(state, now),
+        }
+    }
+
+    /// Count a request with the token bucket algorithm, as its "generic cell rate"
+    /// form: the state is the time at which the bucket is full again
+    /// ## Args
+    /// - self
+    /// - state: Option<&LimitState>
+    /// - now: u64
+    /// ## Returns
+    /// - (LimitState, Decision)
+    fn apply_token_bucket(&self, state: Option<&LimitState>, now: u64) -> (LimitState, Decision) {
+        let interval = (micros(self.period) / self.limit).max(1);
+        let burst = interval * self.limit;
+        let full_at = state.map_or(now, |state| state.timestamp.max(now));
+        let next_full_at = full_at + interval;
+
+        if next_full_at - now <= burst {
+            let state = LimitState {
+                timestamp: next_full_at,
+                count: 0,
+                previous_count: 0,
+                expires_at: next_full_at,
+            };
+            let decision = Decision {
+                allowed: true,
+                limit: self.limit,
+                remaining: (burst - (next_full_at - now)) / interval,
+                reset: Duration::from_micros(next_full_at - now),
+                retry_after: None,
+            };
+            return (state, decision);
+        }
+
+        let state = LimitState {
+            timestamp: full_at,
+            count: 0,
+            previous_count: 0,
+            expires_at: full_at,
+        };
+        let decision = Decision {
+            allowed: false,
+            limit: self.limit,
+            remaining: 0,
+            reset: Duration::from_micros(full_at - now),
+            retry_after: Some(Duration::from_micros(next_full_at - burst - now)),
+        };
+        (state, decision)
+    }
+
+    /// Count a request with the sliding window algorithm: the requests of the previous
+    /// window are weighted by how much of it is still in the sliding window
+    /// ## Args
+    /// - self
+    /// - state: Option<&LimitState>
+    /// - now: u64
+    /// ## Returns
+    /// - (LimitState, Decision)
+    fn apply_sliding_window(&self, state: Option<&LimitState>, now: u64) -> (LimitState, Decision) {
+        let window = micros(self.period);
+        let start = now - now % window;
+        let elapsed = now - start;
+        let (count, previous_count) = match state {
+            Some(state) if state.timestamp == start => (state.count, state.previous_count),
+            Some(state) if state.timestamp + window == start => (0, state.count),
+            _ => (0, 0),
+        };
+
+        let used = weighted(previous_count, window - elapsed, window) + count;
+        let allowed = used < self.limit;
+        let count = if allowed { count + 1 } else { count };
+        let retry_after = if allowed {
+            None
+        } else if count < self.limit {
+            // Wait for the previous window to slide out enough
+            let allowed_at = allowed_from(previous_count, self.limit - count, window);
+            Some(allowed_at - elapsed)
+        } else {
+            // Wait for the next window, where this one is the previous window
+            let allowed_at = allowed_from(count, self.limit, window);
+            Some(window - elapsed + allowed_at)
+        };
+
+        let state = LimitState {
+            timestamp: start,
+            count,
+            previous_count,
+            expires_at: start + 2 * window,
+        };
+        let decision = Decision {
+            allowed,
+            limit: self.limit,
+            remaining: self.limit.saturating_sub(used + 1),
+            reset: Duration::from_micros(window - elapsed),
+            retry_after: retry_after.map(Duration::from_micros),
+        };
+        (state, decision)
+    }
+}
+
+/// Get a Duration in microseconds
+/// ## Args
+/// - duration: Duration
+/// ## Returns
+/// - u64
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+/// Weight the requests of the previous window
+/// ## Args
+/// - previous_count: u64
+/// - remaining: u64 (time of the previous window still in the sliding window)
+/// - window: u64
+/// ## Returns
+/// - u64 (rounded down)
+fn weighted(previous_count: u64, remaining: u64, window: u64) -> u64 {
+    (previous_count as u128 * remaining as u128 / window as u128) as u64
+}
+
+/// Get the time in a window from which the previous window weighs less than `free`
+/// ## Args
+/// - previous_count: u64
+/// - free: u64 (requests left to the current window, at least 1)
+/// - window: u64
+/// ## Returns
+/// - u64 (time since the start of the window)
+fn allowed_from(previous_count: u64, free: u64, window: u64) -> u64 {
+    if previous_count == 0 {
+        return 0;
+    }
+    // previous_count * (window - t) / window < free
+    let weight = (free as u128 * window as u128).div_ceil(previous_count as u128);
+    (window as u128 + 1).saturating_sub(weight) as u64
+}
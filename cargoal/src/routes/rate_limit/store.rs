@@ -0,0 +1,85 @@
+use super::quota::{Decision, Quota};
+use std::fmt;
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Define the RateLimitError enum
+/// ## Variants
+/// - Database(sqlx::Error): the database of a store failed
+/// - Store(String): another store failed
+#[derive(Debug)]
+pub enum RateLimitError {
+    Database(sqlx::Error),
+    Store(String),
+}
+
+/// Implement the Display trait for RateLimitError
+impl fmt::Display for RateLimitError {
+    /// Format the RateLimitError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitError::Database(err) => write!(f, "Rate limit database error: {}", err),
+            RateLimitError::Store(reason) => write!(f, "Rate limit store error: {}", reason),
+        }
+    }
+}
+
+/// Implement the Error trait for RateLimitError
+impl std::error::Error for RateLimitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RateLimitError::Database(err) => Some(err),
+            RateLimitError::Store(_) => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for RateLimitError {
+    fn from(err: sqlx::Error) -> Self {
+        RateLimitError::Database(err)
+    }
+}
+
+/// Define the RateLimitStore trait
+/// Where the LimitState of the clients are kept. A store shared by several instances
+/// of the application (e.g. on a `Database`) limits the clients across all of them
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Count a request: load the state of the key, give it to `quota.apply` and keep
+    /// the new state, without letting a concurrent request of the same key in between
+    /// ## Args
+    /// - self
+    /// - key: &str
+    /// - quota: &Quota
+    /// - now: u64 (Unix timestamp, in microseconds)
+    /// ## Returns
+    /// - Result<Decision, RateLimitError>
+    fn hit(
+        &self,
+        key: &str,
+        quota: &Quota,
+        now: u64,
+    ) -> impl Future<Output = Result<Decision, RateLimitError>> + Send;
+
+    /// Delete the expired states
+    /// ## Args
+    /// - self
+    /// - now: u64 (Unix timestamp, in microseconds)
+    /// ## Returns
+    /// - Result<u64, RateLimitError> (the number of deleted states)
+    fn cleanup(&self, now: u64) -> impl Future<Output = Result<u64, RateLimitError>> + Send;
+}
+
+/// Get the current Unix timestamp
+/// ## Returns
+/// - u64 (in microseconds)
+pub(crate) fn unix_now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
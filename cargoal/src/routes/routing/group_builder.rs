@@ -1,20 +1,30 @@
+use crate::routes::extract::state::AppState;
 use crate::routes::http::method::HttpMethod;
-use crate::routes::http::request::Request;
-use crate::routes::http::response::Response;
-use crate::routes::routing::middleware::Middleware;
+use crate::routes::routing::middleware::{BoxedMiddleware, IntoMiddleware};
 use crate::routes::routing::route_builder::RouteBuilder;
 use crate::routes::server::server_handle::ServerHandle;
+use std::future::Future;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 
 /// Define the GroupBuilder struct
+/// Its settings apply to every route (and nested group) created inside the group
 /// ## Fields
 /// - prefix: String
 /// - server: ServerHandle
-/// - middlewares: Vec<Middleware>
+/// - middlewares: Vec<BoxedMiddleware>
+/// - subdomain: Option<String>
+/// - template_dirs: Vec<String>
+/// - layout: Option<String>
+/// - state: AppState
 pub struct GroupBuilder {
     prefix: String,
     server: ServerHandle,
-    middlewares: Vec<Middleware>,
+    middlewares: Vec<BoxedMiddleware>,
+    subdomain: Option<String>,
+    template_dirs: Vec<String>,
+    layout: Option<String>,
+    state: AppState,
 }
 
 /// Implement the GroupBuilder struct
@@ -30,6 +40,10 @@ impl GroupBuilder {
             prefix: prefix.to_string(),
             server,
             middlewares: Vec::new(),
+            subdomain: None,
+            template_dirs: Vec::new(),
+            layout: None,
+            state: AppState::default(),
         }
     }
 
@@ -44,25 +58,103 @@ impl GroupBuilder {
         let mut route_builder = self.server.route(&full_path, method);
 
         for middleware in &self.middlewares {
-            let middleware_ref = Arc::clone(middleware);
-            route_builder = route_builder.with_middleware(move |req| middleware_ref(req));
+            route_builder = route_builder.with_boxed_middleware(Arc::clone(middleware));
+        }
+        if let Some(subdomain) = &self.subdomain {
+            route_builder = route_builder.with_subdomain(subdomain);
+        }
+        for dir in &self.template_dirs {
+            route_builder = route_builder.with_template_dir(dir);
+        }
+        if let Some(layout) = &self.layout {
+            route_builder = route_builder.with_layout(layout);
         }
 
-        route_builder
+        route_builder.with_group_state(self.state.clone())
+    }
+
+    /// Add a nested group, which starts with the prefix and the settings of this group
+    /// ## Args
+    /// - prefix: &str (appended to the prefix of this group)
+    /// - group: F
+    /// ## Where
+    /// - F: FnOnce(Arc<Mutex<GroupBuilder>>) -> Fut
+    /// - Fut: Future<Output = ()>
+    /// ## Side Effects
+    /// - Adds the routes of the nested group to the Server
+    pub async fn group<F, Fut>(&self, prefix: &str, group: F)
+    where
+        F: FnOnce(Arc<Mutex<GroupBuilder>>) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let nested = GroupBuilder {
+            prefix: format!("{}{}", self.prefix, prefix),
+            server: self.server.clone(),
+            middlewares: self.middlewares.clone(),
+            subdomain: self.subdomain.clone(),
+            template_dirs: self.template_dirs.clone(),
+            layout: self.layout.clone(),
+            state: self.state.clone(),
+        };
+
+        group(Arc::new(Mutex::new(nested))).await;
     }
 
     /// Add a middleware to the Group
+    /// Group middlewares run after the global ones and before the ones of the routes
     /// ## Args
     /// - middleware: F
     /// ## Where
-    /// - F: Fn(&Request) -> Option<Response> + Send + Sync + 'static
+    /// - F: IntoMiddleware<M> (see `RouteBuilder::with_middleware`)
     /// ## Returns
     /// - &mut Self
-    pub fn add_middleware<F>(&mut self, middleware: F) -> &mut Self
+    pub fn add_middleware<F, M>(&mut self, middleware: F) -> &mut Self
     where
-        F: Fn(&Request) -> Option<Response> + Send + Sync + 'static,
+        F: IntoMiddleware<M>,
     {
-        self.middlewares.push(Arc::new(middleware));
+        self.middlewares.push(middleware.into_middleware());
+        self
+    }
+
+    /// Set the subdomain of the routes of the Group
+    /// ## Args
+    /// - subdomain: &str
+    /// ## Returns
+    /// - &mut Self
+    pub fn with_subdomain(&mut self, subdomain: &str) -> &mut Self {
+        self.subdomain = Some(subdomain.to_string());
+        self
+    }
+
+    /// Add a template directory for the routes of the Group
+    /// Its templates take precedence over the templates of the Server with the same name
+    /// ## Args
+    /// - dir: &str
+    /// ## Returns
+    /// - &mut Self
+    pub fn with_template_dir(&mut self, dir: &str) -> &mut Self {
+        self.template_dirs.push(dir.to_string());
+        self
+    }
+
+    /// Set the layout of the templates of the Group (see `RouteBuilder::with_layout`)
+    /// ## Args
+    /// - layout: &str (template name)
+    /// ## Returns
+    /// - &mut Self
+    pub fn with_layout(&mut self, layout: &str) -> &mut Self {
+        self.layout = Some(layout.to_string());
+        self
+    }
+
+    /// Share a value with the handlers of the Group, which get it with the `State<T>`
+    /// extractor, in place of the value of the same type given to the Server
+    /// ## Args
+    /// - state: T
+    /// ## Returns
+    /// - &mut Self
+    pub fn with_state<T: Send + Sync + 'static>(&mut self, state: T) -> &mut Self {
+        self.state.insert(state);
         self
     }
 }
@@ -0,0 +1,113 @@
+use crate::routes::extract::FromRequest;
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Define the BoxFuture type
+pub(crate) type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Define the Handler type
+pub(crate) type Handler = Arc<dyn Fn(Request) -> BoxFuture<'static, Response> + Send + Sync>;
+
+/// Marker for synchronous handlers: Fn(Request) -> Response
+pub struct SyncHandler;
+
+/// Marker for asynchronous handlers: Fn(Request) -> impl Future<Output = Response>
+pub struct AsyncHandler;
+
+/// Define the IntoHandler trait
+/// Implemented for every function or closure that can be used as a route handler
+/// ## Type Parameters
+/// - M: marker type distinguishing the handler kinds
+pub trait IntoHandler<M>: Send + Sync + 'static {
+    /// Convert the value into a Handler
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Handler
+    fn into_handler(self) -> Handler;
+}
+
+/// Implement IntoHandler for synchronous handlers
+impl<F> IntoHandler<SyncHandler> for F
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    fn into_handler(self) -> Handler {
+        Arc::new(move |req: Request| {
+            let response = self(req);
+            Box::pin(async move { response })
+        })
+    }
+}
+
+/// Implement IntoHandler for asynchronous handlers
+impl<F, Fut> IntoHandler<(AsyncHandler, Fut)> for F
+where
+    F: Fn(Request) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Response> + Send + 'static,
+{
+    fn into_handler(self) -> Handler {
+        Arc::new(move |req: Request| Box::pin(self(req)))
+    }
+}
+
+/// Marker for synchronous handlers taking extractors: Fn(T1, T2, ...) -> Response
+pub struct SyncExtractorHandler;
+
+/// Marker for asynchronous handlers taking extractors: Fn(T1, T2, ...) -> impl Future<Output = Response>
+pub struct AsyncExtractorHandler;
+
+/// Implement IntoHandler for handlers whose arguments are all extractors
+/// Each argument is extracted in order, the first failing extractor answers the Request
+macro_rules! impl_extractor_handlers {
+    ($($ty:ident),*) => {
+        #[allow(non_snake_case, unused_variables)]
+        impl<F, $($ty,)*> IntoHandler<(SyncExtractorHandler, ($($ty,)*))> for F
+        where
+            F: Fn($($ty),*) -> Response + Send + Sync + 'static,
+            $($ty: FromRequest + Send + 'static,)*
+        {
+            fn into_handler(self) -> Handler {
+                Arc::new(move |req: Request| {
+                    $(let $ty = match $ty::from_request(&req) {
+                        Ok(value) => value,
+                        Err(rejection) => return Box::pin(async move { rejection }) as BoxFuture<'static, Response>,
+                    };)*
+                    let response = self($($ty),*);
+                    Box::pin(async move { response })
+                })
+            }
+        }
+
+        #[allow(non_snake_case, unused_variables)]
+        impl<F, Fut, $($ty,)*> IntoHandler<(AsyncExtractorHandler, ($($ty,)*), Fut)> for F
+        where
+            F: Fn($($ty),*) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Response> + Send + 'static,
+            $($ty: FromRequest + Send + 'static,)*
+        {
+            fn into_handler(self) -> Handler {
+                Arc::new(move |req: Request| {
+                    $(let $ty = match $ty::from_request(&req) {
+                        Ok(value) => value,
+                        Err(rejection) => return Box::pin(async move { rejection }) as BoxFuture<'static, Response>,
+                    };)*
+                    Box::pin(self($($ty),*))
+                })
+            }
+        }
+    };
+}
+
+impl_extractor_handlers!();
+impl_extractor_handlers!(T1);
+impl_extractor_handlers!(T1, T2);
+impl_extractor_handlers!(T1, T2, T3);
+impl_extractor_handlers!(T1, T2, T3, T4);
+impl_extractor_handlers!(T1, T2, T3, T4, T5);
+impl_extractor_handlers!(T1, T2, T3, T4, T5, T6);
+impl_extractor_handlers!(T1, T2, T3, T4, T5, T6, T7);
+impl_extractor_handlers!(T1, T2, T3, T4, T5, T6, T7, T8);
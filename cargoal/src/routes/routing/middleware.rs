@@ -1,6 +1,156 @@
 use crate::routes::http::request::Request;
 use crate::routes::http::response::Response;
+use crate::routes::routing::handler::{BoxFuture, Handler};
+use std::future::Future;
 use std::sync::Arc;
 
-/// Define the Middleware type
-pub(crate) type Middleware = Arc<dyn Fn(&Request) -> Option<Response> + Send + Sync>;
+/// Define the BoxedMiddleware type
+pub(crate) type BoxedMiddleware =
+    Arc<dyn Fn(Request, Next) -> BoxFuture<'static, Response> + Send + Sync>;
+
+/// Define the Next struct
+/// The rest of the chain after a middleware: the next middlewares, then the handler
+/// ## Fields
+/// - middlewares: Arc<Vec<BoxedMiddleware>>
+/// - index: usize (index of the next middleware to run)
+/// - endpoint: Handler
+pub struct Next {
+    middlewares: Arc<Vec<BoxedMiddleware>>,
+    index: usize,
+    endpoint: Handler,
+}
+
+/// Implement the Next struct
+impl Next {
+    /// Create a new chain
+    /// ## Args
+    /// - middlewares: Arc<Vec<BoxedMiddleware>> (run in order)
+    /// - endpoint: Handler (run after the last middleware)
+    /// ## Returns
+    /// - Next
+    pub(crate) fn new(middlewares: Arc<Vec<BoxedMiddleware>>, endpoint: Handler) -> Self {
+        Self {
+            middlewares,
+            index: 0,
+            endpoint,
+        }
+    }
+
+    /// Run the rest of the chain
+    /// ## Args
+    /// - self
+    /// - req: Request
+    /// ## Returns
+    /// - Response
+    pub async fn run(mut self, req: Request) -> Response {
+        match self.middlewares.get(self.index).cloned() {
+            Some(middleware) => {
+                self.index += 1;
+                middleware(req, self).await
+            }
+            None => (self.endpoint)(req).await,
+        }
+    }
+}
+
+/// Define the Middleware trait
+/// An around-style middleware: it can change the Request, answer without calling the
+/// rest of the chain, or change the Response returned by `next.run(req)`
+///
+/// Middlewares run in the order global (`ServerHandle::add_middleware`), group
+/// (`GroupBuilder::add_middleware`, outer groups first) then route
+/// (`RouteBuilder::with_middleware`), each level in the order the middlewares were
+/// added; the Response goes back through them in the reverse order
+pub trait Middleware: Send + Sync + 'static {
+    /// Handle a Request
+    /// ## Args
+    /// - self
+    /// - req: Request
+    /// - next: Next
+    /// ## Returns
+    /// - Response
+    fn handle(&self, req: Request, next: Next) -> impl Future<Output = Response> + Send;
+}
+
+/// Marker for synchronous middlewares: Fn(&Request) -> Option<Response>
+pub struct SyncMiddleware;
+
+/// Marker for asynchronous middlewares: Fn(&Request) -> impl Future<Output = Option<Response>>
+pub struct AsyncMiddleware;
+
+/// Marker for around-style middlewares: Fn(Request, Next) -> impl Future<Output = Response>
+pub struct AroundMiddleware;
+
+/// Marker for the types implementing the Middleware trait
+pub struct TraitMiddleware;
+
+/// Define the IntoMiddleware trait
+/// Implemented for every function, closure or type that can be used as a middleware.
+/// Asynchronous middlewares receive a borrowed Request, so the returned future
+/// must own everything it needs (clone the values out of the Request first).
+/// ## Type Parameters
+/// - M: marker type distinguishing the middleware kinds
+pub trait IntoMiddleware<M>: Send + Sync + 'static {
+    /// Convert the value into a BoxedMiddleware
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - BoxedMiddleware
+    fn into_middleware(self) -> BoxedMiddleware;
+}
+
+/// Implement IntoMiddleware for synchronous middlewares
+/// The Request goes on to the rest of the chain when the middleware returns None
+impl<F> IntoMiddleware<SyncMiddleware> for F
+where
+    F: Fn(&Request) -> Option<Response> + Send + Sync + 'static,
+{
+    fn into_middleware(self) -> BoxedMiddleware {
+        Arc::new(move |req: Request, next: Next| match self(&req) {
+            Some(response) => Box::pin(async move { response }),
+            None => Box::pin(next.run(req)),
+        })
+    }
+}
+
+/// Implement IntoMiddleware for asynchronous middlewares
+/// The Request goes on to the rest of the chain when the middleware returns None
+impl<F, Fut> IntoMiddleware<(AsyncMiddleware, Fut)> for F
+where
+    F: Fn(&Request) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<Response>> + Send + 'static,
+{
+    fn into_middleware(self) -> BoxedMiddleware {
+        Arc::new(move |req: Request, next: Next| {
+            let future = self(&req);
+            Box::pin(async move {
+                match future.await {
+                    Some(response) => response,
+                    None => next.run(req).await,
+                }
+            })
+        })
+    }
+}
+
+/// Implement IntoMiddleware for around-style middleware functions
+impl<F, Fut> IntoMiddleware<(AroundMiddleware, Fut)> for F
+where
+    F: Fn(Request, Next) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Response> + Send + 'static,
+{
+    fn into_middleware(self) -> BoxedMiddleware {
+        Arc::new(move |req: Request, next: Next| Box::pin(self(req, next)))
+    }
+}
+
+/// Implement IntoMiddleware for the types implementing the Middleware trait
+impl<T: Middleware> IntoMiddleware<TraitMiddleware> for T {
+    fn into_middleware(self) -> BoxedMiddleware {
+        let middleware = Arc::new(self);
+        Arc::new(move |req: Request, next: Next| {
+            let middleware = Arc::clone(&middleware);
+            Box::pin(async move { middleware.handle(req, next).await })
+        })
+    }
+}
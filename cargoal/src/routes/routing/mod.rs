@@ -1,10 +1,16 @@
 pub(crate) mod group_builder;
+pub(crate) mod handler;
 pub(crate) mod middleware;
+pub(crate) mod pattern;
 pub(crate) mod route;
 pub(crate) mod route_builder;
 pub(crate) mod router;
+pub(crate) mod url;
 
 pub use group_builder::GroupBuilder;
+pub use handler::IntoHandler;
+pub use middleware::{IntoMiddleware, Middleware, Next};
 pub use route::Route;
 pub use route_builder::RouteBuilder;
-pub use router::Router;
+pub use router::{RouteError, RouteMatch, Router};
+pub use url::{UrlError, UrlGenerator};
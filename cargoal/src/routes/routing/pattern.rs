@@ -0,0 +1,149 @@
+use crate::routes::routing::router::RouteError;
+use regex::Regex;
+
+/// Define the Segment enum
+/// A segment of a route path
+/// ## Variants
+/// - Static(&str): a literal segment (`users`)
+/// - Param: a named segment (`:id`), with an optional constraint (`:id<int>`)
+/// - Wildcard(&str): a catch-all for the remaining segments (`*rest`)
+#[derive(Debug, Clone)]
+pub(crate) enum Segment<'p> {
+    Static(&'p str),
+    Param {
+        name: &'p str,
+        constraint: Option<Constraint>,
+    },
+    Wildcard(&'p str),
+}
+
+/// Define the Constraint struct
+/// ## Fields
+/// - source: String (as written in the path, e.g. `int` or `[a-z-]+`)
+/// - regex: Regex (anchored, matching a whole segment)
+#[derive(Debug, Clone)]
+pub(crate) struct Constraint {
+    pub(crate) source: String,
+    pub(crate) regex: Regex,
+}
+
+/// Implement the Constraint struct
+impl Constraint {
+    /// Compile a constraint, resolving the named ones
+    /// - int: `[0-9]+`
+    /// - alpha: `[a-zA-Z]+`
+    /// - alnum: `[a-zA-Z0-9]+`
+    /// - uuid: a hyphenated UUID
+    /// - anything else is used as a regex
+    /// ## Args
+    /// - path: &str
+    /// - source: &str
+    /// ## Returns
+    /// - Result<Constraint, RouteError>
+    fn compile(path: &str, source: &str) -> Result<Constraint, RouteError> {
+        let pattern = match source {
+            "int" => "[0-9]+",
+            "alpha" => "[a-zA-Z]+",
+            "alnum" => "[a-zA-Z0-9]+",
+            "uuid" => "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+            pattern => pattern,
+        };
+
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(|regex| Constraint {
+                source: source.to_string(),
+                regex,
+            })
+            .map_err(|err| RouteError::InvalidRegex {
+                path: path.to_string(),
+                reason: err.to_string(),
+            })
+    }
+}
+
+/// Parse a route path into the segment lists it matches
+/// Trailing optional segments (`/archive/:year?/:month?`) expand into one list per
+/// accepted length (`/archive`, `/archive/:year`, `/archive/:year/:month`)
+/// ## Args
+/// - path: &str
+/// ## Returns
+/// - Result<Vec<Vec<Segment>>, RouteError>
+pub(crate) fn parse_path(path: &str) -> Result<Vec<Vec<Segment<'_>>>, RouteError> {
+    let (segments, first_optional) = parse_segments(path)?;
+
+    let expansions = match first_optional {
+        Some(first) => (first..=segments.len())
+            .map(|len| match len {
+                // Every segment is optional: the shortest form is the root path
+                0 => vec![Segment::Static("")],
+                len => segments[..len].to_vec(),
+            })
+            .collect(),
+        None => vec![segments],
+    };
+
+    Ok(expansions)
+}
+
+/// Parse a route path into its segments
+/// ## Args
+/// - path: &str
+/// ## Returns
+/// - Result<(Vec<Segment>, Option<usize>), RouteError> (the segments and the index of the
+///   first optional one)
+pub(crate) fn parse_segments(path: &str) -> Result<(Vec<Segment<'_>>, Option<usize>), RouteError> {
+    let invalid = |reason: &str| RouteError::InvalidPath {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let raw_segments: Vec<&str> = path
+        .strip_prefix('/')
+        .ok_or_else(|| invalid("the path must start with '/'"))?
+        .split('/')
+        .collect();
+
+    let mut segments = Vec::with_capacity(raw_segments.len());
+    let mut first_optional = None;
+    for (i, raw) in raw_segments.iter().enumerate() {
+        let (raw, optional) = match raw.strip_suffix('?') {
+            Some(raw) => (raw, true),
+            None => (*raw, false),
+        };
+        match (optional, first_optional) {
+            (true, None) => first_optional = Some(i),
+            (false, Some(_)) => return Err(invalid("optional segments must be at the end")),
+            _ => {}
+        }
+
+        let segment = if let Some(name) = raw.strip_prefix('*') {
+            if i != raw_segments.len() - 1 {
+                return Err(invalid("a wildcard must be the last segment"));
+            }
+            if name.is_empty() {
+                return Err(invalid("a wildcard must have a name"));
+            }
+            Segment::Wildcard(name)
+        } else if let Some(param) = raw.strip_prefix(':') {
+            let (name, constraint) = match param.split_once('<') {
+                Some((name, constraint)) => {
+                    let constraint = constraint
+                        .strip_suffix('>')
+                        .filter(|constraint| !constraint.is_empty())
+                        .ok_or_else(|| invalid("a constraint must be written `<...>`"))?;
+                    (name, Some(Constraint::compile(path, constraint)?))
+                }
+                None => (param, None),
+            };
+            if name.is_empty() {
+                return Err(invalid("a parameter must have a name"));
+            }
+            Segment::Param { name, constraint }
+        } else {
+            Segment::Static(raw)
+        };
+        segments.push(segment);
+    }
+
+    Ok((segments, first_optional))
+}
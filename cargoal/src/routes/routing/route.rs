@@ -1,7 +1,5 @@
 use crate::routes::http::method::HttpMethod;
-use crate::routes::http::request::Request;
-use crate::routes::http::response::Response;
-use crate::routes::routing::middleware::Middleware;
+use crate::routes::routing::handler::Handler;
 use regex::Regex;
 
 /// Define the Route struct
@@ -9,14 +7,42 @@ use regex::Regex;
 /// - subdomain: Option<String>
 /// - path: String
 /// - method: HttpMethod
-/// - handler: Box<dyn Fn(Request) -> Response + Send + Sync>
+/// - handler: Handler
 /// - regex: Option<Regex>
-/// - middlewares: Vec<Middleware>
 pub struct Route {
     pub(crate) subdomain: Option<String>,
     pub(crate) path: String,
     pub(crate) method: HttpMethod,
-    pub(crate) handler: Box<dyn Fn(Request) -> Response + Send + Sync>,
+    pub(crate) handler: Handler,
     pub(crate) regex: Option<Regex>,
-    pub(crate) middlewares: Vec<Middleware>,
+}
+
+/// Implement the Route struct
+impl Route {
+    /// Get the path pattern of the Route
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &str
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the method of the Route
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &HttpMethod
+    pub fn method(&self) -> &HttpMethod {
+        &self.method
+    }
+
+    /// Get the subdomain of the Route
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<&str>
+    pub fn subdomain(&self) -> Option<&str> {
+        self.subdomain.as_deref()
+    }
 }
@@ -1,13 +1,24 @@
+use crate::renderer::rendering::{CSP_NONCE_VAR, CSRF_TOKEN_VAR};
+use crate::renderer::TemplateRenderer;
+use crate::routes::extract::state::{AppState, GroupState};
 use crate::routes::http::method::HttpMethod;
 use crate::routes::http::request::Request;
 use crate::routes::http::response::Response;
-use crate::routes::routing::middleware::Middleware;
+use crate::routes::http::status::StatusCode;
+use crate::routes::middlewares::{csrf_rejection, CspNonce, CsrfToken};
+use crate::routes::routing::handler::{Handler, IntoHandler};
+use crate::routes::routing::middleware::{BoxedMiddleware, IntoMiddleware, Next};
+use crate::routes::routing::router::RouteError;
 use crate::routes::server::server_handle::ServerHandle;
 use minijinja::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-type ContextFn = Box<dyn Fn(&Request) -> HashMap<String, Value> + Send + Sync>;
+/// Media types a Route with both a handler and a template can answer with
+/// JSON comes first so that API clients sending `*/*` (or no Accept header) get the data
+const NEGOTIATED_TYPES: [&str; 2] = ["application/json", "text/html"];
+
+type ContextFn = Arc<dyn Fn(&Request) -> HashMap<String, Value> + Send + Sync>;
 
 /// Define the RouteBuilder struct
 /// ## Fields
@@ -16,20 +27,28 @@ type ContextFn = Box<dyn Fn(&Request) -> HashMap<String, Value> + Send + Sync>;
 /// - template: Option<String>
 /// - context_fn: Option<ContextFn>
 /// - server: ServerHandle
-/// - handler: Option<Box<dyn Fn(Request) -> Response + Send + Sync>>
+/// - handler: Option<Handler>
 /// - subdomain: Option<String>
 /// - regex: Option<String>
-/// - middlewares: Vec<Middleware>
+/// - middlewares: Vec<BoxedMiddleware>
+/// - name: Option<String>
+/// - template_dirs: Vec<String>
+/// - layout: Option<String>
+/// - state: AppState (values shared by the group of the Route)
 pub struct RouteBuilder {
     path: String,
     method: HttpMethod,
     template: Option<String>,
     context_fn: Option<ContextFn>,
     server: ServerHandle,
-    handler: Option<Box<dyn Fn(Request) -> Response + Send + Sync>>,
+    handler: Option<Handler>,
     subdomain: Option<String>,
     regex: Option<String>,
-    middlewares: Vec<Middleware>,
+    middlewares: Vec<BoxedMiddleware>,
+    name: Option<String>,
+    template_dirs: Vec<String>,
+    layout: Option<String>,
+    state: AppState,
 }
 
 /// Implement the RouteBuilder struct
@@ -52,6 +71,10 @@ impl RouteBuilder {
             subdomain: None,
             regex: None,
             middlewares: Vec::new(),
+            name: None,
+            template_dirs: Vec::new(),
+            layout: None,
+            state: AppState::default(),
         }
     }
 
@@ -66,6 +89,17 @@ impl RouteBuilder {
         self
     }
 
+    /// Set the name of the Route, used to build its URL with `url_for`
+    /// ## Args
+    /// - self
+    /// - name: &str (e.g. `user.show`)
+    /// ## Returns
+    /// - RouteBuilder
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
     /// Set the template for the Route
     /// ## Args
     /// - self
@@ -77,6 +111,42 @@ impl RouteBuilder {
         self
     }
 
+    /// Add a template directory for the Route
+    /// Its templates take precedence over the templates of the Server with the same name
+    /// ## Args
+    /// - self
+    /// - dir: &str
+    /// ## Returns
+    /// - RouteBuilder
+    pub fn with_template_dir(mut self, dir: &str) -> Self {
+        self.template_dirs.push(dir.to_string());
+        self
+    }
+
+    /// Set the layout of the template of the Route
+    /// The layout is rendered with the context of the Route and the rendered template
+    /// as `content` (`{{ content }}`)
+    /// ## Args
+    /// - self
+    /// - layout: &str (template name)
+    /// ## Returns
+    /// - RouteBuilder
+    pub fn with_layout(mut self, layout: &str) -> Self {
+        self.layout = Some(layout.to_string());
+        self
+    }
+
+    /// Set the values shared by the group of the Route
+    /// ## Args
+    /// - self
+    /// - state: AppState
+    /// ## Returns
+    /// - RouteBuilder
+    pub(crate) fn with_group_state(mut self, state: AppState) -> Self {
+        self.state = state;
+        self
+    }
+
     /// Set the context function for the Route
     /// ## Args
     /// - self
@@ -91,7 +161,7 @@ impl RouteBuilder {
         F: Fn(&Request) -> HashMap<String, V> + Send + Sync + 'static,
         V: Into<Value>,
     {
-        self.context_fn = Some(Box::new(move |req| {
+        self.context_fn = Some(Arc::new(move |req| {
             context_fn(req)
                 .into_iter()
                 .map(|(k, v)| (k, v.into()))
@@ -101,6 +171,8 @@ impl RouteBuilder {
     }
 
     /// Set the regex for the Route
+    /// The request path must match it, and the Route also serves the paths it matches
+    /// outside of the path pattern. Its named groups are parameters of the Route
     /// ## Args
     /// - self
     /// - regex: &str
@@ -112,34 +184,52 @@ impl RouteBuilder {
     }
 
     /// Add a Middleware to the Route
+    /// Route middlewares run after the global and group ones, in the order they are added
     /// ## Args
     /// - self
     /// - middleware: F
     /// ## Where
-    /// - F: Fn(&Request) -> Option<Response> + Send + Sync + 'static
+    /// - F: IntoMiddleware<M> (a `Middleware`, an around-style
+    ///   `Fn(Request, Next) -> impl Future<Output = Response>`, or a short-circuiting sync
+    ///   `Fn(&Request) -> Option<Response>` or async
+    ///   `Fn(&Request) -> impl Future<Output = Option<Response>>`)
     /// ## Returns
     /// - RouteBuilder
-    pub fn with_middleware<F>(mut self, middleware: F) -> Self
+    pub fn with_middleware<F, M>(mut self, middleware: F) -> Self
     where
-        F: Fn(&Request) -> Option<Response> + Send + Sync + 'static,
+        F: IntoMiddleware<M>,
     {
-        self.middlewares.push(Arc::new(middleware));
+        self.middlewares.push(middleware.into_middleware());
+        self
+    }
+
+    /// Add an already built Middleware to the Route
+    /// ## Args
+    /// - self
+    /// - middleware: BoxedMiddleware
+    /// ## Returns
+    /// - RouteBuilder
+    pub(crate) fn with_boxed_middleware(mut self, middleware: BoxedMiddleware) -> Self {
+        self.middlewares.push(middleware);
         self
     }
 
     /// Set the handler for the Route
+    /// When a template is also set, the Accept header chooses between the handler
+    /// (JSON) and the rendered template (HTML)
     /// ## Args
     /// - self
     /// - handler: F
     /// ## Where
-    /// - F: Fn(Request) -> Response + Send + Sync + 'static
+    /// - F: IntoHandler<M> (sync `Fn(Request) -> Response` or async
+    ///   `Fn(Request) -> impl Future<Output = Response>`)
     /// ## Returns
     /// - RouteBuilder
-    pub fn with_handler<F>(mut self, handler: F) -> Self
+    pub fn with_handler<F, M>(mut self, handler: F) -> Self
     where
-        F: Fn(Request) -> Response + Send + Sync + 'static,
+        F: IntoHandler<M>,
     {
-        self.handler = Some(Box::new(handler));
+        self.handler = Some(handler.into_handler());
         self
     }
 
@@ -148,16 +238,35 @@ impl RouteBuilder {
     /// - self
     /// ## Side Effects
     /// - Adds the Route to the Server's Router
+    /// ## Panics
+    /// - If the Route is invalid or conflicts with a registered Route (see `try_register`)
     pub async fn register(self) {
-        let template = self.template.map(|t| t.to_string()).clone();
+        if let Err(err) = self.try_register().await {
+            panic!("{}", err);
+        }
+    }
+
+    /// Register the Route with the Server, reporting invalid or conflicting Routes
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Result<(), RouteError>
+    /// ## Side Effects
+    /// - Adds the Route to the Server's Router
+    pub async fn try_register(self) -> Result<(), RouteError> {
+        let template = self.template.map(|template| Template {
+            name: template,
+            layout: self.layout,
+        });
         let context_fn = self.context_fn;
         let handler = self.handler;
         let subdomain = self.subdomain;
         let regex = self.regex;
-        let middlewares = self.middlewares.clone();
+        let middlewares = Arc::new(self.middlewares);
 
         // Prepare the route
-        let renderer = self.server.get_template_renderer().await;
+        let renderer = Arc::new(self.server.get_template_renderer(&self.template_dirs).await);
+        let state = (!self.state.is_empty()).then(|| Arc::new(self.state));
         let path = self.path.to_string();
         let method = self.method.clone();
 
@@ -165,53 +274,160 @@ impl RouteBuilder {
         let router_handle = self.server.router();
         let mut router = router_handle.write().await;
 
+        let urls = self.server.urls();
+        if let Some(name) = self.name.as_deref().filter(|name| urls.contains(name)) {
+            return Err(RouteError::DuplicateName(name.to_string()));
+        }
+
+        // The endpoint runs after the middlewares of the route
+        let endpoint: Handler = Arc::new(move |req: Request| {
+            let handler = handler.clone();
+            let template = template.clone();
+            let context_fn = context_fn.clone();
+            let renderer = Arc::clone(&renderer);
+
+            Box::pin(async move {
+                // The requests rejected by the Csrf middleware reach the route, in case
+                // its group is exempt
+                if let Some(response) = csrf_rejection(&req) {
+                    return response;
+                }
+
+                // With both a handler and a template, the Accept header chooses between
+                // the data (JSON) and the rendered page (HTML)
+                if let (Some(handler), Some(_)) = (&handler, &template) {
+                    let response = match req.negotiate(&NEGOTIATED_TYPES) {
+                        Some("text/html") => render_template(&renderer, template, context_fn, &req),
+                        Some(_) => handler(req).await,
+                        None => Response::new(
+                            StatusCode::NOT_ACCEPTABLE,
+                            format!("Available media types: {}", NEGOTIATED_TYPES.join(", ")),
+                        )
+                        .with_header("Content-Type", "text/plain"),
+                    };
+                    return response.with_header("Vary", "Accept");
+                }
+
+                // If a handler is set, use it
+                if let Some(handler) = &handler {
+                    return handler(req).await;
+                }
+
+                render_template(&renderer, template, context_fn, &req)
+            })
+        });
+
         // Add the route to the server
         router.add_route(
             subdomain.as_deref(),
             &path,
             method,
-            move |req: Request| {
-                for middleware in &middlewares {
-                    if let Some(response) = middleware(&req) {
-                        return response;
-                    }
+            Arc::new(move |mut req: Request| {
+                if let Some(state) = &state {
+                    req.extensions.insert(GroupState(Arc::clone(state)));
                 }
+                Box::pin(Next::new(Arc::clone(&middlewares), Arc::clone(&endpoint)).run(req))
+            }),
+            regex.as_deref(),
+        )?;
 
-                // If a handler is set, use it
-                if let Some(handler) = &handler {
-                    return handler(req);
-                }
+        match self.name {
+            Some(name) => urls.register(&name, &path),
+            None => Ok(()),
+        }
+    }
+}
 
-                // If a template is set, render it
-                let context = context_fn.as_ref().map_or_else(HashMap::new, |f| f(&req));
-                let rendered = match template.clone() {
-                    Some(t) => match renderer.render(&t, &context) {
-                        Ok(output) => {
-                            return Response::new(200, Some(output))
-                                .with_header("Content-Type", "text/html")
-                        }
-                        Err(err) => {
-                            eprintln!("Error rendering template '{}': {}", t, err);
-                            if err.contains("not found") {
-                                return Response::new(
-                                    404,
-                                    Some(format!("Template '{}' not found!", t)),
-                                )
-                                .with_header("Content-Type", "text/html");
-                            }
-                            return Response::new(
-                                500,
-                                Some(format!("Internal Server Error: {}", err)),
-                            )
-                            .with_header("Content-Type", "text/html");
-                        }
-                    },
-                    None => "Template not set.".to_string(),
-                };
-
-                Response::new(500, Some(rendered)).with_header("Content-Type", "text/html")
-            },
-            regex.as_deref(),
+/// Define the Template struct
+/// ## Fields
+/// - name: String
+/// - layout: Option<String>
+#[derive(Clone)]
+struct Template {
+    name: String,
+    layout: Option<String>,
+}
+
+/// Render the template of a Route, then its layout
+/// ## Args
+/// - renderer: &TemplateRenderer
+/// - template: Option<Template>
+/// - context_fn: Option<ContextFn>
+/// - req: &Request
+/// ## Returns
+/// - Response
+fn render_template(
+    renderer: &TemplateRenderer,
+    template: Option<Template>,
+    context_fn: Option<ContextFn>,
+    req: &Request,
+) -> Response {
+    // If a template is set, render it
+    let mut context = context_fn.as_ref().map_or_else(HashMap::new, |f| f(req));
+    if let Some(CsrfToken(token)) = req.extensions.get::<CsrfToken>() {
+        // The token is URL-safe base64, it cannot contain HTML special characters
+        context.insert(
+            CSRF_TOKEN_VAR.to_string(),
+            Value::from_safe_string(token.clone()),
+        );
+    }
+    if let Some(CspNonce(nonce)) = req.extensions.get::<CspNonce>() {
+        context.insert(
+            CSP_NONCE_VAR.to_string(),
+            Value::from_safe_string(nonce.clone()),
         );
     }
+    let rendered = match template {
+        Some(Template { name: t, layout }) => {
+            match render_with_layout(renderer, &t, layout.as_deref(), &mut context) {
+                Ok(output) => {
+                    return Response::new(StatusCode::OK, Some(output))
+                        .with_header("Content-Type", "text/html")
+                }
+                Err(err) => {
+                    tracing::error!(template = %t, error = %err, "Error rendering template");
+                    if err.contains("not found") {
+                        return Response::new(
+                            StatusCode::NOT_FOUND,
+                            Some(format!("Template '{}' not found!", t)),
+                        )
+                        .with_header("Content-Type", "text/html");
+                    }
+                    return Response::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Some(format!("Internal Server Error: {}", err)),
+                    )
+                    .with_header("Content-Type", "text/html");
+                }
+            }
+        }
+        None => "Template not set.".to_string(),
+    };
+
+    Response::new(StatusCode::INTERNAL_SERVER_ERROR, Some(rendered))
+        .with_header("Content-Type", "text/html")
+}
+
+/// Render a template, then the layout with the rendered template as `content`
+/// ## Args
+/// - renderer: &TemplateRenderer
+/// - template: &str
+/// - layout: Option<&str>
+/// - context: &mut HashMap<String, Value>
+/// ## Returns
+/// - Result<String, String>
+fn render_with_layout(
+    renderer: &TemplateRenderer,
+    template: &str,
+    layout: Option<&str>,
+    context: &mut HashMap<String, Value>,
+) -> Result<String, String> {
+    let content = renderer.render(template, context)?;
+    match layout {
+        Some(layout) => {
+            context.insert("content".to_string(), Value::from_safe_string(content));
+            renderer.render(layout, context)
+        }
+        None => Ok(content),
+    }
 }
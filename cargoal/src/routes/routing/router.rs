@@ -1,17 +1,159 @@
+use crate::routes::extract::state::AppState;
+use crate::routes::http::forwarded::TrustedProxies;
 use crate::routes::http::method::HttpMethod;
-use crate::routes::http::request::Request;
-use crate::routes::http::response::Response;
-use crate::routes::routing::middleware::Middleware;
+use crate::routes::routing::handler::{Handler, IntoHandler};
+use crate::routes::routing::middleware::BoxedMiddleware;
+use crate::routes::routing::pattern::{parse_path, Constraint, Segment};
 use crate::routes::routing::route::Route;
 use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Define the RouteError enum
+/// ## Variants
+/// - InvalidPath: the path of the route is malformed
+/// - InvalidRegex: the regex of the route does not compile
+/// - Conflict: a route with the same method already matches the same paths
+/// - ParamConflict: two routes use different names for the same parameter
+/// - DuplicateName(String): another route already has this name
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteError {
+    InvalidPath { path: String, reason: String },
+    InvalidRegex { path: String, reason: String },
+    Conflict { method: HttpMethod, path: String },
+    ParamConflict { path: String, existing: String },
+    DuplicateName(String),
+}
+
+/// Implement the Display trait for RouteError
+impl fmt::Display for RouteError {
+    /// Format the RouteError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::InvalidPath { path, reason } => {
+                write!(f, "Invalid route path '{}': {}", path, reason)
+            }
+            RouteError::InvalidRegex { path, reason } => {
+                write!(f, "Invalid regex for route '{}': {}", path, reason)
+            }
+            RouteError::Conflict { method, path } => {
+                write!(f, "Route {} '{}' is already registered", method, path)
+            }
+            RouteError::ParamConflict { path, existing } => write!(
+                f,
+                "Route '{}' conflicts with the parameter '{}' of an existing route",
+                path, existing
+            ),
+            RouteError::DuplicateName(name) => {
+                write!(f, "A route is already named '{}'", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+/// Define the RouteMatch struct
+/// ## Fields
+/// - route: &Route
+/// - params: HashMap<String, String> (path parameters and regex captures)
+/// - path_params: Vec<(String, String)> (the same parameters, in the order of the path)
+pub struct RouteMatch<'a> {
+    pub route: &'a Route,
+    pub params: HashMap<String, String>,
+    pub path_params: Vec<(String, String)>,
+}
+
+/// Define the Node struct
+/// A node of the routing tree, matching one segment of the path
+/// ## Fields
+/// - statics: HashMap<String, Node> (children matching a literal segment)
+/// - params: Vec<ParamNode> (children matching a non-empty segment, constrained ones first)
+/// - wildcard: Option<WildcardNode> (routes matching all the remaining segments)
+/// - routes: Vec<usize> (indexes of the routes ending at this node)
+#[derive(Default)]
+struct Node {
+    statics: HashMap<String, Node>,
+    params: Vec<ParamNode>,
+    wildcard: Option<WildcardNode>,
+    routes: Vec<usize>,
+}
+
+/// Define the ParamNode struct
+/// ## Fields
+/// - name: String
+/// - constraint: Option<Constraint>
+/// - node: Node
+struct ParamNode {
+    name: String,
+    constraint: Option<Constraint>,
+    node: Node,
+}
+
+/// Implement the ParamNode struct
+impl ParamNode {
+    /// Check if the parameter accepts a segment of the request path
+    /// ## Args
+    /// - self
+    /// - segment: &str
+    /// ## Returns
+    /// - bool
+    fn accepts(&self, segment: &str) -> bool {
+        !segment.is_empty()
+            && self
+                .constraint
+                .as_ref()
+                .is_none_or(|constraint| constraint.regex.is_match(segment))
+    }
+
+    /// Check if the parameter has a constraint written as `source`
+    /// ## Args
+    /// - self
+    /// - source: Option<&str>
+    /// ## Returns
+    /// - bool
+    fn has_constraint(&self, source: Option<&str>) -> bool {
+        self.constraint.as_ref().map(|c| c.source.as_str()) == source
+    }
+}
+
+/// Define the WildcardNode struct
+/// ## Fields
+/// - name: String
+/// - routes: Vec<usize>
+struct WildcardNode {
+    name: String,
+    routes: Vec<usize>,
+}
 
 /// Define the Router struct
+/// Routes are compiled into one tree per subdomain, where the segments of a path are
+/// matched with the priority static > `:param<constraint>` > `:param` > `*wildcard`
 /// ## Fields
 /// - routes: Vec<Route>
-/// - middlewares: Vec<Middleware>
+/// - middlewares: Arc<Vec<BoxedMiddleware>> (global middlewares)
+/// - state: Arc<AppState> (values shared by the handlers)
+/// - trusted_proxies: Arc<TrustedProxies> (proxies whose forwarding headers are read)
+/// - trees: HashMap<Option<String>, Node> (one tree per subdomain)
 pub struct Router {
     pub(crate) routes: Vec<Route>,
-    pub(crate) middlewares: Vec<Middleware>,
+    pub(crate) middlewares: Arc<Vec<BoxedMiddleware>>,
+    pub(crate) state: Arc<AppState>,
+    pub(crate) trusted_proxies: Arc<TrustedProxies>,
+    trees: HashMap<Option<String>, Node>,
+}
+
+/// Implement the Default trait for Router
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Implement the Router struct
@@ -19,62 +161,94 @@ impl Router {
     /// Create a new Router instance
     /// ## Returns
     /// - Router
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             routes: Vec::new(),
-            middlewares: Vec::new(),
+            middlewares: Arc::new(Vec::new()),
+            state: Arc::new(AppState::default()),
+            trusted_proxies: Arc::new(TrustedProxies::default()),
+            trees: HashMap::new(),
         }
     }
 
-    /// Add a route to the Router
+    /// Add a handler to the Router
     /// ## Args
     /// - subdomain: Option<&str>
-    /// - path: &str
+    /// - path: &str (segments can be literals, `:param`, `:param<constraint>` or a final
+    ///   `*wildcard`, and trailing segments can be made optional with `?`)
     /// - method: HttpMethod
     /// - handler: F
-    /// - regex: Option<&str>
     /// ## Where
-    /// - F: Fn(Request) -> Response + Send + Sync + 'static
+    /// - F: IntoHandler<M>
+    /// ## Returns
+    /// - Result<(), RouteError>
+    pub fn insert<F, M>(
+        &mut self,
+        subdomain: Option<&str>,
+        path: &str,
+        method: HttpMethod,
+        handler: F,
+    ) -> Result<(), RouteError>
+    where
+        F: IntoHandler<M>,
+    {
+        self.add_route(subdomain, path, method, handler.into_handler(), None)
+    }
+
+    /// Add a route to the Router
+    /// ## Args
+    /// - subdomain: Option<&str>
+    /// - path: &str
+    /// - method: HttpMethod
+    /// - handler: Handler
+    /// - regex: Option<&str> (the request path must match it, whether or not it matches
+    ///   the path pattern)
     /// ## Returns
-    /// - ()
+    /// - Result<(), RouteError>
     /// ## Side Effects
     /// - Adds a Route to the Router
-    pub(crate) fn add_route<F>(
+    pub(crate) fn add_route(
         &mut self,
         subdomain: Option<&str>,
         path: &str,
         method: HttpMethod,
-        handler: F,
+        handler: Handler,
         regex: Option<&str>,
-    ) where
-        F: Fn(Request) -> Response + Send + Sync + 'static,
-    {
+    ) -> Result<(), RouteError> {
         // Compile the regex if it exists
-        let compiled_regex = regex.map(|r| Regex::new(r).unwrap());
-
-        // Compile the dynamic regex if it exists
-        let dynamic_regex = regex.or_else(|| {
-            if path.contains(":") {
-                let mut regex_path = path.to_string();
-                regex_path = regex_path.replace(":", "(?P<");
-                regex_path = regex_path.replace("/", r"\/");
-                regex_path.push_str(">[^/]+)");
-                let regex_string = format!("^{}$", regex_path);
-                Some(Box::leak(regex_string.into_boxed_str()))
-            } else {
-                None
+        let regex = regex
+            .map(Regex::new)
+            .transpose()
+            .map_err(|err| RouteError::InvalidRegex {
+                path: path.to_string(),
+                reason: err.to_string(),
+            })?;
+
+        let expansions = parse_path(path)?;
+
+        // Check every form of the path before touching the tree
+        let tree = self.trees.get(&subdomain.map(String::from));
+        for segments in &expansions {
+            if let Some(tree) = tree {
+                self.check_conflicts(tree, segments, path, &method)?;
             }
-        });
-        let compiled_dynamic_regex = dynamic_regex.map(|r| Regex::new(r).unwrap());
+        }
+
+        let index = self.routes.len();
+        let tree = self.trees.entry(subdomain.map(String::from)).or_default();
+        for segments in expansions {
+            insert_segments(tree, segments, index);
+        }
 
         self.routes.push(Route {
             subdomain: subdomain.map(|s| s.to_string()),
             path: path.to_string(),
             method,
-            handler: Box::new(handler),
-            regex: compiled_regex.or(compiled_dynamic_regex),
-            middlewares: Vec::new(),
+            handler,
+            regex,
         });
+
+        Ok(())
     }
 
     /// Get the allowed methods for a path and subdomain
@@ -88,14 +262,29 @@ impl Router {
         path: &str,
         subdomain: Option<&str>,
     ) -> Vec<HttpMethod> {
-        self.routes
-            .iter()
-            .filter(|route| {
-                (route.subdomain.is_none() || route.subdomain.as_deref() == subdomain)
-                    && (route.path == path || self.match_dynamic_path(&route.path, path))
-            })
-            .map(|route| route.method.clone())
-            .collect()
+        let mut methods: Vec<HttpMethod> = Vec::new();
+        let mut matched = Vec::new();
+        if let (Some(tree), Some(segments)) = (self.tree(subdomain), split_path(path)) {
+            self.collect_matches(tree, &segments, path, &mut matched);
+        }
+        matched.extend(
+            (0..self.routes.len()).filter(|&index| self.regex_matches(index, path, subdomain)),
+        );
+        for index in matched {
+            let method = &self.routes[index].method;
+            if !methods.contains(method) {
+                methods.push(method.clone());
+            }
+        }
+
+        // HEAD is served by the GET routes and OPTIONS is always answered
+        if methods.contains(&HttpMethod::GET) && !methods.contains(&HttpMethod::HEAD) {
+            methods.push(HttpMethod::HEAD);
+        }
+        if !methods.is_empty() && !methods.contains(&HttpMethod::OPTIONS) {
+            methods.push(HttpMethod::OPTIONS);
+        }
+        methods
     }
 
     /// Find a route by path, method, and subdomain
@@ -104,77 +293,340 @@ impl Router {
     /// - method: &HttpMethod
     /// - subdomain: Option<&str>
     /// ## Returns
-    /// - Option<&Route>
-    pub(crate) fn find_route(
+    /// - Option<RouteMatch> (the Route with its parameters)
+    pub fn find_route(
         &self,
         path: &str,
         method: &HttpMethod,
         subdomain: Option<&str>,
-    ) -> Option<&Route> {
-        self.routes.iter().find(|route| {
-            route.method == *method
-                && (route.subdomain.as_deref() == subdomain)
-                && (route.path == path
-                    || route.regex.as_ref().is_some_and(|re| re.is_match(path))
-                    || self.match_dynamic_path(&route.path, path))
+    ) -> Option<RouteMatch<'_>> {
+        let mut params = Vec::new();
+        let tree_match = self
+            .tree(subdomain)
+            .zip(split_path(path))
+            .and_then(|(tree, segments)| {
+                self.match_node(tree, &segments, path, method, &mut params)
+            });
+        let index = match tree_match {
+            Some(index) => index,
+            None => {
+                params.clear();
+                self.regex_route(path, method, subdomain)?
+            }
+        };
+        let route = &self.routes[index];
+
+        let mut path_params: Vec<(String, String)> = params
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+
+        // The named groups of a custom regex are parameters too
+        if let Some(regex) = &route.regex {
+            if let Some(captures) = regex.captures(path) {
+                for name in regex.capture_names().flatten() {
+                    if let Some(value) = captures.name(name) {
+                        let value = value.as_str().to_string();
+                        match path_params.iter_mut().find(|(param, _)| param == name) {
+                            Some(param) => param.1 = value,
+                            None => path_params.push((name.to_string(), value)),
+                        }
+                    }
+                }
+            }
+        }
+
+        let params = path_params.iter().cloned().collect();
+        Some(RouteMatch {
+            route,
+            params,
+            path_params,
         })
     }
 
-    /// Match a dynamic path
+    /// Check if adding a path to a tree would conflict with the registered routes
     /// ## Args
-    /// - route_path: &str
-    /// - request_path: &str
+    /// - node: &Node
+    /// - segments: &[Segment]
+    /// - path: &str
+    /// - method: &HttpMethod
     /// ## Returns
-    /// - bool
-    fn match_dynamic_path(&self, route_path: &str, request_path: &str) -> bool {
-        let route_parts: Vec<&str> = route_path.split('/').collect();
-        let request_parts: Vec<&str> = request_path.split('/').collect();
+    /// - Result<(), RouteError>
+    fn check_conflicts(
+        &self,
+        mut node: &Node,
+        segments: &[Segment],
+        path: &str,
+        method: &HttpMethod,
+    ) -> Result<(), RouteError> {
+        let param_conflict = |existing: &str| RouteError::ParamConflict {
+            path: path.to_string(),
+            existing: existing.to_string(),
+        };
+
+        let mut routes = None;
+        for segment in segments {
+            match segment {
+                Segment::Static(segment) => match node.statics.get(*segment) {
+                    Some(child) => node = child,
+                    None => return Ok(()),
+                },
+                Segment::Param { name, constraint } => {
+                    let source = constraint.as_ref().map(|c| c.source.as_str());
+                    match node
+                        .params
+                        .iter()
+                        .find(|param| param.has_constraint(source))
+                    {
+                        Some(param) if param.name != *name => {
+                            return Err(param_conflict(&param.name))
+                        }
+                        Some(param) => node = &param.node,
+                        None => return Ok(()),
+                    }
+                }
+                Segment::Wildcard(name) => match &node.wildcard {
+                    Some(wildcard) if wildcard.name != *name => {
+                        return Err(param_conflict(&wildcard.name))
+                    }
+                    Some(wildcard) => routes = Some(&wildcard.routes),
+                    None => return Ok(()),
+                },
+            }
+        }
 
-        if route_parts.len() != request_parts.len() {
-            return false;
+        let routes = routes.unwrap_or(&node.routes);
+        if routes.iter().any(|&i| self.routes[i].method == *method) {
+            return Err(RouteError::Conflict {
+                method: method.clone(),
+                path: path.to_string(),
+            });
         }
 
-        route_parts
+        Ok(())
+    }
+
+    /// Get the tree of a subdomain
+    /// ## Args
+    /// - subdomain: Option<&str>
+    /// ## Returns
+    /// - Option<&Node>
+    fn tree(&self, subdomain: Option<&str>) -> Option<&Node> {
+        self.trees.get(&subdomain.map(String::from))
+    }
+
+    /// Check if the regex of a route (if any) accepts a path
+    /// ## Args
+    /// - index: usize
+    /// - path: &str
+    /// ## Returns
+    /// - bool
+    fn regex_accepts(&self, index: usize, path: &str) -> bool {
+        self.routes[index]
+            .regex
+            .as_ref()
+            .is_none_or(|re| re.is_match(path))
+    }
+
+    /// Check if a route has a regex matching a path on a subdomain
+    /// ## Args
+    /// - index: usize
+    /// - path: &str
+    /// - subdomain: Option<&str>
+    /// ## Returns
+    /// - bool
+    fn regex_matches(&self, index: usize, path: &str, subdomain: Option<&str>) -> bool {
+        let route = &self.routes[index];
+        route.subdomain.as_deref() == subdomain
+            && route.regex.as_ref().is_some_and(|re| re.is_match(path))
+    }
+
+    /// Get the first route whose regex matches a request, for the paths that no path
+    /// pattern matches: a regex alone decides which paths its route serves
+    /// ## Args
+    /// - path: &str
+    /// - method: &HttpMethod
+    /// - subdomain: Option<&str>
+    /// ## Returns
+    /// - Option<usize>
+    fn regex_route(
+        &self,
+        path: &str,
+        method: &HttpMethod,
+        subdomain: Option<&str>,
+    ) -> Option<usize> {
+        (0..self.routes.len()).find(|&index| {
+            self.routes[index].method == *method && self.regex_matches(index, path, subdomain)
+        })
+    }
+
+    /// Get the first route of a list accepting a request
+    /// ## Args
+    /// - routes: &[usize]
+    /// - path: &str
+    /// - method: &HttpMethod
+    /// ## Returns
+    /// - Option<usize>
+    fn accepting_route(&self, routes: &[usize], path: &str, method: &HttpMethod) -> Option<usize> {
+        routes
             .iter()
-            .zip(request_parts.iter())
-            .all(|(route_part, request_part)| {
-                route_part.starts_with(':') || route_part == request_part
-            })
+            .copied()
+            .find(|&index| self.routes[index].method == *method && self.regex_accepts(index, path))
     }
 
-    /// Extract parameters from a path
+    /// Match the remaining segments of a path against a node, backtracking by priority
     /// ## Args
-    /// - route: &Route
-    /// - request_path: &str
+    /// - node: &'n Node
+    /// - segments: &[&str]
+    /// - path: &str
+    /// - method: &HttpMethod
+    /// - params: &mut Vec<(&'n str, String)>
     /// ## Returns
-    /// - std::collections::HashMap<String, String>
-    pub(crate) fn extract_params(
+    /// - Option<usize> (the index of the matched route)
+    fn match_node<'n>(
         &self,
-        route: &Route,
-        request_path: &str,
-    ) -> std::collections::HashMap<String, String> {
-        let mut params = std::collections::HashMap::new();
+        node: &'n Node,
+        segments: &[&str],
+        path: &str,
+        method: &HttpMethod,
+        params: &mut Vec<(&'n str, String)>,
+    ) -> Option<usize> {
+        let Some((segment, rest)) = segments.split_first() else {
+            return self.accepting_route(&node.routes, path, method);
+        };
 
-        if let Some(regex) = &route.regex {
-            if let Some(captures) = regex.captures(request_path) {
-                for name in regex.capture_names().flatten() {
-                    if let Some(value) = captures.name(name) {
-                        params.insert(name.to_string(), value.as_str().to_string());
-                    }
-                }
+        if let Some(child) = node.statics.get(*segment) {
+            if let Some(index) = self.match_node(child, rest, path, method, params) {
+                return Some(index);
+            }
+        }
+
+        for param in node.params.iter().filter(|param| param.accepts(segment)) {
+            params.push((&param.name, segment.to_string()));
+            if let Some(index) = self.match_node(&param.node, rest, path, method, params) {
+                return Some(index);
             }
-        } else {
-            let route_parts: Vec<&str> = route.path.split('/').collect();
-            let request_parts: Vec<&str> = request_path.split('/').collect();
+            params.pop();
+        }
 
-            for (route_part, request_part) in route_parts.iter().zip(request_parts.iter()) {
-                if route_part.starts_with(':') {
-                    let key = route_part.trim_start_matches(':').to_string();
-                    params.insert(key, request_part.to_string());
+        if let Some(wildcard) = &node.wildcard {
+            let remainder = segments.join("/");
+            if !remainder.is_empty() {
+                if let Some(index) = self.accepting_route(&wildcard.routes, path, method) {
+                    params.push((&wildcard.name, remainder));
+                    return Some(index);
                 }
             }
         }
 
-        params
+        None
     }
+
+    /// Collect every route matching the remaining segments of a path, whatever its method
+    /// ## Args
+    /// - node: &Node
+    /// - segments: &[&str]
+    /// - path: &str
+    /// - matched: &mut Vec<usize>
+    fn collect_matches(
+        &self,
+        node: &Node,
+        segments: &[&str],
+        path: &str,
+        matched: &mut Vec<usize>,
+    ) {
+        let Some((segment, rest)) = segments.split_first() else {
+            matched.extend(
+                node.routes
+                    .iter()
+                    .filter(|&&index| self.regex_accepts(index, path)),
+            );
+            return;
+        };
+
+        if let Some(child) = node.statics.get(*segment) {
+            self.collect_matches(child, rest, path, matched);
+        }
+
+        for param in node.params.iter().filter(|param| param.accepts(segment)) {
+            self.collect_matches(&param.node, rest, path, matched);
+        }
+
+        if let Some(wildcard) = &node.wildcard {
+            if !segments.join("/").is_empty() {
+                matched.extend(
+                    wildcard
+                        .routes
+                        .iter()
+                        .filter(|&&index| self.regex_accepts(index, path)),
+                );
+            }
+        }
+    }
+}
+
+/// Insert the segments of a path into a tree, creating the missing nodes
+/// ## Args
+/// - node: &mut Node
+/// - segments: Vec<Segment>
+/// - index: usize (index of the route)
+fn insert_segments(mut node: &mut Node, segments: Vec<Segment>, index: usize) {
+    for segment in segments {
+        match segment {
+            Segment::Static(segment) => {
+                node = node.statics.entry(segment.to_string()).or_default();
+            }
+            Segment::Param { name, constraint } => {
+                let source = constraint.as_ref().map(|c| c.source.clone());
+                let position = match node
+                    .params
+                    .iter()
+                    .position(|param| param.has_constraint(source.as_deref()))
+                {
+                    Some(position) => position,
+                    None => {
+                        // Constrained parameters are tried before the unconstrained one
+                        let position = match constraint {
+                            Some(_) => node
+                                .params
+                                .iter()
+                                .position(|param| param.constraint.is_none())
+                                .unwrap_or(node.params.len()),
+                            None => node.params.len(),
+                        };
+                        node.params.insert(
+                            position,
+                            ParamNode {
+                                name: name.to_string(),
+                                constraint,
+                                node: Node::default(),
+                            },
+                        );
+                        position
+                    }
+                };
+                node = &mut node.params[position].node;
+            }
+            Segment::Wildcard(name) => {
+                node.wildcard
+                    .get_or_insert_with(|| WildcardNode {
+                        name: name.to_string(),
+                        routes: Vec::new(),
+                    })
+                    .routes
+                    .push(index);
+                return;
+            }
+        }
+    }
+    node.routes.push(index);
+}
+
+/// Split a path into its segments
+/// ## Args
+/// - path: &str
+/// ## Returns
+/// - Option<Vec<&str>> (None if the path does not start with '/')
+fn split_path(path: &str) -> Option<Vec<&str>> {
+    path.strip_prefix('/').map(|path| path.split('/').collect())
 }
@@ -0,0 +1,248 @@
+use crate::routes::routing::pattern::{parse_segments, Segment};
+use crate::routes::routing::router::RouteError;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Define the UrlError enum
+/// ## Variants
+/// - UnknownRoute(String): no route has this name
+/// - MissingParam: a required parameter of the route was not given
+/// - InvalidParam: a parameter does not match the constraint of the route
+#[derive(Debug, Clone, PartialEq)]
+pub enum UrlError {
+    UnknownRoute(String),
+    MissingParam {
+        route: String,
+        param: String,
+    },
+    InvalidParam {
+        route: String,
+        param: String,
+        value: String,
+    },
+}
+
+/// Implement the Display trait for UrlError
+impl fmt::Display for UrlError {
+    /// Format the UrlError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlError::UnknownRoute(name) => write!(f, "No route is named '{}'", name),
+            UrlError::MissingParam { route, param } => {
+                write!(f, "Missing parameter '{}' for route '{}'", param, route)
+            }
+            UrlError::InvalidParam {
+                route,
+                param,
+                value,
+            } => write!(
+                f,
+                "Invalid value '{}' for parameter '{}' of route '{}'",
+                value, param, route
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UrlError {}
+
+/// Define the UrlSegment enum
+/// An owned segment of a named route path
+/// ## Variants
+/// - Static(String)
+/// - Param: a parameter with its constraint
+/// - Wildcard(String)
+enum UrlSegment {
+    Static(String),
+    Param {
+        name: String,
+        constraint: Option<Regex>,
+    },
+    Wildcard(String),
+}
+
+/// Define the NamedRoute struct
+/// ## Fields
+/// - segments: Vec<UrlSegment>
+/// - required: usize (number of segments that cannot be omitted)
+struct NamedRoute {
+    segments: Vec<UrlSegment>,
+    required: usize,
+}
+
+/// Define the UrlGenerator struct
+/// Builds the URL of named routes, shared by the Server, its handlers and its templates
+/// ## Fields
+/// - routes: Arc<RwLock<HashMap<String, NamedRoute>>>
+#[derive(Clone, Default)]
+pub struct UrlGenerator {
+    routes: Arc<RwLock<HashMap<String, NamedRoute>>>,
+}
+
+/// Implement the UrlGenerator struct
+impl UrlGenerator {
+    /// Create a new UrlGenerator instance
+    /// ## Returns
+    /// - UrlGenerator
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a route has a name
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - bool
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.routes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(name)
+    }
+
+    /// Register the path of a named route
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - path: &str
+    /// ## Returns
+    /// - Result<(), RouteError> (an error if the name is already used or the path is invalid)
+    pub(crate) fn register(&self, name: &str, path: &str) -> Result<(), RouteError> {
+        let (segments, first_optional) = parse_segments(path)?;
+        let required = first_optional.unwrap_or(segments.len());
+        let segments = segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Static(segment) => UrlSegment::Static(segment.to_string()),
+                Segment::Param { name, constraint } => UrlSegment::Param {
+                    name: name.to_string(),
+                    constraint: constraint.map(|constraint| constraint.regex),
+                },
+                Segment::Wildcard(name) => UrlSegment::Wildcard(name.to_string()),
+            })
+            .collect();
+
+        let mut routes = self.routes.write().unwrap_or_else(|e| e.into_inner());
+        if routes.contains_key(name) {
+            return Err(RouteError::DuplicateName(name.to_string()));
+        }
+        routes.insert(name.to_string(), NamedRoute { segments, required });
+
+        Ok(())
+    }
+
+    /// Build the URL of a named route
+    /// Parameters that are not part of the path are added to the query string
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - params: P (e.g. `[("id", 42)]` or a HashMap)
+    /// ## Where
+    /// - P: IntoIterator<Item = (K, V)>
+    /// - K: Into<String>
+    /// - V: ToString
+    /// ## Returns
+    /// - Result<String, UrlError>
+    pub fn url_for<P, K, V>(&self, name: &str, params: P) -> Result<String, UrlError>
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: ToString,
+    {
+        let mut params: Vec<(String, String)> = params
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.to_string()))
+            .collect();
+        let mut take_param = |param: &str| {
+            params
+                .iter()
+                .position(|(key, _)| key == param)
+                .map(|position| params.remove(position).1)
+        };
+
+        let routes = self.routes.read().unwrap_or_else(|e| e.into_inner());
+        let route = routes
+            .get(name)
+            .ok_or_else(|| UrlError::UnknownRoute(name.to_string()))?;
+
+        let mut url = String::new();
+        for (i, segment) in route.segments.iter().enumerate() {
+            let value = match segment {
+                UrlSegment::Static(segment) => {
+                    url.push('/');
+                    url.push_str(segment);
+                    continue;
+                }
+                UrlSegment::Param { name: param, .. } | UrlSegment::Wildcard(param) => {
+                    match take_param(param) {
+                        Some(value) if !value.is_empty() => value,
+                        // Optional segments are omitted from the first missing one
+                        _ if i >= route.required => break,
+                        _ => {
+                            return Err(UrlError::MissingParam {
+                                route: name.to_string(),
+                                param: param.clone(),
+                            })
+                        }
+                    }
+                }
+            };
+
+            url.push('/');
+            match segment {
+                UrlSegment::Param {
+                    name: param,
+                    constraint,
+                } => {
+                    if constraint.as_ref().is_some_and(|re| !re.is_match(&value)) {
+                        return Err(UrlError::InvalidParam {
+                            route: name.to_string(),
+                            param: param.clone(),
+                            value,
+                        });
+                    }
+                    url.push_str(&percent_encode(&value, false));
+                }
+                _ => url.push_str(&percent_encode(&value, true)),
+            }
+        }
+
+        if url.is_empty() {
+            url.push('/');
+        }
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(&serde_urlencoded::to_string(&params).unwrap_or_default());
+        }
+
+        Ok(url)
+    }
+}
+
+/// Percent-encode a value for a path segment
+/// ## Args
+/// - value: &str
+/// - keep_slashes: bool (true for wildcards, which span several segments)
+/// ## Returns
+/// - String
+fn percent_encode(value: &str, keep_slashes: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slashes => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -1,14 +1,41 @@
+use super::server_handle::ServerHandle;
+use super::tls::CertificateStore;
+use crate::routes::routing::handler::BoxFuture;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Define the LifecycleHook type
+pub(crate) type LifecycleHook = Box<dyn FnOnce(ServerHandle) -> BoxFuture<'static, ()> + Send>;
+
 /// Define the Server struct
 /// ## Fields
 /// - address: String
 /// - template_dirs: Vec<String>
 /// - static_dirs: String
 /// - max_static_file_size: usize
+/// - max_body_size: usize
+/// - keep_alive_timeout: Duration
+/// - read_timeout: Duration
+/// - max_requests_per_connection: usize
+/// - tls: Option<Arc<CertificateStore>> (None to serve plain HTTP)
+/// - tls_reload_interval: Option<Duration> (how often the certificate files are checked)
+/// - shutdown_timeout: Duration (how long the requests in progress can take at shutdown)
+/// - startup_hooks: Vec<LifecycleHook>
+/// - shutdown_hooks: Vec<LifecycleHook>
 pub(crate) struct Server {
     pub(crate) address: String,
     pub(crate) template_dirs: Vec<String>,
     pub(crate) static_dirs: String,
     pub(crate) max_static_file_size: usize,
+    pub(crate) max_body_size: usize,
+    pub(crate) keep_alive_timeout: Duration,
+    pub(crate) read_timeout: Duration,
+    pub(crate) max_requests_per_connection: usize,
+    pub(crate) tls: Option<Arc<CertificateStore>>,
+    pub(crate) tls_reload_interval: Option<Duration>,
+    pub(crate) shutdown_timeout: Duration,
+    pub(crate) startup_hooks: Vec<LifecycleHook>,
+    pub(crate) shutdown_hooks: Vec<LifecycleHook>,
 }
 
 /// Implement the Server struct
@@ -24,6 +51,15 @@ impl Server {
             template_dirs: vec!["templates".to_string()],
             static_dirs: "static".to_string(),
             max_static_file_size: 5 * 1024 * 1024,
+            max_body_size: 10 * 1024 * 1024,
+            keep_alive_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(30),
+            max_requests_per_connection: 100,
+            tls: None,
+            tls_reload_interval: Some(Duration::from_secs(30)),
+            shutdown_timeout: Duration::from_secs(30),
+            startup_hooks: Vec::new(),
+            shutdown_hooks: Vec::new(),
         }
     }
 }
@@ -1,4 +1,10 @@
 pub(crate) mod core;
 pub(crate) mod server_handle;
+pub(crate) mod shutdown;
+pub(crate) mod test_client;
+pub(crate) mod tls;
 
 pub use server_handle::ServerHandle;
+pub use shutdown::ShutdownHandle;
+pub use test_client::{TestClient, TestRequest};
+pub use tls::TlsError;
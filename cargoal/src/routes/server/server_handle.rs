@@ -1,30 +1,51 @@
 use crate::renderer::TemplateRenderer;
+use crate::routes::http::body::Body;
+use crate::routes::http::forwarded::{ProxyError, TrustedProxies};
+use crate::routes::http::key::{Key, KeyError};
 use crate::routes::http::method::HttpMethod;
-use crate::routes::http::request::parse_request;
+use crate::routes::http::parser::{read_request, ParseError};
 use crate::routes::http::request::Request;
-use crate::routes::http::response::format_response;
+use crate::routes::http::response::write_response;
 use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use crate::routes::routing::handler::Handler;
 use crate::routes::routing::RouteBuilder;
-use crate::routes::routing::{GroupBuilder, Router};
+use crate::routes::routing::{GroupBuilder, RouteMatch, Router, UrlError, UrlGenerator};
+use crate::routes::routing::{IntoMiddleware, Next};
 use crate::routes::server::core::Server;
+use crate::routes::server::shutdown::ShutdownHandle;
+use crate::routes::server::tls::{CertificateStore, TlsError};
+use std::future::Future;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::fs;
-use tokio::io::AsyncReadExt;
-use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
-use tokio::net::TcpStream;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{watch, Mutex, RwLock};
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+use tokio_rustls::TlsAcceptor;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
 
 /// Define the ServerHandle struct
 /// ## Fields
 /// - inner: Arc<Mutex<Server>>
 /// - router: Arc<RwLock<Router>>
+/// - urls: UrlGenerator
+/// - shutdown: ShutdownHandle
+/// - local_addr: Arc<watch::Sender<Option<SocketAddr>>> (set while the Server is listening)
 #[derive(Clone)]
 pub struct ServerHandle {
     inner: Arc<Mutex<Server>>,
     router: Arc<RwLock<Router>>,
+    urls: UrlGenerator,
+    shutdown: ShutdownHandle,
+    local_addr: Arc<watch::Sender<Option<SocketAddr>>>,
 }
 
 /// Implement the ServerHandle struct
@@ -39,24 +60,74 @@ impl ServerHandle {
         Self {
             inner: Arc::new(Mutex::new(Server::new(address))),
             router,
+            urls: UrlGenerator::new(),
+            shutdown: ShutdownHandle::new(),
+            local_addr: Arc::new(watch::Sender::new(None)),
         }
     }
 
     /// Add a middleware to the Server
+    /// Global middlewares run first, for every request (including static files and
+    /// unknown routes), in the order they are added
     /// ## Args
     /// - middleware: F
     /// ## Where
-    /// - F: Fn(&Request) -> Option<Response> + Send + Sync + 'static
+    /// - F: IntoMiddleware<M> (see `RouteBuilder::with_middleware`)
     /// ## Returns
     /// - ()
     /// ## Side Effects
     /// - Adds a middleware to the Server
-    pub async fn add_middleware<F>(&self, middleware: F)
+    pub async fn add_middleware<F, M>(&self, middleware: F)
     where
-        F: Fn(&Request) -> Option<Response> + Send + Sync + 'static,
+        F: IntoMiddleware<M>,
     {
         let mut router = self.router.write().await;
-        router.middlewares.push(Arc::new(middleware));
+        Arc::make_mut(&mut router.middlewares).push(middleware.into_middleware());
+    }
+
+    /// Share a value with every handler, which get it with the `State<T>` extractor
+    /// (a database pool, the configuration, ...)
+    /// ## Args
+    /// - state: T (there is one value per type, a new value replaces the previous one)
+    /// ## Side Effects
+    /// - Adds the value to the state of the Server
+    pub async fn with_state<T: Send + Sync + 'static>(&mut self, state: T) {
+        let mut router = self.router.write().await;
+        Arc::make_mut(&mut router.state).insert(state);
+    }
+
+    /// Set the secret key signing and encrypting the cookies (`SignedJar`, `PrivateJar`)
+    /// ## Args
+    /// - secret: &[u8] (at least 32 random bytes, kept out of the source code)
+    /// ## Returns
+    /// - Result<(), KeyError>
+    /// ## Side Effects
+    /// - Adds the derived Key to the state of the Server
+    pub async fn with_secret_key(&mut self, secret: &[u8]) -> Result<(), KeyError> {
+        self.with_state(Key::from_secret(secret)?).await;
+        Ok(())
+    }
+
+    /// Trust the forwarding headers (`Forwarded`, `X-Forwarded-For`,
+    /// `X-Forwarded-Proto`, `X-Forwarded-Host`) of the Requests sent by these proxies,
+    /// to get the real client IP address, scheme and host of the Requests
+    /// ## Args
+    /// - proxies: Vec<&str> (IP addresses and CIDR ranges, e.g. `vec!["10.0.0.0/8"]`)
+    /// ## Returns
+    /// - Result<(), ProxyError>
+    /// ## Side Effects
+    /// - Replaces the trusted proxies of the Server
+    pub async fn with_trusted_proxies(&mut self, proxies: Vec<&str>) -> Result<(), ProxyError> {
+        let trusted_proxies = TrustedProxies::parse(&proxies)?;
+        self.router.write().await.trusted_proxies = Arc::new(trusted_proxies);
+        Ok(())
+    }
+
+    /// Get a value shared with `with_state`
+    /// ## Returns
+    /// - Option<Arc<T>>
+    pub async fn state<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.router.read().await.state.get::<T>()
     }
 
     /// Add a group of routes to the Server
@@ -64,24 +135,20 @@ impl ServerHandle {
     /// - prefix: &str
     /// - group: F
     /// ## Where
-    /// - F: FnOnce(Arc<Mutex<GroupBuilder>>) -> Fut + Send + 'static
-    /// - Fut: std::future::Future<Output = ()> + Send
+    /// - F: FnOnce(Arc<Mutex<GroupBuilder>>) -> Fut
+    /// - Fut: std::future::Future<Output = ()>
     /// ## Returns
     /// - ()
     /// ## Side Effects
     /// - Adds a group of routes to the Server
     pub async fn with_group<F, Fut>(&self, prefix: &str, group: F)
     where
-        F: FnOnce(Arc<Mutex<GroupBuilder>>) -> Fut + Send + 'static,
-        Fut: std::future::Future<Output = ()> + Send,
+        F: FnOnce(Arc<Mutex<GroupBuilder>>) -> Fut,
+        Fut: std::future::Future<Output = ()>,
     {
         let group_builder = Arc::new(Mutex::new(GroupBuilder::new(prefix, self.clone())));
 
-        tokio::spawn(async move {
-            group(Arc::clone(&group_builder)).await;
-        })
-        .await
-        .unwrap();
+        group(group_builder).await;
     }
 
     /// Add a route to the Server
@@ -101,12 +168,43 @@ impl ServerHandle {
         Arc::clone(&self.router)
     }
 
+    /// Get the URL generator of the named routes
+    /// It can be cloned into handlers to build links
+    /// ## Returns
+    /// - UrlGenerator
+    pub fn urls(&self) -> UrlGenerator {
+        self.urls.clone()
+    }
+
+    /// Build the URL of a named route
+    /// ## Args
+    /// - name: &str
+    /// - params: P (parameters that are not part of the path go to the query string)
+    /// ## Where
+    /// - P: IntoIterator<Item = (K, V)>
+    /// - K: Into<String>
+    /// - V: ToString
+    /// ## Returns
+    /// - Result<String, UrlError>
+    pub fn url_for<P, K, V>(&self, name: &str, params: P) -> Result<String, UrlError>
+    where
+        P: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: ToString,
+    {
+        self.urls.url_for(name, params)
+    }
+
     /// Get the template renderer of the Server
+    /// ## Args
+    /// - extra_dirs: &[String] (loaded after the template dirs of the Server)
     /// ## Returns
     /// - TemplateRenderer
-    pub(crate) async fn get_template_renderer(&self) -> TemplateRenderer {
+    pub(crate) async fn get_template_renderer(&self, extra_dirs: &[String]) -> TemplateRenderer {
         let server = self.inner.lock().await;
-        TemplateRenderer::new(server.template_dirs.iter().map(String::as_str).collect())
+        let dirs = server.template_dirs.iter().chain(extra_dirs);
+        TemplateRenderer::new(dirs.map(String::as_str).collect())
+            .with_url_generator(self.urls.clone())
     }
 
     /// Get the address of the server
@@ -129,6 +227,54 @@ impl ServerHandle {
         server.max_static_file_size = size;
     }
 
+    /// Set the maximum request body size
+    /// ## Args
+    /// - size: usize
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Sets the maximum request body size, larger bodies are answered with 413
+    pub async fn with_max_body_size(&mut self, size: usize) {
+        let mut server = self.inner.lock().await;
+        server.max_body_size = size;
+    }
+
+    /// Set how long an idle persistent connection is kept open
+    /// ## Args
+    /// - duration: Duration
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Sets the keep-alive timeout
+    pub async fn with_keep_alive_timeout(&mut self, duration: Duration) {
+        let mut server = self.inner.lock().await;
+        server.keep_alive_timeout = duration;
+    }
+
+    /// Set the maximum time allowed to read a request once it has started
+    /// ## Args
+    /// - duration: Duration
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Sets the read timeout, slower requests are answered with 408
+    pub async fn with_read_timeout(&mut self, duration: Duration) {
+        let mut server = self.inner.lock().await;
+        server.read_timeout = duration;
+    }
+
+    /// Set the maximum number of requests served on a single connection
+    /// ## Args
+    /// - max: usize
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Sets the maximum number of requests per connection
+    pub async fn with_max_requests_per_connection(&mut self, max: usize) {
+        let mut server = self.inner.lock().await;
+        server.max_requests_per_connection = max;
+    }
+
     /// Set the static directory
     /// ## Args
     /// - dir: &str
@@ -153,44 +299,345 @@ impl ServerHandle {
         server.template_dirs = dirs.iter().map(|d| d.to_string()).collect();
     }
 
-    /// Run the server
+    /// Serve HTTPS with a certificate
+    /// It is the default certificate, used when no certificate matches the SNI name
+    /// sent by the client (see `with_tls_for`)
+    /// ## Args
+    /// - cert_path: &str (PEM certificate chain, leaf first)
+    /// - key_path: &str (PEM private key)
+    /// ## Returns
+    /// - Result<(), TlsError>
+    /// ## Side Effects
+    /// - Enables TLS on the Server
+    pub async fn with_tls(&mut self, cert_path: &str, key_path: &str) -> Result<(), TlsError> {
+        self.add_certificate(None, cert_path, key_path).await
+    }
+
+    /// Add the certificate of a server name, chosen with the SNI name sent by the client
+    /// ## Args
+    /// - server_name: &str (e.g. `api.example.com`, or `*.example.com` for a wildcard)
+    /// - cert_path: &str (PEM certificate chain, leaf first)
+    /// - key_path: &str (PEM private key)
+    /// ## Returns
+    /// - Result<(), TlsError>
+    /// ## Side Effects
+    /// - Enables TLS on the Server
+    pub async fn with_tls_for(
+        &mut self,
+        server_name: &str,
+        cert_path: &str,
+        key_path: &str,
+    ) -> Result<(), TlsError> {
+        self.add_certificate(Some(server_name), cert_path, key_path)
+            .await
+    }
+
+    /// Set how often the certificate files are checked, the modified ones are reloaded
+    /// without restarting the Server
+    /// ## Args
+    /// - interval: Option<Duration> (None to disable the automatic reload)
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Sets the certificate reload interval
+    pub async fn with_tls_reload_interval(&mut self, interval: Option<Duration>) {
+        let mut server = self.inner.lock().await;
+        server.tls_reload_interval = interval;
+    }
+
+    /// Reload every certificate from disk now (e.g. after a renewal)
+    /// A certificate that cannot be loaded keeps being served until the next reload
+    /// ## Returns
+    /// - Result<usize, TlsError> (the number of reloaded certificates, or the first error)
+    pub async fn reload_tls(&self) -> Result<usize, TlsError> {
+        match self.inner.lock().await.tls.clone() {
+            Some(store) => store.reload(false).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Load a certificate into the certificate store of the Server
+    /// ## Args
+    /// - server_name: Option<&str> (None for the default certificate)
+    /// - cert_path: &str
+    /// - key_path: &str
+    /// ## Returns
+    /// - Result<(), TlsError>
+    async fn add_certificate(
+        &mut self,
+        server_name: Option<&str>,
+        cert_path: &str,
+        key_path: &str,
+    ) -> Result<(), TlsError> {
+        let store = Arc::clone(
+            self.inner
+                .lock()
+                .await
+                .tls
+                .get_or_insert_with(Default::default),
+        );
+        store.insert(server_name, cert_path, key_path).await
+    }
+
+    /// Set how long the requests in progress can take once the Server is shutting down,
+    /// the connections still open after it are closed
+    /// ## Args
+    /// - duration: Duration
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Sets the shutdown timeout
+    pub async fn with_shutdown_timeout(&mut self, duration: Duration) {
+        let mut server = self.inner.lock().await;
+        server.shutdown_timeout = duration;
+    }
+
+    /// Add a hook run before the Server starts accepting connections
+    /// (e.g. to open a database pool and share it with `with_state`)
+    /// ## Args
+    /// - hook: F
+    /// ## Where
+    /// - F: FnOnce(ServerHandle) -> Fut + Send + 'static
+    /// - Fut: Future<Output = ()> + Send + 'static
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Adds a startup hook, hooks run in the order they are added
+    pub async fn on_startup<F, Fut>(&mut self, hook: F)
+    where
+        F: FnOnce(ServerHandle) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut server = self.inner.lock().await;
+        server
+            .startup_hooks
+            .push(Box::new(move |app| Box::pin(hook(app))));
+    }
+
+    /// Add a hook run once the Server has shut down and its connections are closed
+    /// (e.g. to close a database pool)
+    /// ## Args
+    /// - hook: F
+    /// ## Where
+    /// - F: FnOnce(ServerHandle) -> Fut + Send + 'static
+    /// - Fut: Future<Output = ()> + Send + 'static
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Adds a shutdown hook, hooks run in the order they are added
+    pub async fn on_shutdown<F, Fut>(&mut self, hook: F)
+    where
+        F: FnOnce(ServerHandle) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let mut server = self.inner.lock().await;
+        server
+            .shutdown_hooks
+            .push(Box::new(move |app| Box::pin(hook(app))));
+    }
+
+    /// Get the address the Server is listening on
+    /// With a `:0` port, it holds the port chosen by the operating system
+    /// ## Returns
+    /// - Option<SocketAddr> (None when the Server is not listening)
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.borrow()
+    }
+
+    /// Wait until the Server is listening and accepting connections
+    /// ```rust,ignore
+    /// let app = ServerHandle::new("127.0.0.1:0");
+    /// let server = app.clone();
+    /// tokio::spawn(async move { server.run().await });
+    /// let addr = app.ready().await;
+    /// ```
+    /// ## Returns
+    /// - SocketAddr
+    pub async fn ready(&self) -> SocketAddr {
+        let mut receiver = self.local_addr.subscribe();
+        // The sender lives as long as self, the channel cannot be closed
+        let addr = *receiver
+            .wait_for(Option::is_some)
+            .await
+            .expect("the address channel is never closed");
+        addr.expect("wait_for only returns bound addresses")
+    }
+
+    /// Get a handle to shut the Server down
+    /// ## Returns
+    /// - ShutdownHandle
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Run the server until it is shut down with its `ShutdownHandle`
     /// ## Returns
     /// - ()
     /// ## Side Effects
     /// - Runs the server
+    /// ## Panics
+    /// - If the address cannot be bound or the TLS configuration is invalid
     pub async fn run(&self) {
+        self.run_until(std::future::pending::<()>()).await
+    }
+
+    /// Run the server until a future completes (e.g. `tokio::signal::ctrl_c()`) or it is
+    /// shut down with its `ShutdownHandle`
+    /// The server then stops accepting connections, waits for the requests in progress
+    /// (up to the shutdown timeout) and runs the shutdown hooks. A Server that was shut
+    /// down can run again, its startup and shutdown hooks only run the first time
+    /// ## Args
+    /// - shutdown: F
+    /// ## Where
+    /// - F: Future
+    /// ## Returns
+    /// - ()
+    /// ## Side Effects
+    /// - Runs the server
+    /// ## Panics
+    /// - If the address cannot be bound or the TLS configuration is invalid
+    pub async fn run_until<F: Future>(&self, shutdown: F) {
+        // The ShutdownHandle of a previous run asked it to stop, not this one
+        self.shutdown.reset();
+
+        let startup_hooks = std::mem::take(&mut self.inner.lock().await.startup_hooks);
+        for hook in startup_hooks {
+            hook(self.clone()).await;
+        }
+
         let listener = TcpListener::bind(self.address().await).await.unwrap();
-        println!("Server running on {}", self.address().await);
+        let local_addr = listener.local_addr().unwrap();
+        let (tls, reload_interval, handshake_timeout, shutdown_timeout) = {
+            let server = self.inner.lock().await;
+            (
+                server.tls.clone(),
+                server.tls_reload_interval,
+                server.read_timeout,
+                server.shutdown_timeout,
+            )
+        };
+
+        let mut watcher = None;
+        let acceptor = tls.map(|store| {
+            if let Some(interval) = reload_interval {
+                watcher = Some(tokio::spawn(Self::watch_certificates(
+                    Arc::clone(&store),
+                    interval,
+                )));
+            }
+            TlsAcceptor::from(store.server_config().unwrap())
+        });
+        tracing::info!(
+            "Server running on {}://{}",
+            if acceptor.is_some() { "https" } else { "http" },
+            local_addr
+        );
+        self.local_addr.send_replace(Some(local_addr));
 
+        let mut connections = JoinSet::new();
+        tokio::pin!(shutdown);
         loop {
-            match listener.accept().await {
-                Ok((stream, _)) => {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = &mut shutdown => break,
+                _ = self.shutdown.wait() => break,
+                // Forget the connections that are closed
+                Some(_) = connections.join_next(), if !connections.is_empty() => continue,
+            };
+
+            match accepted {
+                Ok((stream, peer)) => {
                     let handle_clone = self.clone();
-                    tokio::spawn(async move {
-                        handle_clone.handle_connection(stream).await;
+                    let acceptor = acceptor.clone();
+                    connections.spawn(async move {
+                        let Some(acceptor) = acceptor else {
+                            return handle_clone.handle_connection(stream, peer, "http").await;
+                        };
+                        match timeout(handshake_timeout, acceptor.accept(stream)).await {
+                            Ok(Ok(stream)) => {
+                                handle_clone.handle_connection(stream, peer, "https").await
+                            }
+                            Ok(Err(e)) => tracing::debug!(error = %e, "TLS handshake failed"),
+                            Err(_) => tracing::debug!("TLS handshake timed out"),
+                        }
                     });
                 }
-                Err(e) => eprintln!("Failed to accept connection: {}", e),
+                Err(e) => tracing::warn!(error = %e, "Failed to accept connection"),
+            }
+        }
+
+        // Stop accepting connections and let the requests in progress finish
+        tracing::info!(
+            "Shutting down, waiting for {} connection(s)",
+            connections.len()
+        );
+        self.shutdown.shutdown();
+        self.local_addr.send_replace(None);
+        drop(listener);
+        if let Some(watcher) = watcher {
+            watcher.abort();
+        }
+        let drained = timeout(shutdown_timeout, async {
+            while connections.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_err() {
+            tracing::warn!(
+                "Closing {} connection(s) still open after the shutdown timeout",
+                connections.len()
+            );
+            connections.shutdown().await;
+        }
+
+        let shutdown_hooks = std::mem::take(&mut self.inner.lock().await.shutdown_hooks);
+        for hook in shutdown_hooks {
+            hook(self.clone()).await;
+        }
+        tracing::info!("Server stopped");
+    }
+
+    /// Reload the modified certificate files periodically
+    /// ## Args
+    /// - store: Arc<CertificateStore>
+    /// - interval: Duration
+    /// ## Side Effects
+    /// - Replaces the certificates served to the new connections
+    async fn watch_certificates(store: Arc<CertificateStore>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            match store.reload(true).await {
+                Ok(0) => {}
+                Ok(reloaded) => tracing::info!("Reloaded {} TLS certificate(s)", reloaded),
+                Err(e) => tracing::error!(error = %e, "Error reloading TLS certificates"),
             }
         }
     }
 
     /// Send a response to a client
     /// ## Args
-    /// - stream: &mut TcpStream
+    /// - stream: &mut S
     /// - response: Response
+    /// - chunked: bool (whether streaming bodies can use chunked transfer encoding)
+    /// - include_body: bool (false to answer a HEAD request)
+    /// ## Where
+    /// - S: AsyncWrite + Unpin
     /// ## Returns
-    /// - ()
+    /// - std::io::Result<()>
     /// ## Side Effects
     /// - Sends a response to a client
-    async fn send_response(stream: &mut TcpStream, response: Response) {
-        let response_str = format_response(response);
-        if let Err(e) = stream.write_all(response_str.as_bytes()).await {
-            eprintln!("Error writing response: {}", e);
-        }
-        if let Err(e) = stream.flush().await {
-            eprintln!("Error flushing stream: {}", e);
+    async fn send_response<S: AsyncWrite + Unpin>(
+        stream: &mut S,
+        response: Response,
+        chunked: bool,
+        include_body: bool,
+    ) -> std::io::Result<()> {
+        let result = write_response(stream, response, chunked, include_body).await;
+        if let Err(e) = &result {
+            tracing::debug!(error = %e, "Error writing response");
         }
+        result
     }
 
     /// Check if a file is forbidden
@@ -269,119 +716,176 @@ impl ServerHandle {
         }
     }
 
+    /// Check if the client asked to keep the connection open after a request
+    /// ## Args
+    /// - request: &Request
+    /// ## Returns
+    /// - bool
+    fn wants_keep_alive(request: &Request) -> bool {
+        let connection = request.header("connection").unwrap_or_default();
+        let has_token = |token: &str| {
+            connection
+                .split(',')
+                .any(|value| value.trim().eq_ignore_ascii_case(token))
+        };
+
+        if request.version == "HTTP/1.0" {
+            has_token("keep-alive")
+        } else {
+            !has_token("close")
+        }
+    }
+
     /// Handle a connection
+    /// Requests are served one after the other until the client or the server
+    /// closes the connection (HTTP/1.1 persistent connections and pipelining)
     /// ## Args
-    /// - stream: TcpStream
+    /// - stream: S (plain TCP or TLS)
+    /// - peer: SocketAddr
+    /// - scheme: &'static str (`http`, or `https` for TLS connections)
+    /// ## Where
+    /// - S: AsyncRead + AsyncWrite + Unpin
     /// ## Returns
     /// - ()
     /// ## Side Effects
     /// - Handles a connection
-    async fn handle_connection(&self, mut stream: TcpStream) {
-        let router = self.router();
-
-        let mut buffer = Vec::new();
-        let mut temp_buffer = [0; 1024];
+    async fn handle_connection<S>(&self, stream: S, peer: SocketAddr, scheme: &'static str)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let (max_body_size, keep_alive_timeout, read_timeout, max_requests) = {
+            let server = self.inner.lock().await;
+            (
+                server.max_body_size,
+                server.keep_alive_timeout,
+                server.read_timeout,
+                server.max_requests_per_connection,
+            )
+        };
+        let mut reader = BufReader::new(stream);
+        let mut served = 0;
 
-        // read the request
         loop {
-            match stream.read(&mut temp_buffer).await {
-                Ok(0) => return, // EOF
-                Ok(n) => {
-                    buffer.extend_from_slice(&temp_buffer[..n]);
-                    if buffer.windows(4).any(|w| w == b"\r\n\r\n") {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error reading request: {}", e);
+            // Wait for the next request, closing idle connections
+            let next_request = tokio::select! {
+                next_request = timeout(keep_alive_timeout, reader.fill_buf()) => next_request,
+                _ = self.shutdown.wait() => return,
+            };
+            match next_request {
+                Err(_) => return, // idle timeout
+                Ok(Err(e)) => {
+                    tracing::debug!(error = %e, "Error reading request");
                     return;
                 }
+                Ok(Ok([])) => return, // EOF
+                Ok(Ok(_)) => {}
             }
-        }
 
-        let request_str = String::from_utf8_lossy(&buffer[..]);
-        println!("Received request:\n{}", request_str);
+            // read the request
+            let request =
+                match timeout(read_timeout, read_request(&mut reader, max_body_size)).await {
+                    Ok(Ok(Some(mut request))) => {
+                        request.scheme = scheme;
+                        request.with_remote_addr(peer)
+                    }
+                    Ok(Ok(None)) => return, // EOF
+                    Ok(Err(ParseError::Io(e))) => {
+                        tracing::debug!(error = %e, "Error reading request");
+                        return;
+                    }
+                    Ok(Err(e)) => {
+                        tracing::debug!(error = %e, "Invalid request");
+                        let response = Response::new(e.status_code(), Some(e.to_string()))
+                            .with_header("Connection", "close");
+                        let _ = Self::send_response(reader.get_mut(), response, true, true).await;
+                        return;
+                    }
+                    Err(_) => {
+                        let response = Response::new(
+                            StatusCode::REQUEST_TIMEOUT,
+                            Some("Request Timeout".to_string()),
+                        )
+                        .with_header("Connection", "close");
+                        let _ = Self::send_response(reader.get_mut(), response, true, true).await;
+                        return;
+                    }
+                };
 
-        let mut request = parse_request(&request_str);
+            served += 1;
+            let keep_alive = served < max_requests && Self::wants_keep_alive(&request);
+            let chunked = request.version != "HTTP/1.0";
+            let include_body = request.method != HttpMethod::HEAD;
 
-        let router_lock = router.read().await;
+            let response = self.dispatch(request).await;
+
+            // Without chunked encoding, a streaming body ends when the connection is closed,
+            // and connections are closed after their last request when shutting down
+            let keep_alive = keep_alive
+                && !self.shutdown.is_shutting_down()
+                && (chunked || !matches!(response.body, Body::Stream(_)))
+                && !response
+                    .header("connection")
+                    .is_some_and(|value| value.eq_ignore_ascii_case("close"));
+            let response = response.with_header(
+                "Connection",
+                if keep_alive { "keep-alive" } else { "close" },
+            );
 
-        // Execute global middlewares
-        for middleware in &router_lock.middlewares {
-            if let Some(response) = middleware(&request) {
-                Self::send_response(&mut stream, response).await;
+            if Self::send_response(reader.get_mut(), response, chunked, include_body)
+                .await
+                .is_err()
+                || !keep_alive
+            {
                 return;
             }
         }
+    }
 
-        // verify if the request is for a static file
-        if request.path.starts_with("/static/") {
-            let requested_file = &request.path[8..];
-            if let Some(safe_path) = self.sanitize_static_path(requested_file).await {
-                if safe_path.is_dir() || Self::is_forbidden_file(&safe_path) {
-                    Self::send_response(
-                        &mut stream,
-                        Response::new(403, Some("Forbidden".to_string())),
-                    )
-                    .await;
-                    return;
-                }
+    /// Serve a file from the static directory
+    /// ## Args
+    /// - requested_file: &str
+    /// ## Returns
+    /// - Response
+    async fn serve_static(&self, requested_file: &str) -> Response {
+        let safe_path = match self.sanitize_static_path(requested_file).await {
+            Some(safe_path) => safe_path,
+            None => return Response::new(StatusCode::FORBIDDEN, Some("Forbidden".to_string())),
+        };
 
-                match fs::metadata(&safe_path).await {
-                    Ok(metadata)
-                        if metadata.len() > self.inner.lock().await.max_static_file_size as u64 =>
-                    {
-                        Self::send_response(
-                            &mut stream,
-                            Response::new(413, Some("Payload Too Large".to_string())),
-                        )
-                        .await;
-                        return;
-                    }
-                    Ok(_) => match fs::read(&safe_path).await {
-                        Ok(content) => {
-                            let response = Response::new(
-                                200,
-                                Some(String::from_utf8_lossy(&content).to_string()),
-                            )
-                            .with_header("Content-Type", Self::detect_mime_type(&safe_path))
-                            .with_header("X-Content-Type-Options", "nosniff")
-                            .with_header("X-Frame-Options", "DENY");
-                            Self::send_response(&mut stream, response).await;
-                        }
-                        Err(_) => {
-                            Self::send_response(
-                                &mut stream,
-                                Response::new(500, Some("Internal Server Error".to_string())),
-                            )
-                            .await;
-                        }
-                    },
-                    Err(_) => {
-                        Self::send_response(
-                            &mut stream,
-                            Response::new(404, Some("File Not Found".to_string())),
-                        )
-                        .await;
-                    }
-                }
-                return;
-            } else {
-                Self::send_response(
-                    &mut stream,
-                    Response::new(403, Some("Forbidden".to_string())),
+        if safe_path.is_dir() || Self::is_forbidden_file(&safe_path) {
+            return Response::new(StatusCode::FORBIDDEN, Some("Forbidden".to_string()));
+        }
+
+        match fs::metadata(&safe_path).await {
+            Ok(metadata)
+                if metadata.len() > self.inner.lock().await.max_static_file_size as u64 =>
+            {
+                Response::new(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    Some("Payload Too Large".to_string()),
                 )
-                .await;
-                return;
             }
+            Ok(_) => match fs::read(&safe_path).await {
+                Ok(content) => Response::new(StatusCode::OK, content)
+                    .with_header("Content-Type", Self::detect_mime_type(&safe_path)),
+                Err(_) => Response::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Some("Internal Server Error".to_string()),
+                ),
+            },
+            Err(_) => Response::new(StatusCode::NOT_FOUND, Some("File Not Found".to_string())),
         }
+    }
 
-        // Extract the subdomain from the Host header
-        let subdomain = request_str
-            .lines()
-            .find(|line| line.to_lowercase().starts_with("host:"))
-            .and_then(|host_line| {
-                let host = host_line.split_whitespace().nth(1)?;
+    /// Extract the subdomain of a request from its Host header
+    /// ## Args
+    /// - request: &Request
+    /// ## Returns
+    /// - Option<String>
+    fn extract_subdomain(request: &Request) -> Option<String> {
+        request
+            .header("host")
+            .and_then(|host| {
                 if host.contains('.') {
                     let subdomain = host.split('.').next();
                     subdomain.map(String::from)
@@ -391,82 +895,140 @@ impl ServerHandle {
             })
             .or_else(|| {
                 // Extract the subdomain from the X-Mock-Subdomain header (for testing)
-                request_str
-                    .lines()
-                    .find(|line| line.to_lowercase().starts_with("x-mock-subdomain:"))
-                    .and_then(|line| line.split_whitespace().nth(1).map(String::from))
-            });
+                request.header("x-mock-subdomain").map(String::from)
+            })
+    }
 
-        println!("Subdomain: {:?}", subdomain);
+    /// Dispatch a parsed request through the global middlewares
+    /// ## Args
+    /// - request: Request
+    /// ## Returns
+    /// - Response
+    pub(crate) async fn dispatch(&self, mut request: Request) -> Response {
+        let (middlewares, state) = {
+            let router = self.router.read().await;
+            router.trusted_proxies.resolve(&mut request);
+            (Arc::clone(&router.middlewares), Arc::clone(&router.state))
+        };
+        request.extensions.insert(state);
+
+        let server = self.clone();
+        let endpoint: Handler = Arc::new(move |request: Request| {
+            let server = server.clone();
+            Box::pin(async move { server.route_request(request).await })
+        });
+
+        let span = tracing::info_span!(
+            "request",
+            method = %request.method,
+            path = %request.path,
+            subdomain = Empty,
+            route = Empty,
+            status = Empty,
+            latency_ms = Empty,
+        );
+        let start = Instant::now();
+        let response = Next::new(middlewares, endpoint)
+            .run(request)
+            .instrument(span.clone())
+            .await;
+
+        let status = response.status_code.as_u16();
+        let latency = start.elapsed();
+        span.record("status", status);
+        span.record("latency_ms", latency.as_secs_f64() * 1000.0);
+        span.in_scope(|| {
+            if status >= 500 {
+                tracing::error!(status, ?latency, "Request failed");
+            } else {
+                tracing::debug!(status, ?latency, "Request completed");
+            }
+        });
+
+        response
+    }
+
+    /// Route a request to the static files or the routes
+    /// ## Args
+    /// - request: Request
+    /// ## Returns
+    /// - Response
+    async fn route_request(&self, mut request: Request) -> Response {
+        // verify if the request is for a static file
+        if let Some(requested_file) = request.path.strip_prefix("/static/") {
+            return self.serve_static(requested_file).await;
+        }
+
+        let router = self.router();
+        let router_lock = router.read().await;
+
+        // Extract the subdomain from the Host header
+        let subdomain = Self::extract_subdomain(&request);
+
+        if let Some(subdomain) = &subdomain {
+            Span::current().record("subdomain", subdomain.as_str());
+        }
+
+        // HEAD requests are served by the GET routes when there is no HEAD route
+        let find_route = |path: &str, method: &HttpMethod| {
+            router_lock
+                .find_route(path, method, subdomain.as_deref())
+                .or_else(|| match method {
+                    HttpMethod::HEAD => {
+                        router_lock.find_route(path, &HttpMethod::GET, subdomain.as_deref())
+                    }
+                    _ => None,
+                })
+        };
 
         // Redirect if the path ends with a trailing slash
         if request.path.ends_with('/') && request.path != "/" {
             let new_path = request.path.trim_end_matches('/').to_string();
-            if router_lock
-                .find_route(&new_path, &request.method, subdomain.as_deref())
-                .is_some()
-            {
-                let redirect_response = Response::new(301, None).with_header("Location", &new_path);
-                Self::send_response(&mut stream, redirect_response).await;
-                return;
+            if find_route(&new_path, &request.method).is_some() {
+                return Response::new(StatusCode::MOVED_PERMANENTLY, None)
+                    .with_header("Location", &new_path);
             }
         }
 
-        println!("Parsed request: {:?} {:?}", request.method, request.path);
-
         // Search for a matching route
-        if let Some(route) =
-            router_lock.find_route(&request.path, &request.method, subdomain.as_deref())
+        if let Some(RouteMatch {
+            route,
+            params,
+            path_params,
+        }) = find_route(&request.path, &request.method)
         {
-            if let Some(regex) = &route.regex {
-                if !regex.is_match(&request.path) {
-                    Self::send_response(
-                        &mut stream,
-                        Response::new(404, Some("Not Found".to_string())),
-                    )
-                    .await;
-                    return;
-                }
-            }
-
-            // Execute route middlewares
-            for middleware in &route.middlewares {
-                if let Some(response) = middleware(&request) {
-                    Self::send_response(&mut stream, response).await;
-                    return;
-                }
-            }
-
             // Extract route parameters
-            let route_params = router_lock.extract_params(route, &request.path);
-            request.params.extend(route_params);
+            request.params.extend(params);
+            request.path_params = path_params;
+            Span::current().record("route", route.path());
 
-            // Execute the route handler
-            let response = (route.handler)(request);
-            Self::send_response(&mut stream, response).await;
+            // Execute the route handler without holding the router lock, so that slow
+            // handlers do not block the registration of routes nor the next requests
+            let handler = route.handler.clone();
+            drop(router_lock);
+            handler(request).await
         } else {
             // Check if the path is allowed but the method is not
-            if router_lock
-                .routes
+            let allowed_methods =
+                router_lock.get_allowed_methods(&request.path, subdomain.as_deref());
+            if allowed_methods.is_empty() {
+                return Response::new(StatusCode::NOT_FOUND, Some("Not Found".to_string()));
+            }
+
+            let allow_header = allowed_methods
                 .iter()
-                .any(|r| r.path == request.path && r.subdomain.as_deref() == subdomain.as_deref())
-            {
-                let allowed_methods =
-                    router_lock.get_allowed_methods(&request.path, subdomain.as_deref());
-                let allow_header = allowed_methods
-                    .iter()
-                    .map(|m| m.to_string())
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                let response = Response::new(405, Some("Method Not Allowed".to_string()))
-                    .with_header("Allow", &allow_header);
-                Self::send_response(&mut stream, response).await;
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            // OPTIONS requests are answered with the allowed methods when there is no OPTIONS route
+            if request.method == HttpMethod::OPTIONS {
+                Response::new(StatusCode::NO_CONTENT, None).with_header("Allow", &allow_header)
             } else {
-                Self::send_response(
-                    &mut stream,
-                    Response::new(404, Some("Not Found".to_string())),
+                Response::new(
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    Some("Method Not Allowed".to_string()),
                 )
-                .await;
+                .with_header("Allow", &allow_header)
             }
         }
     }
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Define the ShutdownHandle struct
+/// Stops a running Server from anywhere (a signal handler, a test, an admin route):
+/// the Server stops accepting connections, lets the requests in progress finish and
+/// runs its shutdown hooks
+/// ## Fields
+/// - sender: Arc<watch::Sender<bool>>
+#[derive(Clone, Debug)]
+pub struct ShutdownHandle {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+/// Implement the ShutdownHandle struct
+impl ShutdownHandle {
+    /// Create a new ShutdownHandle instance
+    /// ## Returns
+    /// - ShutdownHandle
+    pub(crate) fn new() -> Self {
+        Self {
+            sender: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Ask the Server to shut down
+    /// ## Args
+    /// - self
+    /// ## Side Effects
+    /// - Starts the graceful shutdown of the Server
+    pub fn shutdown(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Forget a previous shutdown, for the Server to run again
+    /// ## Args
+    /// - self
+    /// ## Side Effects
+    /// - The Server is no longer shutting down
+    pub(crate) fn reset(&self) {
+        self.sender.send_replace(false);
+    }
+
+    /// Check if the Server was asked to shut down
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub fn is_shutting_down(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Wait until the Server is asked to shut down
+    /// ## Args
+    /// - self
+    pub async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as self, the channel cannot be closed
+        let _ = receiver.wait_for(|shutting_down| *shutting_down).await;
+    }
+}
@@ -0,0 +1,190 @@
+use crate::routes::http::method::HttpMethod;
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::server::server_handle::ServerHandle;
+use serde::Serialize;
+use std::net::SocketAddr;
+
+/// Define the TestClient struct
+/// Sends Requests through a Server without opening a socket: they go through the
+/// global middlewares, the static files, the subdomain detection and the Router like
+/// the Requests read from a connection. The Server does not need to run, its startup
+/// hooks are not called
+/// ```rust,ignore
+/// let client = TestClient::new(app.clone());
+/// let response = client.get("/users/42").header("X-Mock-Subdomain", "api").send().await;
+/// assert_eq!(response.status_code, StatusCode::OK);
+/// ```
+/// ## Fields
+/// - server: ServerHandle
+#[derive(Clone)]
+pub struct TestClient {
+    server: ServerHandle,
+}
+
+/// Implement the TestClient struct
+impl TestClient {
+    /// Create a new TestClient instance
+    /// ## Args
+    /// - server: ServerHandle
+    /// ## Returns
+    /// - TestClient
+    pub fn new(server: ServerHandle) -> Self {
+        Self { server }
+    }
+
+    /// Send a Request to the Server
+    /// The Response is returned as built by the Server, before it is written to a
+    /// connection (the body of a HEAD Response is kept)
+    /// ## Args
+    /// - self
+    /// - request: Request
+    /// ## Returns
+    /// - Response
+    pub async fn send(&self, request: Request) -> Response {
+        self.server.dispatch(request).await
+    }
+
+    /// Start a Request with a method
+    /// ## Args
+    /// - self
+    /// - method: HttpMethod
+    /// - target: &str (path with an optional query string)
+    /// ## Returns
+    /// - TestRequest
+    pub fn request(&self, method: HttpMethod, target: &str) -> TestRequest {
+        TestRequest {
+            client: self.clone(),
+            request: Request::new(method, target),
+        }
+    }
+
+    /// Start a GET Request
+    /// ## Args
+    /// - self
+    /// - target: &str
+    /// ## Returns
+    /// - TestRequest
+    pub fn get(&self, target: &str) -> TestRequest {
+        self.request(HttpMethod::GET, target)
+    }
+
+    /// Start a POST Request
+    /// ## Args
+    /// - self
+    /// - target: &str
+    /// ## Returns
+    /// - TestRequest
+    pub fn post(&self, target: &str) -> TestRequest {
+        self.request(HttpMethod::POST, target)
+    }
+
+    /// Start a PUT Request
+    /// ## Args
+    /// - self
+    /// - target: &str
+    /// ## Returns
+    /// - TestRequest
+    pub fn put(&self, target: &str) -> TestRequest {
+        self.request(HttpMethod::PUT, target)
+    }
+
+    /// Start a DELETE Request
+    /// ## Args
+    /// - self
+    /// - target: &str
+    /// ## Returns
+    /// - TestRequest
+    pub fn delete(&self, target: &str) -> TestRequest {
+        self.request(HttpMethod::DELETE, target)
+    }
+}
+
+/// Define the TestRequest struct
+/// A Request being built by a TestClient
+/// ## Fields
+/// - client: TestClient
+/// - request: Request
+pub struct TestRequest {
+    client: TestClient,
+    request: Request,
+}
+
+/// Implement the TestRequest struct
+impl TestRequest {
+    /// Add a header to the Request
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// - value: &str
+    /// ## Returns
+    /// - TestRequest
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.request = self.request.with_header(name, value);
+        self
+    }
+
+    /// Set the address the Request comes from (Requests have none by default)
+    /// ## Args
+    /// - self
+    /// - addr: SocketAddr
+    /// ## Returns
+    /// - TestRequest
+    pub fn remote_addr(mut self, addr: SocketAddr) -> Self {
+        self.request = self.request.with_remote_addr(addr);
+        self
+    }
+
+    /// Set the body of the Request
+    /// ## Args
+    /// - self
+    /// - body: B
+    /// ## Where
+    /// - B: Into<Vec<u8>>
+    /// ## Returns
+    /// - TestRequest
+    pub fn body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.request = self.request.with_body(body);
+        self
+    }
+
+    /// Set a JSON body and the `application/json` content type
+    /// ## Args
+    /// - self
+    /// - value: &T
+    /// ## Where
+    /// - T: Serialize
+    /// ## Returns
+    /// - TestRequest
+    /// ## Panics
+    /// - If the value cannot be serialized
+    pub fn json<T: Serialize>(self, value: &T) -> Self {
+        let body = serde_json::to_vec(value).expect("the JSON body cannot be serialized");
+        self.header("Content-Type", "application/json").body(body)
+    }
+
+    /// Set a URL-encoded form body and the `application/x-www-form-urlencoded` content type
+    /// ## Args
+    /// - self
+    /// - value: &T
+    /// ## Where
+    /// - T: Serialize
+    /// ## Returns
+    /// - TestRequest
+    /// ## Panics
+    /// - If the value cannot be serialized
+    pub fn form<T: Serialize>(self, value: &T) -> Self {
+        let body = serde_urlencoded::to_string(value).expect("the form body cannot be serialized");
+        self.header("Content-Type", "application/x-www-form-urlencoded")
+            .body(body)
+    }
+
+    /// Send the Request
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Response
+    pub async fn send(self) -> Response {
+        self.client.send(self.request).await
+    }
+}
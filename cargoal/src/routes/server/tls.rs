@@ -0,0 +1,298 @@
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::ServerConfig;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use tokio::fs;
+
+/// Define the TlsError enum
+/// ## Variants
+/// - Io: a certificate or key file cannot be read
+/// - InvalidPem: a file does not contain a PEM certificate chain or private key
+/// - InvalidKey: the private key is not supported or does not match the certificate
+/// - Config(String): the TLS configuration cannot be built
+#[derive(Debug)]
+pub enum TlsError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    InvalidPem {
+        path: PathBuf,
+        reason: String,
+    },
+    InvalidKey {
+        path: PathBuf,
+        reason: String,
+    },
+    Config(String),
+}
+
+/// Implement the Display trait for TlsError
+impl fmt::Display for TlsError {
+    /// Format the TlsError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::Io { path, source } => {
+                write!(f, "Impossible to read '{}': {}", path.display(), source)
+            }
+            TlsError::InvalidPem { path, reason } => {
+                write!(f, "Invalid PEM file '{}': {}", path.display(), reason)
+            }
+            TlsError::InvalidKey { path, reason } => {
+                write!(f, "Invalid private key '{}': {}", path.display(), reason)
+            }
+            TlsError::Config(reason) => write!(f, "Invalid TLS configuration: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+/// Define the CertificateFiles struct
+/// ## Fields
+/// - cert_path: PathBuf (PEM certificate chain, leaf first)
+/// - key_path: PathBuf (PEM private key)
+/// - modified: Option<(SystemTime, SystemTime)> (modification times when loaded)
+#[derive(Debug, Clone)]
+struct CertificateFiles {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    modified: Option<(SystemTime, SystemTime)>,
+}
+
+/// Implement the CertificateFiles struct
+impl CertificateFiles {
+    /// Get the modification times of the files
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Option<(SystemTime, SystemTime)>
+    async fn modification_times(&self) -> Option<(SystemTime, SystemTime)> {
+        Some((
+            modified(&self.cert_path).await?,
+            modified(&self.key_path).await?,
+        ))
+    }
+
+    /// Load the certificate chain and its private key
+    /// The files are read without blocking the runtime, then parsed
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Result<Arc<CertifiedKey>, TlsError>
+    async fn load(&self) -> Result<Arc<CertifiedKey>, TlsError> {
+        let cert_pem = read(&self.cert_path).await?;
+        let key_pem = read(&self.key_path).await?;
+
+        let certs = CertificateDer::pem_slice_iter(&cert_pem)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| TlsError::InvalidPem {
+                path: self.cert_path.clone(),
+                reason: err.to_string(),
+            })?;
+        if certs.is_empty() {
+            return Err(TlsError::InvalidPem {
+                path: self.cert_path.clone(),
+                reason: "no certificate found".to_string(),
+            });
+        }
+
+        let key = PrivateKeyDer::from_pem_slice(&key_pem).map_err(|err| TlsError::InvalidPem {
+            path: self.key_path.clone(),
+            reason: err.to_string(),
+        })?;
+        let invalid_key = |reason: String| TlsError::InvalidKey {
+            path: self.key_path.clone(),
+            reason,
+        };
+        let signing_key = provider()
+            .key_provider
+            .load_private_key(key)
+            .map_err(|err| invalid_key(err.to_string()))?;
+
+        let certified_key = CertifiedKey::new(certs, signing_key);
+        certified_key
+            .keys_match()
+            .map_err(|err| invalid_key(err.to_string()))?;
+
+        Ok(Arc::new(certified_key))
+    }
+}
+
+/// Define the Certificate struct
+/// ## Fields
+/// - files: CertificateFiles
+/// - key: Arc<CertifiedKey>
+#[derive(Debug)]
+struct Certificate {
+    files: CertificateFiles,
+    key: Arc<CertifiedKey>,
+}
+
+/// Define the CertificateStore struct
+/// The certificates of the Server, chosen with the SNI name sent by the client
+/// ## Fields
+/// - certificates: RwLock<HashMap<Option<String>, Certificate>> (None is the default one)
+#[derive(Debug, Default)]
+pub(crate) struct CertificateStore {
+    certificates: RwLock<HashMap<Option<String>, Certificate>>,
+}
+
+/// Implement the CertificateStore struct
+impl CertificateStore {
+    /// Load a certificate and add it to the store
+    /// The lock is only taken to swap in the loaded certificate
+    /// ## Args
+    /// - self
+    /// - server_name: Option<&str> (None for the default certificate, `*.example.com`
+    ///   for a wildcard)
+    /// - cert_path: &str
+    /// - key_path: &str
+    /// ## Returns
+    /// - Result<(), TlsError>
+    pub(crate) async fn insert(
+        &self,
+        server_name: Option<&str>,
+        cert_path: &str,
+        key_path: &str,
+    ) -> Result<(), TlsError> {
+        let mut files = CertificateFiles {
+            cert_path: PathBuf::from(cert_path),
+            key_path: PathBuf::from(key_path),
+            modified: None,
+        };
+        files.modified = files.modification_times().await;
+        let key = files.load().await?;
+
+        self.certificates
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                server_name.map(|name| name.to_ascii_lowercase()),
+                Certificate { files, key },
+            );
+        Ok(())
+    }
+
+    /// Reload the certificates from disk
+    /// A certificate that cannot be loaded is reported and the previous one is kept.
+    /// The files are read without holding the lock, which is only taken to swap in
+    /// each reloaded certificate
+    /// ## Args
+    /// - self
+    /// - only_changed: bool (only reload the files modified since they were loaded)
+    /// ## Returns
+    /// - Result<usize, TlsError> (the number of reloaded certificates, or the first error)
+    pub(crate) async fn reload(&self, only_changed: bool) -> Result<usize, TlsError> {
+        let files: Vec<(Option<String>, CertificateFiles)> = self
+            .certificates
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(name, certificate)| (name.clone(), certificate.files.clone()))
+            .collect();
+
+        let mut reloaded = 0;
+        let mut first_error = None;
+        for (name, mut files) in files {
+            let modified = files.modification_times().await;
+            if only_changed && modified == files.modified {
+                continue;
+            }
+            match files.load().await {
+                Ok(key) => {
+                    files.modified = modified;
+                    self.certificates
+                        .write()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(name, Certificate { files, key });
+                    reloaded += 1;
+                }
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(reloaded),
+        }
+    }
+
+    /// Build the rustls configuration using the store
+    /// ## Args
+    /// - self: &Arc<Self>
+    /// ## Returns
+    /// - Result<Arc<ServerConfig>, TlsError>
+    pub(crate) fn server_config(self: &Arc<Self>) -> Result<Arc<ServerConfig>, TlsError> {
+        let mut config = ServerConfig::builder_with_provider(Arc::new(provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|err| TlsError::Config(err.to_string()))?
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::clone(self) as Arc<dyn ResolvesServerCert>);
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(Arc::new(config))
+    }
+}
+
+/// Implement ResolvesServerCert for CertificateStore
+/// The certificate of the exact server name is used first, then the wildcard one
+/// (`*.example.com`), then the default one
+impl ResolvesServerCert for CertificateStore {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let certificates = self.certificates.read().unwrap_or_else(|e| e.into_inner());
+        let lookup = |name: Option<String>| certificates.get(&name).map(|c| Arc::clone(&c.key));
+
+        let server_name = client_hello.server_name().map(str::to_ascii_lowercase);
+        server_name
+            .as_deref()
+            .and_then(|name| {
+                lookup(Some(name.to_string())).or_else(|| {
+                    let (_, parent) = name.split_once('.')?;
+                    lookup(Some(format!("*.{}", parent)))
+                })
+            })
+            .or_else(|| lookup(None))
+    }
+}
+
+/// Read a certificate or key file
+/// ## Args
+/// - path: &Path
+/// ## Returns
+/// - Result<Vec<u8>, TlsError>
+async fn read(path: &Path) -> Result<Vec<u8>, TlsError> {
+    fs::read(path).await.map_err(|source| TlsError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Get the modification time of a file
+/// ## Args
+/// - path: &Path
+/// ## Returns
+/// - Option<SystemTime>
+async fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).await.ok()?.modified().ok()
+}
+
+/// Get the crypto provider used by the Server
+/// ## Returns
+/// - CryptoProvider
+fn provider() -> CryptoProvider {
+    ring::default_provider()
+}
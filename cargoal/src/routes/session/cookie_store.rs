@@ -0,0 +1,62 @@
+use super::store::{SessionData, SessionError, SessionStore};
+use crate::routes::http::key::Key;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::sync::Arc;
+
+/// Name the signatures of the sessions are bound to
+const SIGNATURE_CONTEXT: &str = "cargoal.session";
+
+/// Define the CookieStore struct
+/// Keeps the sessions in the session cookie itself, signed so that the client cannot
+/// modify them. Nothing is kept on the server: the sessions cannot be revoked before
+/// they expire, the values are readable by the client and browsers limit cookies to
+/// about 4 KB
+/// ## Fields
+/// - key: Arc<Key>
+#[derive(Debug, Clone)]
+pub struct CookieStore {
+    key: Arc<Key>,
+}
+
+/// Implement the CookieStore struct
+impl CookieStore {
+    /// Create a new CookieStore
+    /// ## Args
+    /// - key: Key (e.g. `Key::from_secret(secret)?`)
+    /// ## Returns
+    /// - CookieStore
+    pub fn new(key: Key) -> Self {
+        Self { key: Arc::new(key) }
+    }
+}
+
+/// Implement the SessionStore trait for CookieStore
+impl SessionStore for CookieStore {
+    async fn load(&self, cookie: &str) -> Result<Option<SessionData>, SessionError> {
+        // Sessions with an invalid signature are unknown sessions
+        let data = self
+            .key
+            .verify(SIGNATURE_CONTEXT, cookie)
+            .and_then(|encoded| URL_SAFE_NO_PAD.decode(encoded).ok())
+            .and_then(|json| serde_json::from_slice(&json).ok());
+        Ok(data)
+    }
+
+    async fn save(
+        &self,
+        _cookie: Option<&str>,
+        data: &SessionData,
+    ) -> Result<String, SessionError> {
+        let encoded = URL_SAFE_NO_PAD.encode(serde_json::to_vec(data)?);
+        Ok(self.key.sign(SIGNATURE_CONTEXT, &encoded))
+    }
+
+    async fn delete(&self, _cookie: &str) -> Result<(), SessionError> {
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> Result<u64, SessionError> {
+        Ok(0)
+    }
+}
@@ -0,0 +1,175 @@
+use super::store::{generate_id, unix_now, SessionData, SessionError, SessionStore};
+use crate::db::connection::{Database, DatabasePool};
+use sqlx::Row;
+use std::sync::Arc;
+
+/// Run the same code on the pool of any database
+macro_rules! on_pool {
+    ($pool:expr, $p:ident => $body:expr) => {
+        match $pool {
+            DatabasePool::Postgres($p) => $body,
+            DatabasePool::MySql($p) => $body,
+            DatabasePool::Sqlite($p) => $body,
+        }
+    };
+}
+
+/// Define the DatabaseStore struct
+/// Keeps the sessions in a table of a Postgres, MySQL or SQLite database, shared by
+/// every instance of the application. Create the table with `migrate`
+/// ## Fields
+/// - database: Arc<Database>
+/// - table: String (`sessions` by default)
+#[derive(Debug, Clone)]
+pub struct DatabaseStore {
+    database: Arc<Database>,
+    table: String,
+}
+
+/// Implement the DatabaseStore struct
+impl DatabaseStore {
+    /// Create a new DatabaseStore using the `sessions` table
+    /// ## Args
+    /// - database: Arc<Database>
+    /// ## Returns
+    /// - DatabaseStore
+    pub fn new(database: Arc<Database>) -> Self {
+        Self {
+            database,
+            table: "sessions".to_string(),
+        }
+    }
+
+    /// Use another table
+    /// ## Args
+    /// - self
+    /// - table: &str
+    /// ## Returns
+    /// - DatabaseStore
+    /// ## Panics
+    /// - If the name is not made of ASCII letters, digits and underscores
+    pub fn with_table(mut self, table: &str) -> Self {
+        assert!(
+            !table.is_empty() && table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+            "Invalid session table name '{}'",
+            table
+        );
+        self.table = table.to_string();
+        self
+    }
+
+    /// Create the table of the sessions if it does not exist
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Result<(), SessionError>
+    pub async fn migrate(&self) -> Result<(), SessionError> {
+        let query = format!(
+            "CREATE TABLE IF NOT EXISTS {} (id VARCHAR(64) PRIMARY KEY, data TEXT NOT NULL, expires_at BIGINT NOT NULL)",
+            self.table
+        );
+        self.database.execute(&query).await?;
+        Ok(())
+    }
+
+    /// Get the placeholder of a query parameter
+    /// ## Args
+    /// - self
+    /// - index: usize (starting at 1)
+    /// ## Returns
+    /// - String (`$1` for Postgres, `?` otherwise)
+    fn placeholder(&self, index: usize) -> String {
+        match self.database.pool() {
+            DatabasePool::Postgres(_) => format!("${}", index),
+            DatabasePool::MySql(_) | DatabasePool::Sqlite(_) => "?".to_string(),
+        }
+    }
+}
+
+/// Implement the SessionStore trait for DatabaseStore
+impl SessionStore for DatabaseStore {
+    async fn load(&self, cookie: &str) -> Result<Option<SessionData>, SessionError> {
+        let query = format!(
+            "SELECT data, expires_at FROM {} WHERE id = {}",
+            self.table,
+            self.placeholder(1)
+        );
+        let row = on_pool!(self.database.pool(), pool => sqlx::query(&query)
+            .bind(cookie)
+            .fetch_optional(pool)
+            .await?
+            .map(|row| row.try_get::<String, _>("data").and_then(|data| {
+                Ok((data, row.try_get::<i64, _>("expires_at")?))
+            }))
+            .transpose()?);
+
+        let Some((data, expires_at)) = row else {
+            return Ok(None);
+        };
+        Ok(Some(SessionData {
+            values: serde_json::from_str(&data)?,
+            expires_at: expires_at.max(0) as u64,
+        }))
+    }
+
+    async fn save(&self, cookie: Option<&str>, data: &SessionData) -> Result<String, SessionError> {
+        let id = cookie.map_or_else(generate_id, str::to_string);
+        let values = serde_json::to_string(&data.values)?;
+        let expires_at = i64::try_from(data.expires_at).unwrap_or(i64::MAX);
+
+        let upsert = match self.database.pool() {
+            DatabasePool::MySql(_) => {
+                "ON DUPLICATE KEY UPDATE data = VALUES(data), expires_at = VALUES(expires_at)"
+            }
+            DatabasePool::Postgres(_) | DatabasePool::Sqlite(_) => {
+                "ON CONFLICT (id) DO UPDATE SET data = excluded.data, expires_at = excluded.expires_at"
+            }
+        };
+        let query = format!(
+            "INSERT INTO {} (id, data, expires_at) VALUES ({}, {}, {}) {}",
+            self.table,
+            self.placeholder(1),
+            self.placeholder(2),
+            self.placeholder(3),
+            upsert
+        );
+        on_pool!(self.database.pool(), pool => sqlx::query(&query)
+            .bind(&id)
+            .bind(&values)
+            .bind(expires_at)
+            .execute(pool)
+            .await
+            .map(|_| ())?);
+
+        Ok(id)
+    }
+
+    async fn delete(&self, cookie: &str) -> Result<(), SessionError> {
+        let query = format!(
+            "DELETE FROM {} WHERE id = {}",
+            self.table,
+            self.placeholder(1)
+        );
+        on_pool!(self.database.pool(), pool => sqlx::query(&query)
+            .bind(cookie)
+            .execute(pool)
+            .await
+            .map(|_| ())?);
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> Result<u64, SessionError> {
+        let query = format!(
+            "DELETE FROM {} WHERE expires_at <= {}",
+            self.table,
+            self.placeholder(1)
+        );
+        let now = i64::try_from(unix_now()).unwrap_or(i64::MAX);
+        let deleted = on_pool!(self.database.pool(), pool => sqlx::query(&query)
+            .bind(now)
+            .execute(pool)
+            .await?
+            .rows_affected());
+        Ok(deleted)
+    }
+}
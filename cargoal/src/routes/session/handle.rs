@@ -0,0 +1,177 @@
+use super::store::SessionError;
+use crate::routes::extract::from_request::{rejection, FromRequest};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Define the Status enum
+/// What the Sessions middleware does with a session once the Response is ready
+/// ## Variants
+/// - Unchanged: nothing
+/// - Changed: save it
+/// - Rotated: save it under a new identifier and delete the previous one
+/// - Destroyed: delete it and the session cookie
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    Unchanged,
+    Changed,
+    Rotated,
+    Destroyed,
+}
+
+/// Define the SessionState struct
+/// ## Fields
+/// - values: HashMap<String, serde_json::Value>
+/// - status: Status
+#[derive(Debug)]
+pub(crate) struct SessionState {
+    pub(crate) values: HashMap<String, serde_json::Value>,
+    pub(crate) status: Status,
+}
+
+/// Define the Session struct
+/// The session of the client, given to the handlers by the Sessions middleware.
+/// Also an extractor, answering 500 when the Sessions middleware is missing.
+/// The clones share the same session
+/// ## Fields
+/// - state: Arc<Mutex<SessionState>>
+#[derive(Debug, Clone)]
+pub struct Session {
+    state: Arc<Mutex<SessionState>>,
+}
+
+/// Implement the Session struct
+impl Session {
+    /// Create a Session holding values
+    /// ## Args
+    /// - values: HashMap<String, serde_json::Value>
+    /// ## Returns
+    /// - Session
+    pub(crate) fn new(values: HashMap<String, serde_json::Value>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SessionState {
+                values,
+                status: Status::Unchanged,
+            })),
+        }
+    }
+
+    /// Lock the state of the Session
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - MutexGuard<SessionState>
+    pub(crate) fn lock(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Mark the Session as changed, unless it is rotated or destroyed
+    /// ## Args
+    /// - state: &mut SessionState
+    fn mark_changed(state: &mut SessionState) {
+        if state.status == Status::Unchanged {
+            state.status = Status::Changed;
+        }
+    }
+
+    /// Get a value of the Session
+    /// ## Args
+    /// - self
+    /// - key: &str
+    /// ## Returns
+    /// - Option<T> (None if the value is missing or has another type)
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.lock();
+        let value = state.values.get(key)?.clone();
+        serde_json::from_value(value).ok()
+    }
+
+    /// Set a value of the Session
+    /// ## Args
+    /// - self
+    /// - key: &str
+    /// - value: T
+    /// ## Where
+    /// - T: Serialize
+    /// ## Returns
+    /// - Result<(), SessionError> (an error if the value cannot be serialized)
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) -> Result<(), SessionError> {
+        let value = serde_json::to_value(value)?;
+        let mut state = self.lock();
+        state.values.insert(key.to_string(), value);
+        Self::mark_changed(&mut state);
+        Ok(())
+    }
+
+    /// Remove a value of the Session
+    /// ## Args
+    /// - self
+    /// - key: &str
+    /// ## Returns
+    /// - bool (true if the value existed)
+    pub fn remove(&self, key: &str) -> bool {
+        let mut state = self.lock();
+        let removed = state.values.remove(key).is_some();
+        if removed {
+            Self::mark_changed(&mut state);
+        }
+        removed
+    }
+
+    /// Remove every value of the Session
+    /// ## Args
+    /// - self
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        if !state.values.is_empty() {
+            state.values.clear();
+            Self::mark_changed(&mut state);
+        }
+    }
+
+    /// Check if the Session has no value
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub fn is_empty(&self) -> bool {
+        self.lock().values.is_empty()
+    }
+
+    /// Give the Session a new identifier, keeping its values
+    /// Call it when the privileges of the client change (login, logout) so that an
+    /// identifier known before cannot be used afterwards
+    /// ## Args
+    /// - self
+    pub fn rotate(&self) {
+        let mut state = self.lock();
+        if state.status != Status::Destroyed {
+            state.status = Status::Rotated;
+        }
+    }
+
+    /// Delete the Session and its cookie
+    /// ## Args
+    /// - self
+    pub fn destroy(&self) {
+        let mut state = self.lock();
+        state.values.clear();
+        state.status = Status::Destroyed;
+    }
+}
+
+/// Implement FromRequest for Session
+impl FromRequest for Session {
+    fn from_request(req: &Request) -> Result<Self, Response> {
+        req.extensions.get::<Session>().cloned().ok_or_else(|| {
+            rejection(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "No session: the Sessions middleware is missing".to_string(),
+            )
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use super::store::{generate_id, SessionData, SessionError, SessionStore};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Define the MemoryStore struct
+/// Keeps the sessions in the memory of the process: they are lost on restart and not
+/// shared between several instances of the application
+/// ## Fields
+/// - sessions: Arc<RwLock<HashMap<String, SessionData>>> (by session identifier)
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    sessions: Arc<RwLock<HashMap<String, SessionData>>>,
+}
+
+/// Implement the MemoryStore struct
+impl MemoryStore {
+    /// Create a new empty MemoryStore
+    /// ## Returns
+    /// - MemoryStore
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of sessions kept, expired ones included
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - usize
+    pub fn len(&self) -> usize {
+        self.sessions
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// Check if no session is kept
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Implement the SessionStore trait for MemoryStore
+impl SessionStore for MemoryStore {
+    async fn load(&self, cookie: &str) -> Result<Option<SessionData>, SessionError> {
+        let sessions = self.sessions.read().unwrap_or_else(|e| e.into_inner());
+        Ok(sessions.get(cookie).cloned())
+    }
+
+    async fn save(&self, cookie: Option<&str>, data: &SessionData) -> Result<String, SessionError> {
+        let id = cookie.map_or_else(generate_id, str::to_string);
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        sessions.insert(id.clone(), data.clone());
+        Ok(id)
+    }
+
+    async fn delete(&self, cookie: &str) -> Result<(), SessionError> {
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(cookie);
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> Result<u64, SessionError> {
+        let mut sessions = self.sessions.write().unwrap_or_else(|e| e.into_inner());
+        let before = sessions.len();
+        sessions.retain(|_, data| !data.is_expired());
+        Ok((before - sessions.len()) as u64)
+    }
+}
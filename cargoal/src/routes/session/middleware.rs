@@ -0,0 +1,256 @@
+use super::handle::{Session, Status};
+use super::store::{unix_now, SessionData, SessionStore};
+use crate::routes::http::cookie::{Cookie, SameSite};
+use crate::routes::http::request::Request;
+use crate::routes::http::response::Response;
+use crate::routes::http::status::StatusCode;
+use crate::routes::routing::{Middleware, Next};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Define the Sessions struct
+/// A middleware loading the session of the client from a store, giving it to the
+/// handlers as a `Session`, then saving it and sending the session cookie when it
+/// changed. New sessions are only created once a value is set
+/// ## Fields
+/// - store: Arc<S>
+/// - cookie_name: String
+/// - ttl: Duration
+/// - secure: bool
+/// - same_site: SameSite
+/// ## Type Parameters
+/// - S: SessionStore
+pub struct Sessions<S> {
+    store: Arc<S>,
+    cookie_name: String,
+    ttl: Duration,
+    secure: bool,
+    same_site: SameSite,
+}
+
+/// Implement the Clone trait for Sessions (without requiring `S: Clone`)
+impl<S> Clone for Sessions<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            cookie_name: self.cookie_name.clone(),
+            ttl: self.ttl,
+            secure: self.secure,
+            same_site: self.same_site,
+        }
+    }
+}
+
+/// Implement the Sessions struct
+impl<S: SessionStore> Sessions<S> {
+    /// Create a new Sessions middleware
+    /// The sessions expire after a day, in a `session` cookie sent over HTTPS only,
+    /// hidden from the scripts and with `SameSite=Lax`
+    /// ## Args
+    /// - store: S
+    /// ## Returns
+    /// - Sessions<S>
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            cookie_name: "session".to_string(),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            secure: true,
+            same_site: SameSite::Lax,
+        }
+    }
+
+    /// Set the name of the session cookie
+    /// ## Args
+    /// - self
+    /// - name: &str
+    /// ## Returns
+    /// - Sessions<S>
+    pub fn with_cookie_name(mut self, name: &str) -> Self {
+        self.cookie_name = name.to_string();
+        self
+    }
+
+    /// Set how long a session lasts after its last change
+    /// ## Args
+    /// - self
+    /// - ttl: Duration
+    /// ## Returns
+    /// - Sessions<S>
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Send the session cookie over HTTPS only (true by default, disable it for
+    /// local development over HTTP)
+    /// ## Args
+    /// - self
+    /// - secure: bool
+    /// ## Returns
+    /// - Sessions<S>
+    pub fn with_secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Set when the session cookie is sent with cross-site requests
+    /// ## Args
+    /// - self
+    /// - same_site: SameSite
+    /// ## Returns
+    /// - Sessions<S>
+    pub fn with_same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Get the store of the sessions
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - &S
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Delete the expired sessions of the store periodically
+    /// ## Args
+    /// - self
+    /// - interval: Duration
+    /// ## Returns
+    /// - JoinHandle<()> (abort it to stop the cleanup)
+    /// ## Panics
+    /// - If called outside of a Tokio runtime
+    pub fn spawn_cleanup(&self, interval: Duration) -> JoinHandle<()> {
+        let store = Arc::clone(&self.store);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match store.cleanup().await {
+                    Ok(0) => {}
+                    Ok(deleted) => tracing::debug!("Deleted {} expired session(s)", deleted),
+                    Err(e) => tracing::error!(error = %e, "Error deleting the expired sessions"),
+                }
+            }
+        })
+    }
+
+    /// Build the session cookie
+    /// ## Args
+    /// - self
+    /// - value: &str
+    /// ## Returns
+    /// - Cookie
+    fn cookie(&self, value: &str) -> Cookie {
+        Cookie::new(&self.cookie_name, value)
+            .with_path("/")
+            .with_max_age(self.ttl)
+            .with_secure(self.secure)
+            .with_http_only(true)
+            .with_same_site(self.same_site)
+    }
+
+    /// Load the session of a cookie
+    /// Expired, unknown and unreadable sessions are replaced with a new session
+    /// ## Args
+    /// - self
+    /// - cookie: Option<&str>
+    /// ## Returns
+    /// - Option<SessionData>
+    async fn load(&self, cookie: Option<&str>) -> Option<SessionData> {
+        let cookie = cookie?;
+        match self.store.load(cookie).await {
+            Ok(Some(data)) if !data.is_expired() => Some(data),
+            Ok(Some(_)) => {
+                if let Err(e) = self.store.delete(cookie).await {
+                    tracing::warn!(error = %e, "Error deleting an expired session");
+                }
+                None
+            }
+            Ok(None) => None,
+            Err(e) => {
+                tracing::error!(error = %e, "Error loading a session");
+                None
+            }
+        }
+    }
+
+    /// Save or delete a session once the Response is ready
+    /// ## Args
+    /// - self
+    /// - session: &Session
+    /// - cookie: Option<&str> (the session cookie of the Request, if its session was loaded)
+    /// - response: Response
+    /// ## Returns
+    /// - Response (500 Internal Server Error if the store fails)
+    async fn store_session(
+        &self,
+        session: &Session,
+        cookie: Option<&str>,
+        response: Response,
+    ) -> Response {
+        let (status, values) = {
+            let state = session.lock();
+            (state.status, state.values.clone())
+        };
+
+        let result = match status {
+            Status::Unchanged => return response,
+            Status::Destroyed => match cookie {
+                Some(cookie) => self.store.delete(cookie).await,
+                None => Ok(()),
+            }
+            .map(|_| response.remove_cookie(Cookie::new(&self.cookie_name, "").with_path("/"))),
+            // Do not create a session for nothing
+            Status::Changed if cookie.is_none() && values.is_empty() => return response,
+            Status::Changed | Status::Rotated => {
+                let data = SessionData {
+                    values,
+                    expires_at: unix_now().saturating_add(self.ttl.as_secs()),
+                };
+                let previous = match (status, cookie) {
+                    (Status::Rotated, Some(previous)) => {
+                        self.store.delete(previous).await.map(|_| None)
+                    }
+                    (_, previous) => Ok(previous),
+                };
+                match previous {
+                    Ok(previous) => self
+                        .store
+                        .save(previous, &data)
+                        .await
+                        .map(|value| response.set_cookie(self.cookie(&value))),
+                    Err(e) => Err(e),
+                }
+            }
+        };
+
+        result.unwrap_or_else(|e| {
+            tracing::error!(error = %e, "Error saving a session");
+            Response::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Some("Internal Server Error".to_string()),
+            )
+        })
+    }
+}
+
+/// Implement the Middleware trait for Sessions
+impl<S: SessionStore> Middleware for Sessions<S> {
+    async fn handle(&self, mut req: Request, next: Next) -> Response {
+        let cookie = req.cookie(&self.cookie_name);
+        let data = self.load(cookie.as_deref()).await;
+        // The cookie of a session that was not loaded is not reused
+        let cookie = cookie.filter(|_| data.is_some());
+
+        let session = Session::new(data.map(|data| data.values).unwrap_or_default());
+        req.extensions.insert(session.clone());
+
+        let response = next.run(req).await;
+        self.store_session(&session, cookie.as_deref(), response)
+            .await
+    }
+}
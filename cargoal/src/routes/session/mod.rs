@@ -0,0 +1,13 @@
+pub(crate) mod cookie_store;
+pub(crate) mod database_store;
+pub(crate) mod handle;
+pub(crate) mod memory_store;
+pub(crate) mod middleware;
+pub(crate) mod store;
+
+pub use cookie_store::CookieStore;
+pub use database_store::DatabaseStore;
+pub use handle::Session;
+pub use memory_store::MemoryStore;
+pub use middleware::Sessions;
+pub use store::{SessionData, SessionError, SessionStore};
@@ -0,0 +1,152 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Define the SessionError enum
+/// ## Variants
+/// - Serialization(serde_json::Error): a value cannot be converted to or from JSON
+/// - Database(sqlx::Error): the database of a DatabaseStore failed
+/// - Store(String): another store failed
+#[derive(Debug)]
+pub enum SessionError {
+    Serialization(serde_json::Error),
+    Database(sqlx::Error),
+    Store(String),
+}
+
+/// Implement the Display trait for SessionError
+impl fmt::Display for SessionError {
+    /// Format the SessionError
+    /// ## Args
+    /// - self
+    /// - f: &mut fmt::Formatter<'_>
+    /// ## Returns
+    /// - fmt::Result
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Serialization(err) => write!(f, "Invalid session value: {}", err),
+            SessionError::Database(err) => write!(f, "Session database error: {}", err),
+            SessionError::Store(reason) => write!(f, "Session store error: {}", reason),
+        }
+    }
+}
+
+/// Implement the Error trait for SessionError
+impl std::error::Error for SessionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SessionError::Serialization(err) => Some(err),
+            SessionError::Database(err) => Some(err),
+            SessionError::Store(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for SessionError {
+    fn from(err: serde_json::Error) -> Self {
+        SessionError::Serialization(err)
+    }
+}
+
+impl From<sqlx::Error> for SessionError {
+    fn from(err: sqlx::Error) -> Self {
+        SessionError::Database(err)
+    }
+}
+
+/// Define the SessionData struct
+/// What a store keeps about a session
+/// ## Fields
+/// - values: HashMap<String, serde_json::Value>
+/// - expires_at: u64 (Unix timestamp, in seconds)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionData {
+    pub values: HashMap<String, serde_json::Value>,
+    pub expires_at: u64,
+}
+
+/// Implement the SessionData struct
+impl SessionData {
+    /// Check if the session has expired
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - bool
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= unix_now()
+    }
+}
+
+/// Define the SessionStore trait
+/// Where the sessions are kept. The client holds a cookie whose value is given back
+/// to the store: an identifier for the server-side stores, the session itself for
+/// the CookieStore
+pub trait SessionStore: Send + Sync + 'static {
+    /// Load a session
+    /// ## Args
+    /// - self
+    /// - cookie: &str (the value of the session cookie)
+    /// ## Returns
+    /// - Result<Option<SessionData>, SessionError> (None for unknown sessions, expired
+    ///   sessions may be returned and are discarded by the caller)
+    fn load(
+        &self,
+        cookie: &str,
+    ) -> impl Future<Output = Result<Option<SessionData>, SessionError>> + Send;
+
+    /// Save a session
+    /// ## Args
+    /// - self
+    /// - cookie: Option<&str> (the value of the session cookie, None for a new session)
+    /// - data: &SessionData
+    /// ## Returns
+    /// - Result<String, SessionError> (the value of the session cookie to send)
+    fn save(
+        &self,
+        cookie: Option<&str>,
+        data: &SessionData,
+    ) -> impl Future<Output = Result<String, SessionError>> + Send;
+
+    /// Delete a session
+    /// ## Args
+    /// - self
+    /// - cookie: &str
+    /// ## Returns
+    /// - Result<(), SessionError>
+    fn delete(&self, cookie: &str) -> impl Future<Output = Result<(), SessionError>> + Send;
+
+    /// Delete the expired sessions
+    /// ## Args
+    /// - self
+    /// ## Returns
+    /// - Result<u64, SessionError> (the number of deleted sessions)
+    fn cleanup(&self) -> impl Future<Output = Result<u64, SessionError>> + Send;
+}
+
+/// Get the current Unix timestamp
+/// ## Returns
+/// - u64 (in seconds)
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Generate the identifier of a new session
+/// ## Returns
+/// - String (256 random bits, URL-safe base64)
+/// ## Panics
+/// - If the system random number generator fails
+pub(crate) fn generate_id() -> String {
+    let mut id = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut id)
+        .expect("the system random number generator failed");
+    URL_SAFE_NO_PAD.encode(id)
+}
@@ -0,0 +1,203 @@
+use cargoal::routes::http::{HttpMethod, StatusCode};
+use cargoal::routes::middlewares::LogFormat;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_core::span::Current;
+
+mod utils;
+use utils::access_log_client;
+
+#[tokio::test]
+async fn test_access_log_common_format() {
+    let (client, buffer) = access_log_client(LogFormat::Common).await;
+
+    let response = client.get("/users/42?tab=posts").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    client.get("/unknown").send().await;
+    client
+        .get("/users/1")
+        .remote_addr("192.0.2.10:40000".parse().unwrap())
+        .send()
+        .await;
+
+    let lines = buffer.lines();
+    assert_eq!(lines.len(), 3);
+    let clf = Regex::new(
+        r#"^- - - \[\d{2}/[A-Z][a-z]{2}/\d{4}:\d{2}:\d{2}:\d{2} \+0000\] "GET /users/42\?tab=posts HTTP/1\.1" 200 7$"#,
+    )
+    .unwrap();
+    assert!(clf.is_match(&lines[0]), "{}", lines[0]);
+    assert!(
+        lines[1].ends_with(r#""GET /unknown HTTP/1.1" 404 9"#),
+        "{}",
+        lines[1]
+    );
+    // The host is the IP address of the client
+    assert!(lines[2].starts_with("192.0.2.10 - - ["), "{}", lines[2]);
+}
+
+#[tokio::test]
+async fn test_access_log_combined_format() {
+    let (client, buffer) = access_log_client(LogFormat::Combined).await;
+
+    client
+        .get("/users/1")
+        .header("Referer", "https://example.com/")
+        .header("User-Agent", "Mozilla/5.0 \"quoted\"")
+        .send()
+        .await;
+    client.request(HttpMethod::HEAD, "/users/1").send().await;
+
+    let lines = buffer.lines();
+    assert!(
+        lines[0].ends_with(
+            r#""GET /users/1 HTTP/1.1" 200 6 "https://example.com/" "Mozilla/5.0 \"quoted\"""#
+        ),
+        "{}",
+        lines[0]
+    );
+    // No body is sent in answer to HEAD requests, and the headers are missing
+    assert!(
+        lines[1].ends_with(r#""HEAD /users/1 HTTP/1.1" 200 - "-" "-""#),
+        "{}",
+        lines[1]
+    );
+}
+
+#[tokio::test]
+async fn test_access_log_json_format() {
+    let (client, buffer) = access_log_client(LogFormat::Json).await;
+
+    client
+        .get("/users/7?lang=en")
+        .header("User-Agent", "curl/8.0")
+        .send()
+        .await;
+
+    let lines = buffer.lines();
+    assert_eq!(lines.len(), 1);
+    let entry: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(entry["method"], "GET");
+    assert_eq!(entry["path"], "/users/7");
+    assert_eq!(entry["query"], "lang=en");
+    assert_eq!(entry["version"], "HTTP/1.1");
+    assert_eq!(entry["status"], 200);
+    assert_eq!(entry["bytes"], 6);
+    assert_eq!(entry["referer"], serde_json::Value::Null);
+    assert_eq!(entry["client_ip"], serde_json::Value::Null);
+    assert_eq!(entry["user_agent"], "curl/8.0");
+    assert!(entry["duration_ms"].as_f64().unwrap() >= 0.0);
+    let time = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z$").unwrap();
+    assert!(time.is_match(entry["time"].as_str().unwrap()));
+}
+
+/// A subscriber keeping the fields of the request spans, and the spans entered to
+/// find the current one
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    next_id: Arc<AtomicU64>,
+    spans: Arc<Mutex<HashMap<u64, HashMap<String, String>>>>,
+    metadata: Arc<Mutex<HashMap<u64, &'static Metadata<'static>>>>,
+    entered: Arc<Mutex<Vec<u64>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.metadata.lock().unwrap().insert(id, attrs.metadata());
+        if attrs.metadata().name() == "request" {
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            self.spans.lock().unwrap().insert(id, fields);
+        }
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(fields) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        self.entered.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, span: &Id) {
+        let mut entered = self.entered.lock().unwrap();
+        if let Some(position) = entered.iter().rposition(|id| *id == span.into_u64()) {
+            entered.remove(position);
+        }
+    }
+
+    fn current_span(&self) -> Current {
+        let current = self.entered.lock().unwrap().last().copied();
+        match current.and_then(|id| Some((id, *self.metadata.lock().unwrap().get(&id)?))) {
+            Some((id, metadata)) => Current::new(Id::from_u64(id), metadata),
+            None => Current::none(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_request_spans() {
+    let recorder = SpanRecorder::default();
+    let _guard = tracing::dispatcher::set_default(&recorder.clone().into());
+    let (client, _) = access_log_client(LogFormat::Common).await;
+
+    client
+        .get("/users/42")
+        .header("Authorization", "Bearer secret")
+        .send()
+        .await;
+    client
+        .get("/users/42")
+        .header("X-Mock-Subdomain", "api")
+        .send()
+        .await;
+
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(spans.len(), 2);
+    let fields = &spans[&1];
+    assert_eq!(fields["method"], "GET");
+    assert_eq!(fields["path"], "/users/42");
+    assert_eq!(fields["route"], "/users/:id");
+    assert_eq!(fields["status"], "200");
+    assert!(fields.contains_key("latency_ms"));
+    assert!(!fields.contains_key("subdomain"));
+    // The headers are never recorded
+    assert!(!fields.values().any(|value| value.contains("secret")));
+
+    // No route of the subdomain matches
+    let fields = &spans[&2];
+    assert_eq!(fields["subdomain"], "api");
+    assert_eq!(fields["status"], "404");
+    assert!(!fields.contains_key("route"));
+}
@@ -0,0 +1,163 @@
+use cargoal::routes::http::{Cookie, Key, KeyError};
+use cargoal::routes::server::{ServerHandle, TestClient};
+
+mod utils;
+use utils::cookie_server;
+
+const SECRET: &[u8] = b"an insecure secret used by the cookie tests only";
+
+#[tokio::test]
+async fn test_request_cookies() {
+    let client = TestClient::new(cookie_server(None).await);
+
+    let response = client
+        .get("/preferences")
+        .header("Cookie", "theme=\"light\"; lang=en;theme=dark; invalid")
+        .send()
+        .await;
+    assert_eq!(response.text(), Some("2 cookies, theme=light"));
+
+    let response = client.get("/preferences").send().await;
+    assert_eq!(response.text(), Some("0 cookies, theme="));
+}
+
+#[tokio::test]
+async fn test_set_cookie() {
+    let client = TestClient::new(cookie_server(None).await);
+
+    let response = client.post("/preferences").send().await;
+    assert_eq!(response.cookies.len(), 2);
+    assert_eq!(
+        response.cookie("theme").unwrap().to_string(),
+        "theme=dark; Path=/; Domain=example.com; Expires=Sun, 06 Nov 1994 08:49:37 GMT; \
+         Max-Age=3600; Secure; HttpOnly; SameSite=Lax"
+    );
+    // A cookie set twice is only sent once
+    assert_eq!(response.cookie("lang").unwrap().to_string(), "lang=en");
+
+    let response = client.delete("/preferences").send().await;
+    assert_eq!(
+        response.cookie("theme").unwrap().to_string(),
+        "theme=; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Max-Age=0"
+    );
+
+    // The values cannot inject attributes nor headers
+    let cookie = Cookie::new("id", "1; Domain=evil.com\r\nX-Injected: 1");
+    assert_eq!(cookie.to_string(), "id=1 Domain=evil.comX-Injected: 1");
+}
+
+#[tokio::test]
+async fn test_set_cookie_headers() {
+    let app = cookie_server(None).await;
+    let server = app.clone();
+    tokio::spawn(async move {
+        server.run().await;
+    });
+    let port = app.ready().await.port();
+
+    let response = reqwest::Client::new()
+        .post(format!("http://localhost:{}/preferences", port))
+        .send()
+        .await
+        .unwrap();
+    let mut cookies: Vec<_> = response
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect();
+    cookies.sort();
+    assert_eq!(cookies.len(), 2);
+    assert_eq!(cookies[0], "lang=en");
+    assert!(cookies[1].starts_with("theme=dark; Path=/"));
+}
+
+#[tokio::test]
+async fn test_signed_cookies() {
+    let client = TestClient::new(cookie_server(Some(SECRET)).await);
+
+    let response = client.post("/signed").send().await;
+    let signed = response.cookie("user").unwrap().value().to_string();
+    assert!(signed.starts_with("ada."));
+
+    let response = client
+        .get("/signed")
+        .header("Cookie", &format!("user={}", signed))
+        .send()
+        .await;
+    assert_eq!(response.text(), Some("ada"));
+
+    // Modified values, signatures moved to another cookie and unsigned values are rejected
+    let tampered = signed.replacen("ada", "bob", 1);
+    for cookie in [
+        format!("user={}", tampered),
+        format!("admin={}", signed),
+        "user=ada".to_string(),
+    ] {
+        let response = client.get("/signed").header("Cookie", &cookie).send().await;
+        assert_eq!(response.status_code, 401, "{}", cookie);
+    }
+
+    // Another secret does not accept the signature
+    let other =
+        TestClient::new(cookie_server(Some(b"another secret of at least thirty two bytes")).await);
+    let response = other
+        .get("/signed")
+        .header("Cookie", &format!("user={}", signed))
+        .send()
+        .await;
+    assert_eq!(response.status_code, 401);
+}
+
+#[tokio::test]
+async fn test_private_cookies() {
+    let client = TestClient::new(cookie_server(Some(SECRET)).await);
+
+    let response = client.post("/private").send().await;
+    let encrypted = response.cookie("cart").unwrap().value().to_string();
+    assert!(!encrypted.contains("items"));
+
+    // The same value is encrypted differently every time
+    let response = client.post("/private").send().await;
+    assert_ne!(response.cookie("cart").unwrap().value(), encrypted);
+
+    let response = client
+        .get("/private")
+        .header("Cookie", &format!("cart={}", encrypted))
+        .send()
+        .await;
+    assert_eq!(response.text(), Some("3 items"));
+
+    let mut tampered = encrypted.clone().into_bytes();
+    let last = tampered.len() - 1;
+    tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+    let tampered = String::from_utf8(tampered).unwrap();
+    for cookie in [
+        format!("cart={}", tampered),
+        format!("wishlist={}", encrypted),
+        "cart=3 items".to_string(),
+    ] {
+        let response = client
+            .get("/private")
+            .header("Cookie", &cookie)
+            .send()
+            .await;
+        assert_eq!(response.status_code, 401, "{}", cookie);
+    }
+}
+
+#[tokio::test]
+async fn test_secret_key() {
+    assert_eq!(
+        Key::from_secret(b"too short").unwrap_err(),
+        KeyError::TooShort(9)
+    );
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    assert!(app.with_secret_key(b"too short").await.is_err());
+
+    // The jars need a secret key
+    let client = TestClient::new(cookie_server(None).await);
+    let response = client.get("/signed").send().await;
+    assert_eq!(response.status_code, 500);
+    assert_eq!(response.text(), Some("No secret key is configured"));
+}
@@ -0,0 +1,145 @@
+use cargoal::routes::http::{HttpMethod, StatusCode};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+mod utils;
+use utils::{start_test_server, test_client};
+
+#[tokio::test]
+async fn test_head_is_served_by_get_routes() {
+    let client = test_client().await;
+
+    let get = client.get("/about").send().await;
+    let length = get.text().unwrap().len();
+
+    let response = client.request(HttpMethod::HEAD, "/about").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("content-type").unwrap(), "text/html");
+
+    // The body is not sent but its length is: the next request on the connection is read correctly
+    let port = start_test_server().await;
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(
+            b"HEAD /about HTTP/1.1\r\n\r\nGET /unknown-route HTTP/1.1\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await.unwrap();
+    let raw = String::from_utf8_lossy(&raw);
+    let (head, rest) = raw.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(head
+        .lines()
+        .any(|line| line == format!("Content-Length: {}", length)));
+    assert!(rest.starts_with("HTTP/1.1 404 Not Found\r\n"));
+}
+
+#[tokio::test]
+async fn test_automatic_options() {
+    let client = test_client().await;
+
+    let response = client.request(HttpMethod::OPTIONS, "/about").send().await;
+    assert_eq!(response.status_code, StatusCode::NO_CONTENT);
+    assert_eq!(response.header("allow").unwrap(), "GET, HEAD, OPTIONS");
+
+    let response = client
+        .request(HttpMethod::OPTIONS, "/unknown-route")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_cors_preflight() {
+    let client = test_client().await;
+
+    let response = client
+        .request(HttpMethod::OPTIONS, "/about")
+        .header("Origin", "https://app.example.com")
+        .header("Access-Control-Request-Method", "PUT")
+        .header("Access-Control-Request-Headers", "content-type")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.header("access-control-allow-origin").unwrap(),
+        "https://app.example.com"
+    );
+    assert_eq!(
+        response.header("access-control-allow-methods").unwrap(),
+        "GET, POST, PUT"
+    );
+    assert_eq!(
+        response.header("access-control-allow-headers").unwrap(),
+        "Content-Type, X-Requested-With"
+    );
+    assert_eq!(
+        response.header("access-control-allow-credentials").unwrap(),
+        "true"
+    );
+    assert_eq!(response.header("access-control-max-age").unwrap(), "600");
+    assert_eq!(response.header("vary").unwrap(), "Origin");
+
+    let response = client
+        .request(HttpMethod::OPTIONS, "/about")
+        .header("Origin", "https://evil.example.com")
+        .header("Access-Control-Request-Method", "GET")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+    assert!(response.header("access-control-allow-origin").is_none());
+
+    let response = client
+        .request(HttpMethod::OPTIONS, "/about")
+        .header("Origin", "https://app.example.com")
+        .header("Access-Control-Request-Method", "DELETE")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+
+    let response = client
+        .request(HttpMethod::OPTIONS, "/about")
+        .header("Origin", "https://app.example.com")
+        .header("Access-Control-Request-Method", "GET")
+        .header("Access-Control-Request-Headers", "X-Secret")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+    assert_eq!(response.header("vary").unwrap(), "Origin");
+}
+
+#[tokio::test]
+async fn test_cors_actual_request() {
+    let client = test_client().await;
+
+    let response = client
+        .get("/products/1")
+        .header("Origin", "https://app.example.com")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(
+        response.header("access-control-allow-origin").unwrap(),
+        "https://app.example.com"
+    );
+    assert_eq!(
+        response.header("access-control-allow-credentials").unwrap(),
+        "true"
+    );
+    assert_eq!(response.header("vary").unwrap(), "Accept, Origin");
+
+    let response = client
+        .get("/about")
+        .header("Origin", "https://evil.example.com")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.header("access-control-allow-origin").is_none());
+    // The response to a disallowed origin is not cached for the allowed ones
+    assert_eq!(response.header("vary").unwrap(), "Origin");
+
+    let response = client.get("/about").send().await;
+    assert!(response.header("vary").is_none());
+}
@@ -0,0 +1,145 @@
+use cargoal::routes::http::{HttpMethod, StatusCode};
+use cargoal::routes::server::{ServerHandle, TestClient};
+use regex::Regex;
+
+mod utils;
+use utils::csrf_client;
+
+/// Load the form, returning the session cookie and the token of the form
+async fn load_form(client: &TestClient) -> (String, String) {
+    let response = client.get("/profile").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    let session = response.cookie("session").unwrap().value().to_string();
+
+    let token = Regex::new(r#"name="_csrf_token" value="([A-Za-z0-9_-]{43})""#)
+        .unwrap()
+        .captures(response.text().unwrap())
+        .expect("the form holds the token")[1]
+        .to_string();
+    (format!("session={}", session), token)
+}
+
+#[tokio::test]
+async fn test_csrf_token_in_templates_and_extensions() {
+    let client = csrf_client(true).await;
+    let (cookie, token) = load_form(&client).await;
+
+    // The token stays the same during the session
+    let response = client.get("/token").header("Cookie", &cookie).send().await;
+    assert_eq!(response.text(), Some(token.as_str()));
+    assert!(response.cookies.is_empty());
+
+    // Every session has its own token
+    let (_, other_token) = load_form(&client).await;
+    assert_ne!(token, other_token);
+}
+
+#[tokio::test]
+async fn test_csrf_validation() {
+    let client = csrf_client(true).await;
+    let (cookie, token) = load_form(&client).await;
+
+    // From the form field
+    let response = client
+        .post("/profile")
+        .header("Cookie", &cookie)
+        .form(&[("_csrf_token", token.as_str()), ("name", "Ada")])
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("name=Ada"));
+
+    // From the header
+    let response = client
+        .delete("/profile")
+        .header("Cookie", &cookie)
+        .header("X-CSRF-Token", &token)
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::NO_CONTENT);
+
+    // Missing and invalid tokens
+    let (other_cookie, other_token) = load_form(&client).await;
+    for (cookie, token) in [
+        (cookie.as_str(), None),
+        (cookie.as_str(), Some("invalid")),
+        (cookie.as_str(), Some(other_token.as_str())),
+        (other_cookie.as_str(), Some(token.as_str())),
+        ("session=unknown", Some(token.as_str())),
+    ] {
+        let mut request = client.post("/profile").header("Cookie", cookie);
+        if let Some(token) = token {
+            request = request.form(&[("_csrf_token", token), ("name", "Eve")]);
+        }
+        let response = request.send().await;
+        assert_eq!(response.status_code, StatusCode::FORBIDDEN, "{:?}", token);
+        assert_eq!(response.text(), Some("Invalid CSRF token"));
+    }
+
+    let response = client.delete("/profile").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_csrf_exempt_paths() {
+    let client = csrf_client(true).await;
+
+    let response = client.post("/api/items").send().await;
+    assert_eq!(response.status_code, StatusCode::CREATED);
+    // The exempt paths do not get a session
+    assert!(response.cookies.is_empty());
+
+    // Only the paths under the exempt path are exempt
+    let response = client.post("/apis").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_csrf_exempt_groups() {
+    let client = csrf_client(true).await;
+
+    // The group of the api subdomain is exempt
+    let response = client
+        .post("/profile")
+        .header("X-Mock-Subdomain", "api")
+        .body("name=Ada")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.text(), Some("Saved name=Ada"));
+    assert!(response.cookies.is_empty());
+
+    // The same path of the site is protected
+    let response = client.post("/profile").body("name=Eve").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+    assert_eq!(response.text(), Some("Invalid CSRF token"));
+    assert!(response.cookies.is_empty());
+}
+
+#[tokio::test]
+async fn test_csrf_requires_sessions() {
+    let client = csrf_client(false).await;
+
+    let response = client.get("/profile").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let response = client.post("/api/items").send().await;
+    assert_eq!(response.status_code, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_csrf_token_without_middleware() {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+    app.route("/profile", HttpMethod::GET)
+        .with_template("csrf_form.html")
+        .register()
+        .await;
+
+    let response = TestClient::new(app).get("/profile").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("csrf_token() requires the Csrf middleware"));
+}
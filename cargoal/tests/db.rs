@@ -13,11 +13,8 @@ async fn test_opening_connection() {
     );
     let db_result = Database::new(db_config).await;
     match db_result {
-        Ok(db) => {
-            db.close().await;
-            assert!(true);
-        }
-        Err(_) => assert!(false),
+        Ok(db) => db.close().await,
+        Err(err) => panic!("Failed to open the database connection: {}", err),
     }
 }
 
@@ -53,11 +50,8 @@ async fn test_retrieve_db_type() {
     );
     let db_result = Database::new(db_config).await;
     match db_result {
-        Ok(db) => {
-            db.close().await;
-            assert!(true);
-        }
-        Err(_) => assert!(false),
+        Ok(db) => db.close().await,
+        Err(err) => panic!("Failed to open the database connection: {}", err),
     }
 
     let given_type = DatabaseType::rust_type_to_sql_type("i32");
@@ -0,0 +1,148 @@
+use cargoal::routes::http::StatusCode;
+
+mod utils;
+use utils::test_client;
+
+#[tokio::test]
+async fn test_path_extractor() {
+    let client = test_client().await;
+
+    let response = client.get("/extract/users/7/posts/42").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "User 7 post 42");
+
+    let response = client.get("/extract/users/seven/posts/42").send().await;
+    assert_eq!(response.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.text().unwrap(),
+        "Invalid path parameters: user_id: cannot parse `seven` as u32"
+    );
+}
+
+#[tokio::test]
+async fn test_path_extractor_scalar_and_tuple() {
+    let client = test_client().await;
+
+    let response = client.get("/extract/items/12").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "Item 12");
+
+    // The tuple follows the order of the parameters in the path
+    let response = client
+        .get("/extract/teams/core%20team/members/3")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "Team core team member 3");
+
+    let response = client.get("/extract/teams/core/members/-3").send().await;
+    assert_eq!(response.status_code, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.text().unwrap(),
+        "Invalid path parameters: id: cannot parse `-3` as u32"
+    );
+}
+
+#[tokio::test]
+async fn test_query_extractor() {
+    let client = test_client().await;
+
+    let response = client.get("/extract/posts?page=2&per_page=5").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "Page 2 with 5 items");
+
+    let response = client.get("/extract/posts?page=3").send().await;
+    assert_eq!(response.text().unwrap(), "Page 3 with 10 items");
+
+    let response = client.get("/extract/posts?per_page=5").send().await;
+    assert_eq!(response.status_code, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_json_extractor() {
+    let client = test_client().await;
+
+    let response = client
+        .post("/extract/users")
+        .header("Content-Type", "application/json")
+        .body(r#"{"name":"Alice","age":30}"#)
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::CREATED);
+    assert_eq!(response.text().unwrap(), "Created Alice aged 30");
+
+    // Valid JSON that does not match the expected shape
+    let response = client
+        .post("/extract/users")
+        .header("Content-Type", "application/json")
+        .body(r#"{"name":"Alice","age":"thirty"}"#)
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Malformed JSON
+    let response = client
+        .post("/extract/users")
+        .header("Content-Type", "application/json")
+        .body(r#"{"name":"Alice","#)
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post("/extract/users")
+        .header("Content-Type", "text/plain")
+        .body(r#"{"name":"Alice","age":30}"#)
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_form_extractor() {
+    let client = test_client().await;
+
+    let response = client
+        .post("/extract/form")
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("name=Bob+Smith&age=41")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "Form from Bob Smith aged 41");
+
+    let response = client
+        .post("/extract/form")
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("name=Bob")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_header_extractor() {
+    let client = test_client().await;
+
+    let response = client
+        .get("/extract/header")
+        .header("User-Agent", "cargoal-test")
+        .header("Authorization", "Bearer secret")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(
+        response.text().unwrap(),
+        "Agent cargoal-test with auth Bearer"
+    );
+
+    let response = client
+        .get("/extract/header")
+        .header("User-Agent", "cargoal-test")
+        .send()
+        .await;
+    assert_eq!(
+        response.text().unwrap(),
+        "Agent cargoal-test with auth none"
+    );
+}
@@ -0,0 +1,43 @@
+use cargoal::routes::http::StatusCode;
+
+mod utils;
+use utils::test_client;
+
+#[tokio::test]
+async fn test_nested_group_inherits_settings() {
+    let client = test_client().await;
+
+    let response = client
+        .get("/admin/v2/config")
+        .header("X-Mock-Subdomain", "admin")
+        .header("X-Request-Id", "nested")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("x-request-id").unwrap(), "nested");
+    assert_eq!(response.text().unwrap(), "admin handled request nested");
+}
+
+#[tokio::test]
+async fn test_group_subdomain() {
+    let client = test_client().await;
+
+    let response = client.get("/admin/v2/config").send().await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_group_template_dir_and_layout() {
+    let client = test_client().await;
+
+    let response = client
+        .get("/admin/dashboard")
+        .header("X-Mock-Subdomain", "admin")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    let body = response.text().unwrap();
+    assert!(body.contains("<title>Dashboard</title>"));
+    assert!(body.contains("<nav>Admin</nav>"));
+    assert!(body.contains("<h1>Dashboard</h1>"));
+}
@@ -0,0 +1,166 @@
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::server::ServerHandle;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+mod utils;
+use utils::start_test_server;
+
+/// Read a single response (head and Content-Length framed body) from a connection
+async fn read_response(reader: &mut BufReader<TcpStream>) -> (String, String) {
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        if line == "\r\n" || line.is_empty() {
+            break;
+        }
+        head.push_str(&line);
+    }
+
+    let length = head
+        .lines()
+        .find_map(|line| line.strip_prefix("Content-Length: "))
+        .map(|value| value.trim().parse::<usize>().unwrap())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.unwrap();
+
+    (head, String::from_utf8(body).unwrap())
+}
+
+/// Start a server with a small connection limit and a short idle timeout
+async fn start_limited_server() -> u16 {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+    app.with_max_requests_per_connection(2).await;
+    app.with_keep_alive_timeout(Duration::from_millis(300))
+        .await;
+    app.route("/ping", HttpMethod::GET)
+        .with_handler(|_req: Request| Response::new(StatusCode::OK, Some("pong".to_string())))
+        .register()
+        .await;
+
+    let server = app.clone();
+    tokio::spawn(async move {
+        server.run().await;
+    });
+    app.ready().await.port()
+}
+
+#[tokio::test]
+async fn test_keep_alive_reuses_connection() {
+    let port = start_test_server().await;
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let mut reader = BufReader::new(stream);
+
+    for value in ["first", "second", "third"] {
+        let request = format!(
+            "GET /echo-header HTTP/1.1\r\nHost: localhost\r\nX-Custom-Header: {}\r\n\r\n",
+            value
+        );
+        reader
+            .get_mut()
+            .write_all(request.as_bytes())
+            .await
+            .unwrap();
+
+        let (head, body) = read_response(&mut reader).await;
+        assert!(head.starts_with("HTTP/1.1 200"));
+        assert!(head.contains("Connection: keep-alive"));
+        assert_eq!(body, format!("Header value: {}", value));
+    }
+}
+
+#[tokio::test]
+async fn test_pipelined_requests() {
+    let port = start_test_server().await;
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(
+            b"GET /echo-header HTTP/1.1\r\nX-Custom-Header: one\r\n\r\n\
+              GET /echo-header HTTP/1.1\r\nX-Custom-Header: two\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    timeout(Duration::from_secs(2), stream.read_to_string(&mut response))
+        .await
+        .expect("the server should close the connection")
+        .unwrap();
+
+    assert_eq!(response.matches("HTTP/1.1 200").count(), 2);
+    let first = response.find("Header value: one").unwrap();
+    let second = response.find("Header value: two").unwrap();
+    assert!(first < second);
+    assert!(response.ends_with("Header value: two"));
+}
+
+#[tokio::test]
+async fn test_http_1_0_closes_by_default() {
+    let port = start_test_server().await;
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET /echo-header HTTP/1.0\r\nX-Custom-Header: legacy\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    timeout(Duration::from_secs(2), stream.read_to_string(&mut response))
+        .await
+        .expect("the server should close the connection")
+        .unwrap();
+
+    assert!(response.contains("Connection: close"));
+    assert!(response.contains("Content-Length: 20"));
+    assert!(response.ends_with("Header value: legacy"));
+}
+
+#[tokio::test]
+async fn test_max_requests_per_connection() {
+    let port = start_limited_server().await;
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(
+            b"GET /ping HTTP/1.1\r\n\r\nGET /ping HTTP/1.1\r\n\r\nGET /ping HTTP/1.1\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    timeout(Duration::from_secs(2), stream.read_to_string(&mut response))
+        .await
+        .expect("the server should close the connection")
+        .unwrap();
+
+    assert_eq!(response.matches("HTTP/1.1 200").count(), 2);
+    assert_eq!(response.matches("Connection: keep-alive").count(), 1);
+    assert_eq!(response.matches("Connection: close").count(), 1);
+}
+
+#[tokio::test]
+async fn test_idle_connection_is_closed() {
+    let port = start_limited_server().await;
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let mut reader = BufReader::new(stream);
+    reader
+        .get_mut()
+        .write_all(b"GET /ping HTTP/1.1\r\n\r\n")
+        .await
+        .unwrap();
+
+    let (head, body) = read_response(&mut reader).await;
+    assert!(head.contains("Connection: keep-alive"));
+    assert_eq!(body, "pong");
+
+    // Nothing is sent anymore: the server must close the connection after its idle timeout
+    let mut rest = Vec::new();
+    let read = timeout(Duration::from_secs(2), reader.read_to_end(&mut rest))
+        .await
+        .expect("the server should close the idle connection")
+        .unwrap();
+    assert_eq!(read, 0);
+}
@@ -1,73 +1,78 @@
-use reqwest::Client;
-use reqwest::StatusCode;
+use cargoal::routes::http::StatusCode;
 
 mod utils;
-use utils::start_test_server;
+use utils::test_client;
 
 #[tokio::test]
 async fn test_middleware_log() {
-    start_test_server(8086).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8086/middleware-test/log")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert!(response
-        .text()
-        .await
-        .unwrap()
-        .contains("Middleware executed!"));
+    let client = test_client().await;
+    let response = client.get("/middleware-test/log").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Middleware executed!"));
 }
 
 #[tokio::test]
 async fn test_global_middleware_block() {
-    start_test_server(8092).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8092/middleware-block")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::FORBIDDEN);
-    assert!(response
-        .text()
-        .await
-        .unwrap()
-        .contains("Forbidden by middleware"));
+    let client = test_client().await;
+    let response = client.get("/middleware-block").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+    assert!(response.text().unwrap().contains("Forbidden by middleware"));
 }
 
 #[tokio::test]
 async fn test_route_middleware_block() {
-    start_test_server(8093).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8093/middleware-block-2")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::FORBIDDEN);
-    assert!(response
-        .text()
-        .await
-        .unwrap()
-        .contains("Forbidden by middleware"));
+    let client = test_client().await;
+    let response = client.get("/middleware-block-2").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+    assert!(response.text().unwrap().contains("Forbidden by middleware"));
 }
 
 #[tokio::test]
 async fn test_group_middleware_block() {
-    start_test_server(8094).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8094/middleware-block-3/block")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let client = test_client().await;
+    let response = client.get("/middleware-block-3/block").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+    assert!(response.text().unwrap().contains("Forbidden by middleware"));
+}
+
+#[tokio::test]
+async fn test_async_middleware() {
+    let client = test_client().await;
+    let response = client.get("/async-middleware/allowed").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
     assert!(response
         .text()
-        .await
         .unwrap()
-        .contains("Forbidden by middleware"));
+        .contains("Allowed: /async-middleware/allowed"));
+
+    let response = client.get("/async-middleware/blocked").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("Forbidden by async middleware"));
+}
+
+#[tokio::test]
+async fn test_middleware_ordering() {
+    let client = test_client().await;
+    let response = client.get("/ordering/trace").send().await;
+
+    assert_eq!(response.status_code, StatusCode::OK);
+    // Responses go back through the middlewares in the reverse order
+    assert_eq!(
+        response.header("x-trace").unwrap(),
+        "route-2,route-1,group,global"
+    );
+    assert_eq!(response.text().unwrap(), "global,group,route-1,route-2");
+}
+
+#[tokio::test]
+async fn test_middleware_post_processing() {
+    let client = test_client().await;
+    let response = client.get("/ordering/timed").send().await;
+
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+    assert!(response.header("x-response-time").unwrap().ends_with("us"));
+    assert_eq!(response.text().unwrap(), "Nothing to see here");
 }
@@ -0,0 +1,65 @@
+use cargoal::routes::http::StatusCode;
+
+mod utils;
+use utils::test_client;
+
+#[tokio::test]
+async fn test_json_response() {
+    let client = test_client().await;
+    let response = client.post("/json-created").send().await;
+
+    assert_eq!(response.status_code, StatusCode::CREATED);
+    assert_eq!(response.header("content-type").unwrap(), "application/json");
+    let body: serde_json::Value = serde_json::from_str(response.text().unwrap()).unwrap();
+    assert_eq!(body, serde_json::json!({ "id": 3, "status": "created" }));
+}
+
+#[tokio::test]
+async fn test_negotiation_serves_html_to_browsers() {
+    let client = test_client().await;
+    let response = client
+        .get("/products/1")
+        .header(
+            "Accept",
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        )
+        .send()
+        .await;
+
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("content-type").unwrap(), "text/html");
+    assert_eq!(response.header("vary").unwrap(), "Accept");
+    assert!(response.text().unwrap().contains("<h1>Keyboard</h1>"));
+}
+
+#[tokio::test]
+async fn test_negotiation_serves_json_to_api_clients() {
+    let client = test_client().await;
+
+    for accept in ["application/json", "*/*", "text/html;q=0.5, application/*"] {
+        let response = client
+            .get("/products/1")
+            .header("Accept", accept)
+            .send()
+            .await;
+
+        assert_eq!(response.status_code, StatusCode::OK);
+        assert_eq!(response.header("content-type").unwrap(), "application/json");
+        assert_eq!(
+            response.text().unwrap(),
+            r#"{"name":"Keyboard","price":49}"#
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_negotiation_not_acceptable() {
+    let client = test_client().await;
+    let response = client
+        .get("/products/1")
+        .header("Accept", "image/png, application/json;q=0")
+        .send()
+        .await;
+
+    assert_eq!(response.status_code, StatusCode::NOT_ACCEPTABLE);
+}
@@ -0,0 +1,62 @@
+use cargoal::routes::http::StatusCode;
+use cargoal::routes::server::TestClient;
+
+mod utils;
+use utils::test_client;
+
+/// Send a GET request and get the status and body of the response
+async fn get(client: &TestClient, path: &str) -> (StatusCode, String) {
+    let response = client.get(path).send().await;
+    (
+        response.status_code,
+        response.text().unwrap_or_default().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_typed_parameters() {
+    let client = test_client().await;
+
+    assert_eq!(
+        get(&client, "/articles/42").await,
+        (StatusCode::OK, "id=42".to_string())
+    );
+    assert_eq!(
+        get(&client, "/articles/hello-world").await,
+        (StatusCode::OK, "slug=hello-world".to_string())
+    );
+    assert_eq!(
+        get(&client, "/articles/Hello_42").await.0,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn test_optional_segments() {
+    let client = test_client().await;
+
+    assert_eq!(
+        get(&client, "/archive").await,
+        (StatusCode::OK, "Archive".to_string())
+    );
+    assert_eq!(
+        get(&client, "/archive/2024").await,
+        (StatusCode::OK, "Archive 2024".to_string())
+    );
+    assert_eq!(
+        get(&client, "/archive/2024/5").await,
+        (StatusCode::OK, "Archive 2024-05".to_string())
+    );
+    assert_eq!(get(&client, "/archive/may").await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_catch_all() {
+    let client = test_client().await;
+
+    assert_eq!(
+        get(&client, "/assets/css/themes/dark.css").await,
+        (StatusCode::OK, "rest=css/themes/dark.css".to_string())
+    );
+    assert_eq!(get(&client, "/assets").await.0, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,175 @@
+use cargoal::routes::http::ProxyError;
+use cargoal::routes::server::TestClient;
+use std::net::SocketAddr;
+
+mod utils;
+use utils::proxy_server;
+
+/// Get the client IP address, scheme and host seen by the handler
+async fn client_of(client: &TestClient, peer: &str, headers: &[(&str, &str)]) -> String {
+    let mut request = client
+        .get("/client")
+        .header("Host", "internal:8080")
+        .remote_addr(peer.parse::<SocketAddr>().unwrap());
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let text = request.send().await.text().unwrap().to_string();
+    // Without the address of the peer
+    text.split_once(' ').unwrap().1.to_string()
+}
+
+#[tokio::test]
+async fn test_remote_addr_of_connections() {
+    let app = proxy_server().await;
+    let server = app.clone();
+    tokio::spawn(async move {
+        server.run().await;
+    });
+    let port = app.ready().await.port();
+
+    // The forwarding headers of untrusted peers are ignored
+    let response = reqwest::Client::new()
+        .get(format!("http://localhost:{}/client", port))
+        .header("X-Forwarded-For", "203.0.113.7")
+        .header("X-Forwarded-Proto", "https")
+        .send()
+        .await
+        .unwrap();
+    let text = response.text().await.unwrap();
+    let parts: Vec<&str> = text.split(' ').collect();
+    let peer: SocketAddr = parts[0].parse().unwrap();
+    assert_eq!(peer.ip().to_string(), "127.0.0.1");
+    assert_ne!(peer.port(), port);
+    assert_eq!(
+        parts[1..],
+        ["127.0.0.1", "http", &format!("localhost:{}", port)]
+    );
+
+    // Requests that do not come from a connection have no address
+    let response = TestClient::new(app).get("/client").send().await;
+    assert_eq!(response.text(), Some("- - http -"));
+}
+
+#[tokio::test]
+async fn test_x_forwarded_headers() {
+    let mut app = proxy_server().await;
+    app.with_trusted_proxies(vec!["127.0.0.1", "10.0.0.0/8"])
+        .await
+        .unwrap();
+    let client = TestClient::new(app);
+
+    let forwarded = [
+        ("X-Forwarded-For", "203.0.113.7, 10.1.2.3"),
+        ("X-Forwarded-Proto", "https"),
+        ("X-Forwarded-Host", "example.com"),
+    ];
+    assert_eq!(
+        client_of(&client, "127.0.0.1:50000", &forwarded).await,
+        "203.0.113.7 https example.com"
+    );
+    // IPv4 addresses mapped to IPv6 are the same addresses
+    assert_eq!(
+        client_of(&client, "[::ffff:127.0.0.1]:50000", &forwarded).await,
+        "203.0.113.7 https example.com"
+    );
+
+    // The addresses added by the client are not believed
+    assert_eq!(
+        client_of(
+            &client,
+            "10.0.0.1:50000",
+            &[("X-Forwarded-For", "198.51.100.1, 203.0.113.7, 10.1.2.3")]
+        )
+        .await,
+        "203.0.113.7 http internal:8080"
+    );
+    // A Request going through trusted proxies only
+    assert_eq!(
+        client_of(
+            &client,
+            "10.0.0.1:50000",
+            &[("X-Forwarded-For", "10.0.0.5")]
+        )
+        .await,
+        "10.0.0.5 http internal:8080"
+    );
+    // Invalid values are ignored
+    assert_eq!(
+        client_of(
+            &client,
+            "10.0.0.1:50000",
+            &[
+                ("X-Forwarded-For", "not-an-address"),
+                ("X-Forwarded-Proto", "gopher"),
+                ("X-Forwarded-Host", "evil.com/path")
+            ]
+        )
+        .await,
+        "10.0.0.1 http internal:8080"
+    );
+
+    // The forwarding headers of untrusted peers are ignored
+    assert_eq!(
+        client_of(&client, "192.0.2.1:50000", &forwarded).await,
+        "192.0.2.1 http internal:8080"
+    );
+}
+
+#[tokio::test]
+async fn test_forwarded_header() {
+    let mut app = proxy_server().await;
+    app.with_trusted_proxies(vec!["10.0.0.0/8", "fd00::/8"])
+        .await
+        .unwrap();
+    let client = TestClient::new(app);
+
+    assert_eq!(
+        client_of(
+            &client,
+            "[fd00::1]:50000",
+            &[(
+                "Forwarded",
+                r#"for="[2001:db8::17]:4711";proto=https;host=shop.example.com, for=10.0.0.2;proto=http"#
+            )]
+        )
+        .await,
+        "2001:db8::17 https shop.example.com"
+    );
+
+    // Forwarded takes precedence over X-Forwarded-For
+    assert_eq!(
+        client_of(
+            &client,
+            "10.0.0.1:50000",
+            &[
+                ("Forwarded", "for=192.0.2.43:47011"),
+                ("X-Forwarded-For", "203.0.113.7")
+            ]
+        )
+        .await,
+        "192.0.2.43 http internal:8080"
+    );
+
+    // The client is unknown past an obfuscated address
+    assert_eq!(
+        client_of(
+            &client,
+            "10.0.0.1:50000",
+            &[("Forwarded", "for=192.0.2.43, for=_hidden, for=10.0.0.2")]
+        )
+        .await,
+        "10.0.0.2 http internal:8080"
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_trusted_proxies() {
+    let mut app = proxy_server().await;
+    for proxy in ["10.0.0.0/33", "localhost", "fd00::/129"] {
+        assert_eq!(
+            app.with_trusted_proxies(vec!["127.0.0.1", proxy]).await,
+            Err(ProxyError::InvalidAddress(proxy.to_string()))
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use cargoal::routes::http::StatusCode;
+use cargoal::routes::rate_limit::{MemoryStore, Quota, RateLimit, RateLimitStore};
+use cargoal::routes::server::{ServerHandle, TestClient};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+mod utils;
+use utils::rate_limit_server;
+
+const SECOND: u64 = 1_000_000;
+
+#[tokio::test]
+async fn test_route_rate_limit() {
+    let client = TestClient::new(rate_limit_server().await);
+
+    for remaining in ["1", "0"] {
+        let response = client
+            .get("/search")
+            .header("X-API-Key", "alice")
+            .send()
+            .await;
+        assert_eq!(response.status_code, StatusCode::OK);
+        assert_eq!(response.header("RateLimit-Limit"), Some("2"));
+        assert_eq!(response.header("RateLimit-Remaining"), Some(remaining));
+        assert_eq!(response.header("RateLimit-Policy"), Some("2;w=60"));
+    }
+
+    let response = client
+        .get("/search")
+        .header("X-API-Key", "alice")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.text(), Some("Too Many Requests"));
+    // One request every 30 seconds
+    let retry_after: u64 = response.header("Retry-After").unwrap().parse().unwrap();
+    assert!((29..=30).contains(&retry_after), "{}", retry_after);
+    assert_eq!(response.header("RateLimit-Remaining"), Some("0"));
+    assert_eq!(response.header("RateLimit-Reset"), Some("60"));
+
+    // Each key has its own limit, and the requests without a key are not limited
+    let response = client
+        .get("/search")
+        .header("X-API-Key", "bob")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    for _ in 0..3 {
+        let response = client.get("/search").send().await;
+        assert_eq!(response.status_code, StatusCode::OK);
+        assert_eq!(response.header("RateLimit-Limit"), None);
+    }
+}
+
+#[tokio::test]
+async fn test_group_rate_limit() {
+    let client = TestClient::new(rate_limit_server().await);
+
+    let response = client.get("/reports/daily?account=1").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+
+    // The limit is shared by the routes of the group
+    let response = client.get("/reports/weekly?account=1").send().await;
+    assert_eq!(response.status_code, StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.header("Retry-After").is_some());
+
+    let response = client.get("/reports/weekly?account=2").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+
+    // Other routes are not limited
+    let response = client.get("/search").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_global_rate_limit_by_ip() {
+    let app = rate_limit_server().await;
+    app.add_middleware(RateLimit::new(Quota::sliding_window(
+        3,
+        Duration::from_secs(3600),
+    )))
+    .await;
+    let client = TestClient::new(app);
+    let alice: SocketAddr = "192.0.2.1:50000".parse().unwrap();
+    let bob: SocketAddr = "192.0.2.2:50000".parse().unwrap();
+
+    let mut statuses = Vec::new();
+    for target in [
+        "/search",
+        "/reports/daily?account=3",
+        "/unknown-route",
+        "/search",
+    ] {
+        let response = client.get(target).remote_addr(alice).send().await;
+        statuses.push(response.status_code.as_u16());
+        if response.status_code == StatusCode::TOO_MANY_REQUESTS {
+            assert_eq!(response.header("ratelimit-limit"), Some("3"));
+            assert!(response.header("retry-after").is_some());
+        }
+    }
+    assert_eq!(statuses, [200, 200, 404, 429]);
+
+    // Each address has its own limit, whatever its port
+    let response = client.get("/search").remote_addr(bob).send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    let response = client
+        .get("/search")
+        .remote_addr("192.0.2.1:50001".parse().unwrap())
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::TOO_MANY_REQUESTS);
+
+    // The requests without an address are not limited
+    let response = client.get("/search").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_token_bucket_refill() {
+    let store = MemoryStore::new();
+    let quota = Quota::token_bucket(3, Duration::from_secs(3));
+    let now = 1_000 * SECOND;
+
+    for remaining in [2, 1, 0] {
+        let decision = store.hit("client", &quota, now).await.unwrap();
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, remaining);
+    }
+    let decision = store.hit("client", &quota, now).await.unwrap();
+    assert!(!decision.allowed);
+    assert_eq!(decision.retry_after, Some(Duration::from_secs(1)));
+    assert_eq!(decision.reset, Duration::from_secs(3));
+
+    // A token is back after a second
+    let decision = store.hit("client", &quota, now + SECOND).await.unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.remaining, 0);
+    assert!(
+        !store
+            .hit("client", &quota, now + SECOND)
+            .await
+            .unwrap()
+            .allowed
+    );
+
+    // The bucket is full again once the client stops
+    assert_eq!(store.cleanup(now + 3 * SECOND).await.unwrap(), 0);
+    assert_eq!(store.cleanup(now + 4 * SECOND).await.unwrap(), 1);
+    assert!(store.is_empty());
+    let decision = store.hit("client", &quota, now + 4 * SECOND).await.unwrap();
+    assert_eq!(decision.remaining, 2);
+}
+
+#[tokio::test]
+async fn test_sliding_window() {
+    let store = MemoryStore::new();
+    let quota = Quota::sliding_window(4, Duration::from_secs(10));
+    let start = 1_000 * SECOND;
+
+    // Four requests at the end of a window
+    for remaining in [3, 2, 1, 0] {
+        let decision = store
+            .hit("client", &quota, start + 8 * SECOND)
+            .await
+            .unwrap();
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, remaining);
+        assert_eq!(decision.reset, Duration::from_secs(2));
+    }
+    let decision = store
+        .hit("client", &quota, start + 9 * SECOND)
+        .await
+        .unwrap();
+    assert!(!decision.allowed);
+    // At the start of the next window, the previous one still counts fully, then
+    // weighs less than 4 right after
+    assert_eq!(
+        decision.retry_after,
+        Some(Duration::from_micros(SECOND + 1))
+    );
+
+    // At 20% of the next window, the previous window weighs 3 requests
+    let now = start + 12 * SECOND;
+    let decision = store.hit("client", &quota, now).await.unwrap();
+    assert!(decision.allowed);
+    assert_eq!(decision.remaining, 0);
+    let decision = store.hit("client", &quota, now).await.unwrap();
+    assert!(!decision.allowed);
+    // The previous window weighs 2 requests after 25% of this one
+    assert_eq!(
+        decision.retry_after,
+        Some(Duration::from_micros(SECOND / 2 + 1))
+    );
+
+    // Two windows later, the client starts over
+    let decision = store
+        .hit("client", &quota, start + 30 * SECOND)
+        .await
+        .unwrap();
+    assert_eq!(decision.remaining, 3);
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.cleanup(start + 50 * SECOND).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_cleanup_task() {
+    let limit =
+        RateLimit::new(Quota::token_bucket(1, Duration::from_millis(10))).by_header("X-API-Key");
+    let client = {
+        let mut app = ServerHandle::new("127.0.0.1:0");
+        app.with_template_dirs(vec!["tests/templates"]).await;
+        app.add_middleware(limit.clone()).await;
+        TestClient::new(app)
+    };
+
+    for key in ["alice", "bob"] {
+        let response = client.get("/").header("X-API-Key", key).send().await;
+        assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+    }
+    assert_eq!(limit.store().len(), 2);
+
+    let cleanup = limit.spawn_cleanup(Duration::from_millis(20));
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    cleanup.abort();
+    assert!(limit.store().is_empty());
+}
@@ -1,93 +1,77 @@
-use reqwest::Client;
-use reqwest::StatusCode;
+use cargoal::routes::http::StatusCode;
 
 mod utils;
-use utils::start_test_server;
+use utils::test_client;
 
 #[tokio::test]
 async fn test_valid_user_id() {
-    start_test_server(8080).await;
-    let client = Client::new();
+    let client = test_client().await;
     let response = client
-        .get("http://localhost:8080/v1/users/42")
+        .get("/v1/users/42")
         .header("X-Mock-Subdomain", "api")
         .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert!(response
-        .text()
-        .await
-        .unwrap()
-        .contains("Details about ID: 42"));
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Details about ID: 42"));
 }
 
 #[tokio::test]
 async fn test_invalid_user_id() {
-    start_test_server(8081).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8081/v1/users/abc")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let client = test_client().await;
+    let response = client.get("/v1/users/abc").send().await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
 }
 
 #[tokio::test]
 async fn test_valid_item_name() {
-    start_test_server(8082).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8082/v1/items/widget")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
+    let client = test_client().await;
+    let response = client.get("/v1/items/widget").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
     assert!(response
         .text()
-        .await
         .unwrap()
         .contains("Details about item name: widget"));
 }
 
 #[tokio::test]
 async fn test_invalid_item_name() {
-    start_test_server(8083).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8083/v1/items/widget123")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let client = test_client().await;
+    let response = client.get("/v1/items/widget123").send().await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
 }
 
 #[tokio::test]
 async fn test_valid_order_id() {
-    start_test_server(8084).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8084/v1/orders/order-123_456")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
+    let client = test_client().await;
+    let response = client.get("/v1/orders/order-123_456").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
     assert!(response
         .text()
-        .await
         .unwrap()
         .contains("Details about order ID: order-123_456"));
 }
 
 #[tokio::test]
 async fn test_invalid_order_id() {
-    start_test_server(8085).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8085/v1/orders/order%20id")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let client = test_client().await;
+    let response = client.get("/v1/orders/order%20id").send().await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_regex_matches_outside_of_the_pattern() {
+    let client = test_client().await;
+    // The regex of `/v1/orders/legacy/:order_id` also accepts `/v1/purchases/...`
+    let response = client.get("/v1/purchases/77").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("Details about order ID: 77"));
+
+    let response = client.get("/v1/purchases/abc").send().await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+
+    let response = client.post("/v1/purchases/77").send().await;
+    assert_eq!(response.status_code, StatusCode::METHOD_NOT_ALLOWED);
 }
@@ -0,0 +1,100 @@
+use cargoal::routes::http::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+mod utils;
+use utils::{start_test_server, test_client};
+
+/// Send a raw request and read the whole response until the server closes the connection
+async fn send_raw(port: u16, raw: &[u8]) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(raw).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_request_headers() {
+    let client = test_client().await;
+    let response = client
+        .get("/echo-header")
+        .header("X-Custom-Header", "cargoal")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Header value: cargoal"));
+}
+
+#[tokio::test]
+async fn test_binary_body() {
+    let client = test_client().await;
+    let body: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+    let expected_sum: u32 = body.iter().map(|&b| b as u32).sum();
+    let response = client.post("/binary").body(body).send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains(&format!("Received 4096 bytes, checksum {}", expected_sum)));
+}
+
+#[tokio::test]
+async fn test_chunked_body() {
+    let port = start_test_server().await;
+    let response = send_raw(
+        port,
+        b"POST /submit HTTP/1.1\r\nHost: localhost\r\nX-Mock-Subdomain: api\r\nConnection: close\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n7;ext=1\r\n, world\r\n0\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("Received body: Hello, world"));
+}
+
+#[tokio::test]
+async fn test_malformed_request_line() {
+    let port = start_test_server().await;
+    let response = send_raw(port, b"GARBAGE\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+
+    // The server must still be alive after a malformed request
+    let response = send_raw(
+        port,
+        b"GET /echo-header HTTP/1.1\r\nX-Custom-Header: ok\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.contains("Header value: ok"));
+}
+
+#[tokio::test]
+async fn test_malformed_headers() {
+    let port = start_test_server().await;
+    let response = send_raw(port, b"GET / HTTP/1.1\r\nNo colon here\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+
+    let response = send_raw(
+        port,
+        b"POST /binary HTTP/1.1\r\nContent-Length: abc\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+
+    let response = send_raw(
+        port,
+        b"POST /binary HTTP/1.1\r\nContent-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 400"));
+}
+
+#[tokio::test]
+async fn test_body_too_large() {
+    let port = start_test_server().await;
+    let response = send_raw(
+        port,
+        b"POST /binary HTTP/1.1\r\nContent-Length: 999999999999\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 413"));
+}
@@ -0,0 +1,98 @@
+use cargoal::routes::http::{InvalidStatusCode, StatusCode};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+mod utils;
+use utils::start_test_server;
+
+/// Send a raw request and read the whole response until the server closes the connection
+async fn send_raw(port: u16, raw: &[u8]) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream.write_all(raw).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_status_code_reason_phrases() {
+    assert_eq!(StatusCode::NOT_FOUND.as_u16(), 404);
+    assert_eq!(StatusCode::NOT_FOUND.canonical_reason(), Some("Not Found"));
+    assert_eq!(
+        StatusCode::try_from(500u16),
+        Ok(StatusCode::INTERNAL_SERVER_ERROR)
+    );
+    // Invalid codes are errors rather than 500 Internal Server Error
+    assert_eq!(StatusCode::try_from(1000u16), Err(InvalidStatusCode(1000)));
+    assert_eq!(StatusCode::try_from(42u16), Err(InvalidStatusCode(42)));
+    assert_eq!(StatusCode::from_u16(42), None);
+    assert_eq!(StatusCode::from_u16(599).unwrap().canonical_reason(), None);
+    assert_eq!(
+        StatusCode::METHOD_NOT_ALLOWED.to_string(),
+        "405 Method Not Allowed"
+    );
+    assert!(StatusCode::SEE_OTHER.is_redirection());
+    assert!(StatusCode::BAD_GATEWAY.is_server_error());
+}
+
+#[tokio::test]
+async fn test_status_line() {
+    let port = start_test_server().await;
+
+    let response = send_raw(
+        port,
+        b"GET /unknown-route HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+    let response = send_raw(port, b"DELETE /about HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+    let response = send_raw(port, b"GET /about HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+
+    let response = send_raw(port, b"BROKEN\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+}
+
+#[tokio::test]
+async fn test_custom_reason_phrase() {
+    let port = start_test_server().await;
+    let response = send_raw(port, b"GET /teapot HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+    assert!(response.starts_with("HTTP/1.1 418 Short And Stout\r\n"));
+    assert!(response.ends_with("No coffee here"));
+}
+
+#[tokio::test]
+async fn test_streaming_response() {
+    let port = start_test_server().await;
+
+    let response = send_raw(
+        port,
+        b"GET /export.csv HTTP/1.1\r\nConnection: close\r\n\r\n",
+    )
+    .await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Transfer-Encoding: chunked\r\n"));
+    assert!(!response.contains("Content-Length"));
+    assert!(response.ends_with("\r\n0\r\n\r\n"));
+
+    let response = reqwest::get(format!("http://localhost:{}/export.csv", port))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "0,item-0\n1,item-1\n2,item-2\n"
+    );
+}
+
+#[tokio::test]
+async fn test_streaming_response_http_1_0() {
+    let port = start_test_server().await;
+    let response = send_raw(port, b"GET /export.csv HTTP/1.0\r\n\r\n").await;
+    assert!(response.contains("Connection: close\r\n"));
+    assert!(!response.contains("Transfer-Encoding"));
+    assert!(response.ends_with("\r\n\r\n0,item-0\n1,item-1\n2,item-2\n"));
+}
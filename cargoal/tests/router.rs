@@ -0,0 +1,209 @@
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::routing::{RouteError, Router};
+
+fn ok_handler(_req: Request) -> Response {
+    Response::new(StatusCode::OK, "OK")
+}
+
+/// Get the path pattern of the route matching a request
+fn matched(router: &Router, method: HttpMethod, path: &str) -> Option<String> {
+    router
+        .find_route(path, &method, None)
+        .map(|m| m.route.path().to_string())
+}
+
+#[test]
+fn test_static_param_wildcard_priority() {
+    let mut router = Router::new();
+    router
+        .insert(None, "/files/*path", HttpMethod::GET, ok_handler)
+        .unwrap();
+    router
+        .insert(None, "/files/:name", HttpMethod::GET, ok_handler)
+        .unwrap();
+    router
+        .insert(None, "/files/readme", HttpMethod::GET, ok_handler)
+        .unwrap();
+
+    assert_eq!(
+        matched(&router, HttpMethod::GET, "/files/readme").as_deref(),
+        Some("/files/readme")
+    );
+    assert_eq!(
+        matched(&router, HttpMethod::GET, "/files/report.pdf").as_deref(),
+        Some("/files/:name")
+    );
+    assert_eq!(
+        matched(&router, HttpMethod::GET, "/files/2024/report.pdf").as_deref(),
+        Some("/files/*path")
+    );
+    assert_eq!(matched(&router, HttpMethod::GET, "/files"), None);
+
+    let route_match = router
+        .find_route("/files/2024/report.pdf", &HttpMethod::GET, None)
+        .unwrap();
+    assert_eq!(route_match.params.get("path").unwrap(), "2024/report.pdf");
+}
+
+#[test]
+fn test_backtracking_to_lower_priority() {
+    let mut router = Router::new();
+    router
+        .insert(None, "/users/new", HttpMethod::GET, ok_handler)
+        .unwrap();
+    router
+        .insert(None, "/users/:id/posts", HttpMethod::GET, ok_handler)
+        .unwrap();
+    router
+        .insert(None, "/users/:id", HttpMethod::DELETE, ok_handler)
+        .unwrap();
+
+    // The static segment matches but has no `posts` child
+    let route_match = router
+        .find_route("/users/new/posts", &HttpMethod::GET, None)
+        .unwrap();
+    assert_eq!(route_match.route.path(), "/users/:id/posts");
+    assert_eq!(route_match.params.get("id").unwrap(), "new");
+
+    // The static route does not handle DELETE
+    assert_eq!(
+        matched(&router, HttpMethod::DELETE, "/users/new").as_deref(),
+        Some("/users/:id")
+    );
+    assert_eq!(matched(&router, HttpMethod::GET, "/users/"), None);
+}
+
+#[test]
+fn test_conflicts_are_detected() {
+    let mut router = Router::new();
+    router
+        .insert(None, "/users/:id", HttpMethod::GET, ok_handler)
+        .unwrap();
+
+    assert!(matches!(
+        router.insert(None, "/users/:id", HttpMethod::GET, ok_handler),
+        Err(RouteError::Conflict { .. })
+    ));
+    assert!(matches!(
+        router.insert(None, "/users/:user_id/posts", HttpMethod::GET, ok_handler),
+        Err(RouteError::ParamConflict { .. })
+    ));
+    assert!(matches!(
+        router.insert(None, "/files/*path/edit", HttpMethod::GET, ok_handler),
+        Err(RouteError::InvalidPath { .. })
+    ));
+    assert!(matches!(
+        router.insert(None, "users", HttpMethod::GET, ok_handler),
+        Err(RouteError::InvalidPath { .. })
+    ));
+
+    // Another method, or another subdomain, is not a conflict
+    router
+        .insert(None, "/users/:id", HttpMethod::PUT, ok_handler)
+        .unwrap();
+    router
+        .insert(Some("api"), "/users/:user_id", HttpMethod::GET, ok_handler)
+        .unwrap();
+}
+
+#[test]
+fn test_subdomain_trees() {
+    let mut router = Router::new();
+    router
+        .insert(Some("api"), "/status", HttpMethod::GET, ok_handler)
+        .unwrap();
+
+    assert!(router
+        .find_route("/status", &HttpMethod::GET, Some("api"))
+        .is_some());
+    assert!(router
+        .find_route("/status", &HttpMethod::GET, None)
+        .is_none());
+    assert!(router
+        .find_route("/status", &HttpMethod::GET, Some("www"))
+        .is_none());
+}
+
+#[test]
+fn test_typed_parameters() {
+    let mut router = Router::new();
+    router
+        .insert(None, "/posts/:id<int>", HttpMethod::GET, ok_handler)
+        .unwrap();
+    router
+        .insert(None, "/posts/:slug<[a-z-]+>", HttpMethod::GET, ok_handler)
+        .unwrap();
+
+    let route_match = router
+        .find_route("/posts/42", &HttpMethod::GET, None)
+        .unwrap();
+    assert_eq!(route_match.route.path(), "/posts/:id<int>");
+    assert_eq!(route_match.params.get("id").unwrap(), "42");
+
+    let route_match = router
+        .find_route("/posts/hello-world", &HttpMethod::GET, None)
+        .unwrap();
+    assert_eq!(route_match.route.path(), "/posts/:slug<[a-z-]+>");
+    assert_eq!(route_match.params.get("slug").unwrap(), "hello-world");
+
+    assert_eq!(matched(&router, HttpMethod::GET, "/posts/Hello_42"), None);
+
+    // An unconstrained parameter is only tried after the constrained ones
+    router
+        .insert(None, "/posts/:key", HttpMethod::GET, ok_handler)
+        .unwrap();
+    assert_eq!(
+        matched(&router, HttpMethod::GET, "/posts/7").as_deref(),
+        Some("/posts/:id<int>")
+    );
+    assert_eq!(
+        matched(&router, HttpMethod::GET, "/posts/Hello_42").as_deref(),
+        Some("/posts/:key")
+    );
+
+    assert!(matches!(
+        router.insert(None, "/posts/:post_id<int>", HttpMethod::GET, ok_handler),
+        Err(RouteError::ParamConflict { .. })
+    ));
+    assert!(matches!(
+        router.insert(None, "/broken/:id<[a-z>", HttpMethod::GET, ok_handler),
+        Err(RouteError::InvalidRegex { .. })
+    ));
+}
+
+#[test]
+fn test_optional_segments() {
+    let mut router = Router::new();
+    router
+        .insert(
+            None,
+            "/archive/:year<int>?/:month<int>?",
+            HttpMethod::GET,
+            ok_handler,
+        )
+        .unwrap();
+
+    for path in ["/archive", "/archive/2024", "/archive/2024/05"] {
+        assert_eq!(
+            matched(&router, HttpMethod::GET, path).as_deref(),
+            Some("/archive/:year<int>?/:month<int>?")
+        );
+    }
+    assert_eq!(matched(&router, HttpMethod::GET, "/archive/latest"), None);
+
+    let route_match = router
+        .find_route("/archive/2024", &HttpMethod::GET, None)
+        .unwrap();
+    assert_eq!(route_match.params.get("year").unwrap(), "2024");
+    assert!(!route_match.params.contains_key("month"));
+
+    // Every form of the path is checked for conflicts
+    assert!(matches!(
+        router.insert(None, "/archive", HttpMethod::GET, ok_handler),
+        Err(RouteError::Conflict { .. })
+    ));
+    assert!(matches!(
+        router.insert(None, "/docs/:page?/edit", HttpMethod::GET, ok_handler),
+        Err(RouteError::InvalidPath { .. })
+    ));
+}
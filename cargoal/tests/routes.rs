@@ -1,210 +1,185 @@
-use reqwest::Client;
-use reqwest::StatusCode;
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::server::{ServerHandle, TestClient};
+use std::time::Duration;
 
 mod utils;
-use utils::start_test_server;
+use utils::test_client;
 
 #[tokio::test]
 async fn test_group_routes() {
-    start_test_server(8084).await;
-    let client = Client::new();
+    let client = test_client().await;
     let response = client
-        .get("http://localhost:8084/v1/users")
+        .get("/v1/users")
         .header("X-Mock-Subdomain", "api")
         .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
     assert!(response
         .text()
-        .await
         .unwrap()
         .contains("[{\"id\":1,\"name\":\"Alice\"},{\"id\":2,\"name\":\"Bob\"}]"));
 
     let response = client
-        .get("http://localhost:8084/v1/users/42")
+        .get("/v1/users/42")
         .header("X-Mock-Subdomain", "api")
         .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert!(response
-        .text()
-        .await
-        .unwrap()
-        .contains("Details about ID: 42"));
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Details about ID: 42"));
 }
 
 #[tokio::test]
 async fn test_options_method() {
-    start_test_server(8085).await;
-    let client = Client::new();
+    let client = test_client().await;
     let response = client
-        .request(
-            reqwest::Method::OPTIONS,
-            "http://localhost:8085/options-test",
-        )
+        .request(HttpMethod::OPTIONS, "/options-test")
         .header("X-Mock-Subdomain", "api")
         .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert_eq!(
-        response.headers().get("Allow").unwrap().to_str().unwrap(),
-        "GET, POST"
-    );
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("Allow").unwrap(), "GET, POST");
 }
 
 #[tokio::test]
 async fn test_unknown_route() {
-    start_test_server(8087).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8087/unknown-route")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::NOT_FOUND);
-    assert!(response.text().await.unwrap().contains("Not Found"));
+    let client = test_client().await;
+    let response = client.get("/unknown-route").send().await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+    assert!(response.text().unwrap().contains("Not Found"));
 }
 
 #[tokio::test]
 async fn test_redirect_slash_to_non_slash() {
-    start_test_server(8089).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8089/about/")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert_eq!(response.url().as_str(), "http://localhost:8089/about");
+    let client = test_client().await;
+    let response = client.get("/about/").send().await;
+    assert_eq!(response.status_code, StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(response.header("Location"), Some("/about"));
+
+    let response = client.get("/about").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
 }
 
 #[tokio::test]
 async fn test_unknown_subdomain() {
-    start_test_server(8090).await;
-    let client = Client::new();
+    let client = test_client().await;
     let response = client
-        .get("http://localhost:8090/")
+        .get("/")
         .header("X-Mock-Subdomain", "unknown")
         .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::NOT_FOUND);
-    assert!(response.text().await.unwrap().contains("Not Found"));
+        .await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+    assert!(response.text().unwrap().contains("Not Found"));
 }
 
 #[tokio::test]
 async fn test_bad_request_missing_param() {
-    start_test_server(8091).await;
-    let client = Client::new();
+    let client = test_client().await;
 
     // Test with missing parameter
     let response = client
-        .get("http://localhost:8091/about/")
+        .get("/about/")
         .header("X-Mock-Subdomain", "api")
         .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::NOT_FOUND);
-    assert!(response.text().await.unwrap().contains("Not Found"));
+        .await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+    assert!(response.text().unwrap().contains("Not Found"));
 }
 
 #[tokio::test]
 async fn test_method_not_allowed() {
-    start_test_server(8093).await;
-    let client = Client::new();
-    let response = client
-        .delete("http://localhost:8093/about")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
-    assert_eq!(
-        response.headers().get("Allow").unwrap().to_str().unwrap(),
-        "GET"
-    );
+    let client = test_client().await;
+    let response = client.delete("/about").send().await;
+    assert_eq!(response.status_code, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.header("Allow").unwrap(), "GET, HEAD, OPTIONS");
 }
 
 #[tokio::test]
 async fn test_custom_headers() {
-    start_test_server(8094).await;
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8094/about")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert_eq!(
-        response
-            .headers()
-            .get("Content-Type")
-            .unwrap()
-            .to_str()
-            .unwrap(),
-        "text/html"
-    );
+    let client = test_client().await;
+    let response = client.get("/about").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("Content-Type").unwrap(), "text/html");
 }
 
 #[tokio::test]
 async fn test_high_load() {
-    start_test_server(8095).await;
-    let client = Client::new();
+    let client = test_client().await;
     for _ in 0..100 {
         let response = client
-            .get("http://localhost:8095/v1/users")
+            .get("/v1/users")
             .header("X-Mock-Subdomain", "api")
             .send()
-            .await
-            .unwrap();
-        assert_eq!(response.status(), StatusCode::OK);
+            .await;
+        assert_eq!(response.status_code, StatusCode::OK);
     }
 }
 
 #[tokio::test]
 async fn test_query_parameters() {
-    start_test_server(8096).await;
-    let client = reqwest::Client::new();
+    let client = test_client().await;
 
     // Test with a single parameter
-    let response = client
-        .get("http://localhost:8096/query-test?name=Alice")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert!(response.text().await.unwrap().contains("Hello, Alice!"));
+    let response = client.get("/query-test?name=Alice").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Hello, Alice!"));
 
     // Test with missing parameter
-    let response = client
-        .get("http://localhost:8096/query-test")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = client.get("/query-test").send().await;
+    assert_eq!(response.status_code, StatusCode::BAD_REQUEST);
     assert!(response
         .text()
-        .await
         .unwrap()
         .contains("Missing 'name' parameter"));
 
     // Test with multiple parameters
-    let response = client
-        .get("http://localhost:8096/query-test?name=Bob&age=25")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert!(response.text().await.unwrap().contains("Hello, Bob!"));
+    let response = client.get("/query-test?name=Bob&age=25").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Hello, Bob!"));
 
     // Test with empty parameter
-    let response = client
-        .get("http://localhost:8096/query-test?name=")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-    assert!(response.text().await.unwrap().contains("Hello, !"));
+    let response = client.get("/query-test?name=").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Hello, !"));
+}
+
+#[tokio::test]
+async fn test_async_handler() {
+    let client = test_client().await;
+    let response = client.get("/async").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("Async handler reached: /async"));
+}
+
+#[tokio::test]
+async fn test_handlers_run_without_locking_the_router() {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+    let server = app.clone();
+    app.route("/register", HttpMethod::POST)
+        .with_handler(move |_req: Request| {
+            let server = server.clone();
+            async move {
+                server
+                    .route("/registered", HttpMethod::GET)
+                    .with_handler(|_req: Request| Response::new(StatusCode::OK, "Registered"))
+                    .register()
+                    .await;
+                Response::new(StatusCode::CREATED, "Created")
+            }
+        })
+        .register()
+        .await;
+    let client = TestClient::new(app);
+
+    // A handler registering a route does not wait for itself
+    let response = tokio::time::timeout(Duration::from_secs(5), client.post("/register").send())
+        .await
+        .expect("the handler registers the route");
+    assert_eq!(response.status_code, StatusCode::CREATED);
+
+    let response = client.get("/registered").send().await;
+    assert_eq!(response.text(), Some("Registered"));
 }
@@ -0,0 +1,160 @@
+use cargoal::routes::http::{HttpMethod, StatusCode};
+use cargoal::routes::middlewares::SecurityHeaders;
+use cargoal::routes::server::{ServerHandle, TestClient};
+use regex::Regex;
+use std::time::Duration;
+
+mod utils;
+use utils::security_client;
+
+#[tokio::test]
+async fn test_default_security_headers() {
+    let client = security_client(SecurityHeaders::new()).await;
+
+    // Routes, static files and errors get the same headers
+    for (target, status) in [
+        ("/nonce", StatusCode::INTERNAL_SERVER_ERROR),
+        ("/static/styles.css", StatusCode::OK),
+        ("/unknown-route", StatusCode::NOT_FOUND),
+    ] {
+        let response = client.get(target).send().await;
+        assert_eq!(response.status_code, status, "{}", target);
+        assert_eq!(
+            response.header("Strict-Transport-Security"),
+            Some("max-age=31536000; includeSubDomains")
+        );
+        assert_eq!(response.header("X-Content-Type-Options"), Some("nosniff"));
+        assert_eq!(response.header("X-Frame-Options"), Some("DENY"));
+        assert_eq!(
+            response.header("Referrer-Policy"),
+            Some("strict-origin-when-cross-origin")
+        );
+        assert_eq!(
+            response.header("Cross-Origin-Opener-Policy"),
+            Some("same-origin")
+        );
+        assert_eq!(response.header("Content-Security-Policy"), None);
+        assert_eq!(response.header("Cross-Origin-Embedder-Policy"), None);
+    }
+}
+
+#[tokio::test]
+async fn test_configured_security_headers() {
+    let headers = SecurityHeaders::new()
+        .with_hsts(Duration::from_secs(600), false, true)
+        .with_frame_options("SAMEORIGIN")
+        .with_referrer_policy("no-referrer")
+        .with_permissions_policy("camera=(), geolocation=(self)")
+        .with_cross_origin_embedder_policy("require-corp")
+        .with_content_security_policy("default-src 'self'")
+        .with_header("X-Permitted-Cross-Domain-Policies", "none")
+        .without_header("cross-origin-opener-policy");
+    let client = security_client(headers).await;
+
+    let response = client.get("/static/styles.css").send().await;
+    assert_eq!(
+        response.header("Strict-Transport-Security"),
+        Some("max-age=600; preload")
+    );
+    assert_eq!(response.header("X-Frame-Options"), Some("SAMEORIGIN"));
+    assert_eq!(response.header("Referrer-Policy"), Some("no-referrer"));
+    assert_eq!(
+        response.header("Permissions-Policy"),
+        Some("camera=(), geolocation=(self)")
+    );
+    assert_eq!(
+        response.header("Cross-Origin-Embedder-Policy"),
+        Some("require-corp")
+    );
+    assert_eq!(
+        response.header("Content-Security-Policy"),
+        Some("default-src 'self'")
+    );
+    assert_eq!(
+        response.header("X-Permitted-Cross-Domain-Policies"),
+        Some("none")
+    );
+    assert_eq!(response.header("Cross-Origin-Opener-Policy"), None);
+
+    // Without a `{nonce}` in the policy, no nonce is generated
+    let response = client.get("/nonce").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+    let response = client.get("/dashboard").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn test_route_headers_override_the_policy() {
+    let client = security_client(SecurityHeaders::new()).await;
+
+    // The route sets its own X-Frame-Options, the other headers are added
+    let response = client.get("/embed").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("X-Frame-Options"), Some("ALLOWALL"));
+    assert_eq!(response.header("X-Content-Type-Options"), Some("nosniff"));
+    assert_eq!(
+        response.header("Referrer-Policy"),
+        Some("strict-origin-when-cross-origin")
+    );
+}
+
+#[tokio::test]
+async fn test_csp_nonces() {
+    let headers = SecurityHeaders::new().with_content_security_policy(
+        "default-src 'self'; script-src 'nonce-{nonce}'; style-src 'nonce-{nonce}'",
+    );
+    let client = security_client(headers).await;
+
+    let response = client.get("/dashboard").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    let policy = response.header("Content-Security-Policy").unwrap();
+    let nonce = Regex::new(r"script-src 'nonce-([A-Za-z0-9_-]{22})'")
+        .unwrap()
+        .captures(policy)
+        .expect("the policy holds the nonce")[1]
+        .to_string();
+    assert_eq!(
+        policy,
+        format!(
+            "default-src 'self'; script-src 'nonce-{0}'; style-src 'nonce-{0}'",
+            nonce
+        )
+    );
+
+    // The templates get the nonce of the header
+    let body = response.text().unwrap();
+    assert!(body.contains(&format!(r#"<style nonce="{}">"#, nonce)));
+    assert!(body.contains(&format!(r#"<script nonce="{}">"#, nonce)));
+
+    // Every request gets its own nonce
+    let response = client.get("/nonce").send().await;
+    let other_nonce = response.text().unwrap();
+    assert_ne!(other_nonce, nonce);
+    assert_eq!(
+        response.header("Content-Security-Policy"),
+        Some(
+            format!(
+                "default-src 'self'; script-src 'nonce-{0}'; style-src 'nonce-{0}'",
+                other_nonce
+            )
+            .as_str()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_csp_nonce_without_middleware() {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+    app.route("/dashboard", HttpMethod::GET)
+        .with_template("csp_page.html")
+        .register()
+        .await;
+
+    let response = TestClient::new(app).get("/dashboard").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("csp_nonce() requires a SecurityHeaders policy containing {nonce}"));
+}
@@ -0,0 +1,168 @@
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::server::ServerHandle;
+use reqwest::Client;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+/// Name of the Server, shared by the startup hook
+struct AppName(&'static str);
+
+async fn slow_server(events: Arc<Mutex<Vec<String>>>) -> ServerHandle {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+
+    let startup_events = Arc::clone(&events);
+    app.on_startup(|mut app| async move {
+        app.with_state(AppName("lifecycle")).await;
+        startup_events.lock().unwrap().push("startup".to_string());
+    })
+    .await;
+
+    app.on_shutdown(move |app| async move {
+        let name = app.state::<AppName>().await.unwrap();
+        events.lock().unwrap().push(format!("shutdown {}", name.0));
+    })
+    .await;
+
+    app.route("/slow", HttpMethod::GET)
+        .with_handler(|req: Request| async move {
+            let millis = req.params.get("ms").and_then(|ms| ms.parse().ok());
+            sleep(Duration::from_millis(millis.unwrap_or(500))).await;
+            Response::new(StatusCode::OK, "done")
+        })
+        .register()
+        .await;
+
+    app
+}
+
+#[tokio::test]
+async fn test_run_until_drains_requests_and_runs_hooks() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let app = slow_server(Arc::clone(&events)).await;
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server_app = app.clone();
+    let server = tokio::spawn(async move {
+        server_app
+            .run_until(async {
+                let _ = stopped.await;
+            })
+            .await
+    });
+    let port = app.ready().await.port();
+    assert_ne!(port, 0);
+    assert_eq!(app.local_addr().unwrap().port(), port);
+    assert_eq!(*events.lock().unwrap(), vec!["startup"]);
+
+    let request = tokio::spawn(
+        Client::new()
+            .get(format!("http://localhost:{}/slow", port))
+            .send(),
+    );
+    sleep(Duration::from_millis(100)).await;
+    stop.send(()).unwrap();
+
+    // The request in progress is answered, then the hooks run
+    let response = request.await.unwrap().unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["connection"], "close");
+    assert_eq!(response.text().await.unwrap(), "done");
+    server.await.unwrap();
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["startup", "shutdown lifecycle"]
+    );
+
+    // New connections are refused
+    assert_eq!(app.local_addr(), None);
+    assert!(TcpStream::connect(("127.0.0.1", port)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_shutdown_timeout_closes_slow_connections() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut app = slow_server(Arc::clone(&events)).await;
+    app.with_shutdown_timeout(Duration::from_millis(200)).await;
+    let shutdown = app.shutdown_handle();
+    let server_app = app.clone();
+    let server = tokio::spawn(async move { server_app.run().await });
+    let port = app.ready().await.port();
+
+    let request = tokio::spawn(
+        Client::new()
+            .get(format!("http://localhost:{}/slow?ms=5000", port))
+            .send(),
+    );
+    sleep(Duration::from_millis(100)).await;
+
+    let start = Instant::now();
+    shutdown.shutdown();
+    server.await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(request.await.unwrap().is_err());
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["startup", "shutdown lifecycle"]
+    );
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_idle_connections() {
+    let app = slow_server(Arc::new(Mutex::new(Vec::new()))).await;
+    let shutdown = app.shutdown_handle();
+    let server_app = app.clone();
+    let server = tokio::spawn(async move { server_app.run().await });
+    let port = app.ready().await.port();
+
+    // A keep-alive connection waiting for its next request
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    stream
+        .write_all(b"GET /slow?ms=0 HTTP/1.1\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buffer = [0; 1024];
+    let read = stream.read(&mut buffer).await.unwrap();
+    assert!(String::from_utf8_lossy(&buffer[..read]).contains("keep-alive"));
+
+    let start = Instant::now();
+    shutdown.shutdown();
+    assert!(shutdown.is_shutting_down());
+    server.await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(stream.read(&mut buffer).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_run_again_after_shutdown() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let app = slow_server(Arc::clone(&events)).await;
+    let shutdown = app.shutdown_handle();
+
+    for _ in 0..2 {
+        let server_app = app.clone();
+        let server = tokio::spawn(async move { server_app.run().await });
+        let port = app.ready().await.port();
+        assert!(!shutdown.is_shutting_down());
+
+        let response = Client::new()
+            .get(format!("http://localhost:{}/slow?ms=0", port))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "done");
+
+        shutdown.shutdown();
+        server.await.unwrap();
+        assert_eq!(app.local_addr(), None);
+    }
+
+    // The hooks only run the first time
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["startup", "shutdown lifecycle"]
+    );
+}
@@ -0,0 +1,44 @@
+use cargoal::routes::http::StatusCode;
+
+mod utils;
+use utils::test_client;
+
+#[tokio::test]
+async fn test_state_and_extension_extractors() {
+    let client = test_client().await;
+
+    let response = client
+        .get("/state/config")
+        .header("X-Request-Id", "abc-123")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("x-request-id").unwrap(), "abc-123");
+    assert_eq!(
+        response.text().unwrap(),
+        "cargoal-test handled request abc-123"
+    );
+
+    let response = client.get("/state/config").send().await;
+    assert_eq!(
+        response.text().unwrap(),
+        "cargoal-test handled request generated"
+    );
+}
+
+#[tokio::test]
+async fn test_missing_state_is_a_server_error() {
+    let client = test_client().await;
+
+    let response = client.get("/state/missing-state").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response.text().unwrap().contains("u64"));
+}
+
+#[tokio::test]
+async fn test_missing_extension_is_a_server_error() {
+    let client = test_client().await;
+
+    let response = client.get("/missing-extension").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+}
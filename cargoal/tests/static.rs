@@ -1,100 +1,88 @@
-use reqwest::Client;
-use reqwest::StatusCode;
+use cargoal::routes::http::StatusCode;
+use cargoal::routes::middlewares::SecurityHeaders;
 
 mod utils;
-use utils::start_test_server;
+use utils::{security_client, test_client};
 
 #[tokio::test]
 async fn test_static_file_serving() {
-    start_test_server(8097).await;
-    let client = Client::new();
-
-    let response_css = client
-        .get("http://localhost:8097/static/styles.css")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response_css.status(), StatusCode::OK);
-    let headers_css = response_css.headers();
-    assert_eq!(headers_css.get("Content-Type").unwrap(), "text/css");
-    let content_css = response_css.text().await.unwrap();
+    let client = test_client().await;
+
+    let response_css = client.get("/static/styles.css").send().await;
+    assert_eq!(response_css.status_code, StatusCode::OK);
+    assert_eq!(response_css.header("Content-Type").unwrap(), "text/css");
+    let content_css = response_css.text().unwrap();
     assert!(content_css.contains("body {"));
 
-    let response_js = client
-        .get("http://localhost:8097/static/script.js")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response_js.status(), StatusCode::OK);
-    let headers_js = response_js.headers();
+    let response_js = client.get("/static/script.js").send().await;
+    assert_eq!(response_js.status_code, StatusCode::OK);
     assert_eq!(
-        headers_js.get("Content-Type").unwrap(),
+        response_js.header("Content-Type").unwrap(),
         "application/javascript"
     );
-    let content_js = response_js.text().await.unwrap();
+    let content_js = response_js.text().unwrap();
     assert!(content_js.contains("console.log("));
 }
 
 #[tokio::test]
 async fn test_static_forbidden_files() {
-    start_test_server(8099).await;
-    let client = Client::new();
-
-    let response_php = client
-        .get("http://localhost:8099/static/malicious.php")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response_php.status(), StatusCode::FORBIDDEN);
-
-    let response_exe = client
-        .get("http://localhost:8099/static/malicious.exe")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response_exe.status(), StatusCode::FORBIDDEN);
+    let client = test_client().await;
+
+    let response_php = client.get("/static/malicious.php").send().await;
+    assert_eq!(response_php.status_code, StatusCode::FORBIDDEN);
+
+    let response_exe = client.get("/static/malicious.exe").send().await;
+    assert_eq!(response_exe.status_code, StatusCode::FORBIDDEN);
 }
 
 #[tokio::test]
 async fn test_static_directory_listing_protection() {
-    start_test_server(8100).await;
-    let client = Client::new();
-
-    let response = client
-        .get("http://localhost:8100/static/")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let client = test_client().await;
+
+    let response = client.get("/static/").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
 }
 
 #[tokio::test]
 async fn test_static_large_file_rejection() {
-    start_test_server(8101).await;
+    let client = test_client().await;
+    let response = client.get("/static/big_file.dat").send().await;
 
-    let client = Client::new();
-    let response = client
-        .get("http://localhost:8101/static/big_file.dat")
-        .send()
-        .await
-        .unwrap();
-
-    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response.status_code, StatusCode::PAYLOAD_TOO_LARGE);
 }
 
 #[tokio::test]
 async fn test_static_security_headers() {
-    start_test_server(8102).await;
-    let client = Client::new();
-
-    let response = client
-        .get("http://localhost:8102/static/styles.css")
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
-
-    let headers = response.headers();
-    assert_eq!(headers.get("X-Content-Type-Options").unwrap(), "nosniff");
-    assert_eq!(headers.get("X-Frame-Options").unwrap(), "DENY");
+    // The security headers come from the SecurityHeaders middleware only
+    let client = test_client().await;
+    let response = client.get("/static/styles.css").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("X-Content-Type-Options"), None);
+    assert_eq!(response.header("X-Frame-Options"), None);
+
+    let client = security_client(SecurityHeaders::new()).await;
+    let response = client.get("/static/styles.css").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(
+        response.header("X-Content-Type-Options").unwrap(),
+        "nosniff"
+    );
+    assert_eq!(response.header("X-Frame-Options").unwrap(), "DENY");
+
+    let client = security_client(SecurityHeaders::new().without_header("X-Frame-Options")).await;
+    let response = client.get("/static/styles.css").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("X-Frame-Options"), None);
+}
+
+#[tokio::test]
+async fn test_static_binary_file() {
+    let client = test_client().await;
+
+    let response = client.get("/static/pixel.png").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("Content-Type").unwrap(), "image/png");
+
+    let expected = std::fs::read("tests/static/pixel.png").unwrap();
+    assert_eq!(response.body.as_bytes().unwrap().to_vec(), expected);
 }
@@ -0,0 +1,86 @@
+use cargoal::routes::http::{HttpMethod, Request, StatusCode};
+use serde_json::json;
+
+mod utils;
+use utils::test_client;
+
+#[tokio::test]
+async fn test_client_dispatches_through_the_server() {
+    let client = test_client().await;
+
+    let response = client.get("/about").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.header("content-type"), Some("text/html"));
+    assert!(response.text().unwrap().contains("About Us"));
+
+    // Global middlewares
+    let response = client.get("/middleware-block").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+
+    // Subdomain detection
+    let response = client
+        .get("/about/42")
+        .header("X-Mock-Subdomain", "api")
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert!(response.text().unwrap().contains("Details about ID: 42"));
+
+    // Unknown routes and methods
+    let response = client.get("/unknown-route").send().await;
+    assert_eq!(response.status_code, StatusCode::NOT_FOUND);
+    let response = client.delete("/about").send().await;
+    assert_eq!(response.status_code, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.header("allow"), Some("GET, HEAD, OPTIONS"));
+}
+
+#[tokio::test]
+async fn test_client_bodies() {
+    let client = test_client().await;
+
+    let response = client
+        .post("/extract/users")
+        .json(&json!({ "name": "Ada", "age": 36 }))
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::CREATED);
+    assert_eq!(response.text(), Some("Created Ada aged 36"));
+
+    let response = client
+        .post("/extract/form")
+        .form(&[("name", "Grace"), ("age", "45")])
+        .send()
+        .await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    assert_eq!(response.text(), Some("Form from Grace aged 45"));
+
+    let response = client
+        .post("/submit")
+        .header("X-Mock-Subdomain", "api")
+        .body("raw body")
+        .send()
+        .await;
+    assert_eq!(response.text(), Some("Received body: raw body"));
+}
+
+#[tokio::test]
+async fn test_client_sends_constructed_requests() {
+    let client = test_client().await;
+
+    let request = Request::new(HttpMethod::GET, "/query-test?name=Ada");
+    assert_eq!(request.path, "/query-test");
+    let response = client.send(request).await;
+    assert_eq!(response.text(), Some("Hello, Ada!"));
+
+    let request =
+        Request::new(HttpMethod::GET, "/state/config").with_header("X-Request-Id", "in-process");
+    let response = client.send(request).await;
+    assert_eq!(
+        response.text(),
+        Some("cargoal-test handled request in-process")
+    );
+
+    // Static files
+    let response = client.get("/static/../Cargo.toml").send().await;
+    assert_eq!(response.status_code, StatusCode::FORBIDDEN);
+}
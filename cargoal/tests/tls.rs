@@ -0,0 +1,147 @@
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::server::{ServerHandle, TlsError};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::sleep;
+use tokio_rustls::TlsConnector;
+
+/// Copy the test certificates to a temporary directory, so that they can be replaced
+fn copy_certificates(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cargoal-tls-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for file in ["localhost", "api.localhost", "localhost-renewed"] {
+        for ext in ["pem", "key"] {
+            let file = format!("{}.{}", file, ext);
+            std::fs::copy(format!("tests/certs/{}", file), dir.join(&file)).unwrap();
+        }
+    }
+    dir
+}
+
+async fn start_tls_server(dir: &Path) -> u16 {
+    let path = |file: &str| dir.join(file).to_str().unwrap().to_string();
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+    app.with_tls(&path("localhost.pem"), &path("localhost.key"))
+        .await
+        .unwrap();
+    app.with_tls_for(
+        "api.localhost",
+        &path("api.localhost.pem"),
+        &path("api.localhost.key"),
+    )
+    .await
+    .unwrap();
+    app.with_tls_reload_interval(Some(Duration::from_millis(100)))
+        .await;
+
+    app.route("/hello", HttpMethod::GET)
+        .with_handler(|_req: Request| Response::new(StatusCode::OK, "Hello over TLS"))
+        .register()
+        .await;
+    app.route("/hello", HttpMethod::GET)
+        .with_subdomain("api")
+        .with_handler(|_req: Request| Response::new(StatusCode::OK, "Hello from the API"))
+        .register()
+        .await;
+
+    let server = app.clone();
+    tokio::spawn(async move { server.run().await });
+    app.ready().await.port()
+}
+
+/// Send a request over TLS, returning the certificate of the server and the response
+async fn https_get(port: u16, server_name: &str, path: &str) -> (CertificateDer<'static>, String) {
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_file("tests/certs/ca.pem").unwrap())
+        .unwrap();
+    let config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+    let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let mut stream = TlsConnector::from(Arc::new(config))
+        .connect(
+            ServerName::try_from(server_name.to_string()).unwrap(),
+            stream,
+        )
+        .await
+        .unwrap();
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, server_name
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response).await;
+
+    let certificate = stream.get_ref().1.peer_certificates().unwrap()[0].clone();
+    (certificate, String::from_utf8_lossy(&response).to_string())
+}
+
+fn certificate(file: &str) -> CertificateDer<'static> {
+    CertificateDer::from_pem_file(format!("tests/certs/{}", file)).unwrap()
+}
+
+#[tokio::test]
+async fn test_https_with_sni() {
+    let dir = copy_certificates("sni");
+    let port = start_tls_server(&dir).await;
+
+    let (cert, response) = https_get(port, "localhost", "/hello").await;
+    assert_eq!(cert, certificate("localhost.pem"));
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.ends_with("Hello over TLS"));
+
+    // The SNI name selects the certificate, the Host header the subdomain routes
+    let (cert, response) = https_get(port, "api.localhost", "/hello").await;
+    assert_eq!(cert, certificate("api.localhost.pem"));
+    assert!(response.ends_with("Hello from the API"));
+}
+
+#[tokio::test]
+async fn test_certificate_hot_reload() {
+    let dir = copy_certificates("reload");
+    let port = start_tls_server(&dir).await;
+
+    let (cert, _) = https_get(port, "localhost", "/hello").await;
+    assert_eq!(cert, certificate("localhost.pem"));
+
+    std::fs::copy(dir.join("localhost-renewed.key"), dir.join("localhost.key")).unwrap();
+    std::fs::copy(dir.join("localhost-renewed.pem"), dir.join("localhost.pem")).unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    let (cert, response) = https_get(port, "localhost", "/hello").await;
+    assert_eq!(cert, certificate("localhost-renewed.pem"));
+    assert!(response.ends_with("Hello over TLS"));
+}
+
+#[tokio::test]
+async fn test_invalid_certificate_files() {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+
+    let result = app
+        .with_tls("tests/certs/missing.pem", "tests/certs/localhost.key")
+        .await;
+    assert!(matches!(result, Err(TlsError::Io { .. })));
+
+    let result = app
+        .with_tls("tests/certs/localhost.key", "tests/certs/localhost.key")
+        .await;
+    assert!(matches!(result, Err(TlsError::InvalidPem { .. })));
+
+    let result = app
+        .with_tls("tests/certs/localhost.pem", "tests/certs/api.localhost.key")
+        .await;
+    assert!(matches!(result, Err(TlsError::InvalidKey { .. })));
+}
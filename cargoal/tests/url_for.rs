@@ -0,0 +1,116 @@
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::routing::{RouteError, UrlError};
+use cargoal::routes::server::ServerHandle;
+use std::collections::HashMap;
+
+mod utils;
+use utils::test_client;
+
+fn ok_handler(_req: Request) -> Response {
+    Response::new(StatusCode::OK, "OK")
+}
+
+/// Build a server with a few named routes, without running it
+async fn named_routes_server() -> ServerHandle {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+
+    app.route("/", HttpMethod::GET)
+        .with_name("home")
+        .with_handler(ok_handler)
+        .register()
+        .await;
+    app.route("/users/:id<int>", HttpMethod::GET)
+        .with_name("user.show")
+        .with_handler(ok_handler)
+        .register()
+        .await;
+    app.route("/docs/:page?", HttpMethod::GET)
+        .with_name("docs")
+        .with_handler(ok_handler)
+        .register()
+        .await;
+    app.route("/files/*path", HttpMethod::GET)
+        .with_name("files")
+        .with_handler(ok_handler)
+        .register()
+        .await;
+
+    app
+}
+
+#[tokio::test]
+async fn test_url_for() {
+    let app = named_routes_server().await;
+    let no_params: [(&str, &str); 0] = [];
+
+    assert_eq!(app.url_for("home", no_params).unwrap(), "/");
+    assert_eq!(app.url_for("user.show", [("id", 42)]).unwrap(), "/users/42");
+    assert_eq!(app.url_for("docs", no_params).unwrap(), "/docs");
+    assert_eq!(
+        app.url_for("docs", [("page", "getting started")]).unwrap(),
+        "/docs/getting%20started"
+    );
+    assert_eq!(
+        app.url_for("files", [("path", "css/site.css")]).unwrap(),
+        "/files/css/site.css"
+    );
+
+    // Extra parameters go to the query string
+    let params = HashMap::from([("id", "7"), ("tab", "posts & comments")]);
+    assert_eq!(
+        app.urls().url_for("user.show", params).unwrap(),
+        "/users/7?tab=posts+%26+comments"
+    );
+}
+
+#[tokio::test]
+async fn test_url_for_errors() {
+    let app = named_routes_server().await;
+    let no_params: [(&str, &str); 0] = [];
+
+    assert_eq!(
+        app.url_for("user.show", no_params),
+        Err(UrlError::MissingParam {
+            route: "user.show".to_string(),
+            param: "id".to_string(),
+        })
+    );
+    assert!(matches!(
+        app.url_for("user.show", [("id", "abc")]),
+        Err(UrlError::InvalidParam { .. })
+    ));
+    assert_eq!(
+        app.url_for("user.edit", no_params),
+        Err(UrlError::UnknownRoute("user.edit".to_string()))
+    );
+
+    let duplicate = app
+        .route("/profile", HttpMethod::GET)
+        .with_name("home")
+        .with_handler(ok_handler)
+        .try_register()
+        .await;
+    assert_eq!(
+        duplicate,
+        Err(RouteError::DuplicateName("home".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_url_for_in_templates() {
+    let client = test_client().await;
+
+    let response = client.get("/links").send().await;
+    assert_eq!(response.status_code, StatusCode::OK);
+    let body = response.text().unwrap();
+    assert!(body.contains(r#"<a href="/articles/42">Article</a>"#));
+    assert!(body.contains(r#"<a href="/archive/2024?sort=new">Archive</a>"#));
+
+    let response = client.get("/broken-link").send().await;
+    assert_eq!(response.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("Missing parameter 'id' for route 'article.show'"));
+}
@@ -1,29 +1,46 @@
-use cargoal::routes::http::Request;
+use super::middlewares::RequestId;
+use cargoal::routes::extract::{
+    Authorization, Extension, Form, Header, Json, Path, PrivateJar, Query, SignedJar, State,
+    UserAgent,
+};
 use cargoal::routes::http::Response;
+use cargoal::routes::http::StatusCode;
+use cargoal::routes::http::{Cookie, Request, SameSite};
+use cargoal::routes::middlewares::{CspNonce, CsrfToken};
+use cargoal::routes::session::Session;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, UNIX_EPOCH};
 
 #[cfg(test)]
 pub fn query_test_handler(req: Request) -> Response {
     if let Some(name) = req.params.get("name") {
-        Response::new(200, Some(format!("Hello, {}!", name)))
+        Response::new(StatusCode::OK, Some(format!("Hello, {}!", name)))
     } else {
-        Response::new(400, Some("Missing 'name' parameter".to_string()))
+        Response::new(
+            StatusCode::BAD_REQUEST,
+            Some("Missing 'name' parameter".to_string()),
+        )
     }
 }
 
 #[cfg(test)]
 pub fn submit_handler(req: Request) -> Response {
-    if let Some(body) = req.body.clone() {
-        Response::new(200, Some(format!("Received body: {}", body)))
+    if let Some(body) = req.text() {
+        Response::new(StatusCode::OK, Some(format!("Received body: {}", body)))
             .with_header("Content-Type", "text/plain")
     } else {
-        Response::new(400, Some("No body provided".to_string()))
+        Response::new(
+            StatusCode::BAD_REQUEST,
+            Some("No body provided".to_string()),
+        )
     }
 }
 
 #[cfg(test)]
 pub fn users_handler(_req: Request) -> Response {
     Response::new(
-        200,
+        StatusCode::OK,
         Some("[{\"id\":1,\"name\":\"Alice\"},{\"id\":2,\"name\":\"Bob\"}]".to_string()),
     )
     .with_header("Content-Type", "application/json")
@@ -32,43 +49,431 @@ pub fn users_handler(_req: Request) -> Response {
 #[cfg(test)]
 pub fn user_handler(req: Request) -> Response {
     if let Some(id) = req.params.get("id") {
-        Response::new(200, Some(format!("Details about ID: {}", id)))
+        Response::new(StatusCode::OK, Some(format!("Details about ID: {}", id)))
             .with_header("Content-Type", "application/json")
     } else {
-        Response::new(400, Some("Bad Request: Missing ID".to_string()))
+        Response::new(
+            StatusCode::BAD_REQUEST,
+            Some("Bad Request: Missing ID".to_string()),
+        )
     }
 }
 
 #[cfg(test)]
 pub fn options_test_handler(_req: Request) -> Response {
-    Response::new(200, Some("Available methods: GET, POST".to_string()))
-        .with_header("Allow", "GET, POST")
+    Response::new(
+        StatusCode::OK,
+        Some("Available methods: GET, POST".to_string()),
+    )
+    .with_header("Allow", "GET, POST")
 }
 
 #[cfg(test)]
 pub fn order_handler(req: Request) -> Response {
     if let Some(order_id) = req.params.get("order_id") {
-        Response::new(200, Some(format!("Details about order ID: {}", order_id)))
+        Response::new(
+            StatusCode::OK,
+            Some(format!("Details about order ID: {}", order_id)),
+        )
     } else {
-        Response::new(400, Some("Missing order ID".to_string()))
+        Response::new(
+            StatusCode::BAD_REQUEST,
+            Some("Missing order ID".to_string()),
+        )
     }
 }
 
 #[cfg(test)]
 pub fn item_handler(req: Request) -> Response {
     if let Some(name) = req.params.get("name") {
-        Response::new(200, Some(format!("Details about item name: {}", name)))
+        Response::new(
+            StatusCode::OK,
+            Some(format!("Details about item name: {}", name)),
+        )
     } else {
-        Response::new(400, Some("Missing item name".to_string()))
+        Response::new(
+            StatusCode::BAD_REQUEST,
+            Some("Missing item name".to_string()),
+        )
     }
 }
 
 #[cfg(test)]
 pub fn middleware_test_handler(_req: Request) -> Response {
-    Response::new(200, Some("Middleware executed!".to_string()))
+    Response::new(StatusCode::OK, Some("Middleware executed!".to_string()))
 }
 
 #[cfg(test)]
 pub fn this_should_not_be_reached_handler(_req: Request) -> Response {
-    Response::new(200, Some("This should not be reached".to_string()))
+    Response::new(
+        StatusCode::OK,
+        Some("This should not be reached".to_string()),
+    )
+}
+
+#[cfg(test)]
+pub async fn async_handler(req: Request) -> Response {
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    Response::new(
+        StatusCode::OK,
+        Some(format!("Async handler reached: {}", req.path)),
+    )
+}
+
+#[cfg(test)]
+pub fn echo_header_handler(req: Request) -> Response {
+    match req.header("X-Custom-Header") {
+        Some(value) => Response::new(StatusCode::OK, Some(format!("Header value: {}", value))),
+        None => Response::new(
+            StatusCode::BAD_REQUEST,
+            Some("Missing 'X-Custom-Header' header".to_string()),
+        ),
+    }
+}
+
+#[cfg(test)]
+pub fn binary_body_handler(req: Request) -> Response {
+    let body = req.body.unwrap_or_default();
+    let sum: u32 = body.iter().map(|&b| b as u32).sum();
+    Response::new(
+        StatusCode::OK,
+        Some(format!("Received {} bytes, checksum {}", body.len(), sum)),
+    )
+}
+
+#[cfg(test)]
+pub fn teapot_handler(_req: Request) -> Response {
+    Response::new(StatusCode::IM_A_TEAPOT, Some("No coffee here".to_string()))
+        .with_reason_phrase("Short And Stout")
+}
+
+#[cfg(test)]
+pub fn csv_export_handler(_req: Request) -> Response {
+    let rows =
+        futures_util::stream::iter(0..3).map(|i| Ok(format!("{},item-{}\n", i, i).into_bytes()));
+    Response::stream(StatusCode::OK, rows).with_header("Content-Type", "text/csv")
+}
+
+#[cfg(test)]
+#[derive(Deserialize)]
+pub struct PostParams {
+    pub user_id: u32,
+    pub post_id: u32,
+}
+
+#[cfg(test)]
+#[derive(Deserialize)]
+pub struct Pagination {
+    pub page: u32,
+    pub per_page: Option<u32>,
+}
+
+#[cfg(test)]
+#[derive(Deserialize)]
+pub struct NewUser {
+    pub name: String,
+    pub age: u8,
+}
+
+#[cfg(test)]
+pub fn extract_path_handler(Path(params): Path<PostParams>) -> Response {
+    Response::new(
+        StatusCode::OK,
+        Some(format!("User {} post {}", params.user_id, params.post_id)),
+    )
+}
+
+#[cfg(test)]
+pub fn extract_path_scalar_handler(Path(id): Path<u32>) -> Response {
+    Response::new(StatusCode::OK, format!("Item {}", id))
+}
+
+#[cfg(test)]
+pub fn extract_path_tuple_handler(Path((team, id)): Path<(String, u32)>) -> Response {
+    Response::new(StatusCode::OK, format!("Team {} member {}", team, id))
+}
+
+#[cfg(test)]
+pub fn extract_query_handler(Query(pagination): Query<Pagination>) -> Response {
+    Response::new(
+        StatusCode::OK,
+        Some(format!(
+            "Page {} with {} items",
+            pagination.page,
+            pagination.per_page.unwrap_or(10)
+        )),
+    )
+}
+
+#[cfg(test)]
+pub async fn extract_json_handler(Json(user): Json<NewUser>) -> Response {
+    Response::new(
+        StatusCode::CREATED,
+        Some(format!("Created {} aged {}", user.name, user.age)),
+    )
+}
+
+#[cfg(test)]
+pub fn extract_form_handler(Form(user): Form<NewUser>) -> Response {
+    Response::new(
+        StatusCode::OK,
+        Some(format!("Form from {} aged {}", user.name, user.age)),
+    )
+}
+
+#[cfg(test)]
+pub fn extract_header_handler(
+    Header(user_agent): Header<UserAgent>,
+    authorization: Option<Header<Authorization>>,
+) -> Response {
+    let scheme = authorization
+        .map(|Header(auth)| auth.scheme)
+        .unwrap_or_else(|| "none".to_string());
+    Response::new(
+        StatusCode::OK,
+        Some(format!("Agent {} with auth {}", user_agent.0, scheme)),
+    )
+}
+
+#[cfg(test)]
+#[derive(Serialize)]
+pub struct Product {
+    pub name: String,
+    pub price: u32,
+}
+
+#[cfg(test)]
+pub fn product_json_handler(_req: Request) -> Response {
+    Response::json(Product {
+        name: "Keyboard".to_string(),
+        price: 49,
+    })
+}
+
+#[cfg(test)]
+pub fn created_json_handler(_req: Request) -> Response {
+    Response::json(serde_json::json!({ "id": 3, "status": "created" }))
+        .with_status(StatusCode::CREATED)
+}
+
+#[cfg(test)]
+pub fn params_handler(req: Request) -> Response {
+    let mut params: Vec<String> = req
+        .path_params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    params.sort();
+    Response::new(StatusCode::OK, params.join("&"))
+}
+
+#[cfg(test)]
+#[derive(Deserialize)]
+pub struct ArchiveParams {
+    pub year: Option<u16>,
+    pub month: Option<u8>,
+}
+
+#[cfg(test)]
+pub fn archive_handler(Path(archive): Path<ArchiveParams>) -> Response {
+    match (archive.year, archive.month) {
+        (Some(year), Some(month)) => {
+            Response::new(StatusCode::OK, format!("Archive {}-{:02}", year, month))
+        }
+        (Some(year), None) => Response::new(StatusCode::OK, format!("Archive {}", year)),
+        _ => Response::new(StatusCode::OK, "Archive"),
+    }
+}
+
+#[cfg(test)]
+pub fn trace_handler(req: Request) -> Response {
+    Response::new(
+        StatusCode::OK,
+        req.header("x-trace").unwrap_or_default().to_string(),
+    )
+}
+
+/// Configuration shared with the handlers through `ServerHandle::with_state`
+pub struct AppConfig {
+    pub name: String,
+}
+
+#[cfg(test)]
+pub fn state_handler(config: State<AppConfig>, Extension(id): Extension<RequestId>) -> Response {
+    Response::new(
+        StatusCode::OK,
+        Some(format!("{} handled request {}", config.name, id.0)),
+    )
+}
+
+#[cfg(test)]
+pub fn missing_extension_handler(Extension(id): Extension<RequestId>) -> Response {
+    Response::new(StatusCode::OK, Some(id.0))
+}
+
+#[cfg(test)]
+pub fn missing_state_handler(State(count): State<u64>) -> Response {
+    Response::new(StatusCode::OK, Some(count.to_string()))
+}
+
+#[cfg(test)]
+pub fn logged_user_handler(req: Request) -> Response {
+    Response::new(StatusCode::OK, Some(format!("User {}", req.params["id"])))
+}
+
+#[cfg(test)]
+pub fn set_preferences_handler(_req: Request) -> Response {
+    Response::new(StatusCode::NO_CONTENT, None)
+        .set_cookie(
+            Cookie::new("theme", "dark")
+                .with_path("/")
+                .with_domain("example.com")
+                .with_max_age(Duration::from_secs(3600))
+                .with_expires(UNIX_EPOCH + Duration::from_secs(784_111_777))
+                .with_secure(true)
+                .with_http_only(true)
+                .with_same_site(SameSite::Lax),
+        )
+        .set_cookie(Cookie::new("lang", "fr"))
+        .set_cookie(Cookie::new("lang", "en"))
+}
+
+#[cfg(test)]
+pub fn preferences_handler(req: Request) -> Response {
+    let cookies = req.cookies();
+    let mut names: Vec<_> = cookies.keys().cloned().collect();
+    names.sort();
+    Response::new(
+        StatusCode::OK,
+        format!(
+            "{} cookies, theme={}",
+            names.len(),
+            req.cookie("theme").unwrap_or_default()
+        ),
+    )
+}
+
+#[cfg(test)]
+pub fn remove_preferences_handler(_req: Request) -> Response {
+    Response::new(StatusCode::NO_CONTENT, None)
+        .remove_cookie(Cookie::new("theme", "").with_path("/"))
+}
+
+#[cfg(test)]
+pub fn sign_user_handler(jar: SignedJar) -> Response {
+    Response::new(StatusCode::NO_CONTENT, None).set_cookie(jar.sign(Cookie::new("user", "ada")))
+}
+
+#[cfg(test)]
+pub fn signed_user_handler(jar: SignedJar) -> Response {
+    match jar.get("user") {
+        Some(user) => Response::new(StatusCode::OK, user),
+        None => Response::new(StatusCode::UNAUTHORIZED, "Unauthorized"),
+    }
+}
+
+#[cfg(test)]
+pub fn encrypt_cart_handler(jar: PrivateJar) -> Response {
+    Response::new(StatusCode::NO_CONTENT, None)
+        .set_cookie(jar.encrypt(Cookie::new("cart", "3 items")))
+}
+
+#[cfg(test)]
+pub fn private_cart_handler(jar: PrivateJar) -> Response {
+    match jar.get("cart") {
+        Some(cart) => Response::new(StatusCode::OK, cart),
+        None => Response::new(StatusCode::UNAUTHORIZED, "Unauthorized"),
+    }
+}
+
+#[cfg(test)]
+pub fn login_handler(req: Request) -> Response {
+    // The Session is also a request extension
+    let session = req.extensions.get::<Session>().unwrap();
+    session
+        .insert("user", req.text().unwrap_or_default())
+        .unwrap();
+    session.rotate();
+    Response::new(StatusCode::NO_CONTENT, None)
+}
+
+#[cfg(test)]
+pub fn me_handler(session: Session) -> Response {
+    match session.get::<String>("user") {
+        Some(user) => Response::new(StatusCode::OK, user),
+        None => Response::new(StatusCode::UNAUTHORIZED, "Anonymous"),
+    }
+}
+
+#[cfg(test)]
+pub fn visits_handler(session: Session) -> Response {
+    let visits = session.get::<u32>("visits").unwrap_or_default() + 1;
+    session.insert("visits", visits).unwrap();
+    Response::new(StatusCode::OK, visits.to_string())
+}
+
+#[cfg(test)]
+pub fn logout_handler(session: Session) -> Response {
+    session.destroy();
+    Response::new(StatusCode::NO_CONTENT, None)
+}
+
+#[cfg(test)]
+pub fn save_profile_handler(req: Request) -> Response {
+    Response::new(StatusCode::OK, format!("Saved {}", req.text().unwrap()))
+}
+
+#[cfg(test)]
+pub fn delete_profile_handler(_req: Request) -> Response {
+    Response::new(StatusCode::NO_CONTENT, None)
+}
+
+#[cfg(test)]
+pub fn csrf_token_handler(Extension(CsrfToken(token)): Extension<CsrfToken>) -> Response {
+    Response::new(StatusCode::OK, token)
+}
+
+#[cfg(test)]
+pub fn created_handler(_req: Request) -> Response {
+    Response::new(StatusCode::CREATED, "Created")
+}
+
+#[cfg(test)]
+pub fn embed_handler(_req: Request) -> Response {
+    Response::new(StatusCode::OK, "Embeddable").with_header("X-Frame-Options", "ALLOWALL")
+}
+
+#[cfg(test)]
+pub fn csp_nonce_handler(Extension(CspNonce(nonce)): Extension<CspNonce>) -> Response {
+    Response::new(StatusCode::OK, nonce)
+}
+
+#[cfg(test)]
+pub fn search_handler(_req: Request) -> Response {
+    Response::new(StatusCode::OK, "Results")
+}
+
+#[cfg(test)]
+pub fn daily_report_handler(_req: Request) -> Response {
+    Response::new(StatusCode::OK, "Daily")
+}
+
+#[cfg(test)]
+pub fn weekly_report_handler(_req: Request) -> Response {
+    Response::new(StatusCode::OK, "Weekly")
+}
+
+#[cfg(test)]
+pub fn client_info_handler(req: Request) -> Response {
+    Response::new(
+        StatusCode::OK,
+        format!(
+            "{} {} {} {}",
+            req.remote_addr()
+                .map_or("-".to_string(), |addr| addr.to_string()),
+            req.client_ip().map_or("-".to_string(), |ip| ip.to_string()),
+            req.scheme(),
+            req.host().unwrap_or("-")
+        ),
+    )
 }
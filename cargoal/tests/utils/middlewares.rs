@@ -1,5 +1,7 @@
 use cargoal::routes::http::Request;
 use cargoal::routes::http::Response;
+use cargoal::routes::http::StatusCode;
+use cargoal::routes::routing::{Middleware, Next};
 
 #[cfg(test)]
 pub fn logging_middleware(req: &Request) -> Option<Response> {
@@ -11,7 +13,7 @@ pub fn logging_middleware(req: &Request) -> Option<Response> {
 pub fn block_middleware(req: &Request) -> Option<Response> {
     if req.path == "/middleware-block" {
         Some(Response::new(
-            403,
+            StatusCode::FORBIDDEN,
             Some("Forbidden by middleware".to_string()),
         ))
     } else {
@@ -22,7 +24,85 @@ pub fn block_middleware(req: &Request) -> Option<Response> {
 #[cfg(test)]
 pub fn block_middleware_group(_req: &Request) -> Option<Response> {
     Some(Response::new(
-        403,
+        StatusCode::FORBIDDEN,
         Some("Forbidden by middleware".to_string()),
     ))
 }
+
+#[cfg(test)]
+pub fn async_block_middleware(
+    req: &Request,
+) -> impl std::future::Future<Output = Option<Response>> + Send + 'static {
+    let blocked = req.path.ends_with("/blocked");
+    async move {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        if blocked {
+            Some(Response::new(
+                StatusCode::FORBIDDEN,
+                Some("Forbidden by async middleware".to_string()),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Record the order in which the middlewares see the Request and the Response
+#[cfg(test)]
+pub struct TraceMiddleware(pub &'static str);
+
+#[cfg(test)]
+impl Middleware for TraceMiddleware {
+    async fn handle(&self, mut req: Request, next: Next) -> Response {
+        if !req.path.starts_with("/ordering") {
+            return next.run(req).await;
+        }
+
+        let trace = match req.header("x-trace") {
+            Some(trace) => format!("{},{}", trace, self.0),
+            None => self.0.to_string(),
+        };
+        req.headers.insert("x-trace".to_string(), trace);
+
+        let response = next.run(req).await;
+        let trace = match response.header("X-Trace") {
+            Some(trace) => format!("{},{}", trace, self.0),
+            None => self.0.to_string(),
+        };
+        response.with_header("X-Trace", &trace)
+    }
+}
+
+#[cfg(test)]
+pub async fn timing_middleware(req: Request, next: Next) -> Response {
+    let start = std::time::Instant::now();
+    let response = next.run(req).await;
+    response.with_header(
+        "X-Response-Time",
+        &format!("{}us", start.elapsed().as_micros()),
+    )
+}
+
+#[cfg(test)]
+pub async fn not_found_page_middleware(req: Request, next: Next) -> Response {
+    let response = next.run(req).await;
+    if response.status_code == StatusCode::NOT_FOUND {
+        Response::new(StatusCode::NOT_FOUND, "Nothing to see here")
+    } else {
+        response
+    }
+}
+
+/// Identifier given to a Request by the request_id_middleware
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+#[cfg(test)]
+pub async fn request_id_middleware(mut req: Request, next: Next) -> Response {
+    let id = req
+        .header("x-request-id")
+        .unwrap_or("generated")
+        .to_string();
+    req.extensions.insert(RequestId(id.clone()));
+    next.run(req).await.with_header("X-Request-Id", &id)
+}
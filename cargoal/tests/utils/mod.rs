@@ -1,6 +1,12 @@
+// Every test binary includes these helpers but only uses some of them
+#![allow(dead_code, unused_imports)]
+
 pub mod handlers;
 pub mod middlewares;
+pub mod server;
 pub mod templates;
-pub mod utils;
 
-pub use utils::start_test_server;
+pub use server::{
+    access_log_client, cookie_server, csrf_client, proxy_server, rate_limit_server,
+    security_client, session_client, start_test_server, test_client,
+};
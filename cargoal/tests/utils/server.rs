@@ -0,0 +1,743 @@
+use super::handlers::{
+    archive_handler, async_handler, binary_body_handler, client_info_handler, created_handler,
+    created_json_handler, csp_nonce_handler, csrf_token_handler, csv_export_handler,
+    daily_report_handler, delete_profile_handler, echo_header_handler, embed_handler,
+    encrypt_cart_handler, extract_form_handler, extract_header_handler, extract_json_handler,
+    extract_path_handler, extract_path_scalar_handler, extract_path_tuple_handler,
+    extract_query_handler, item_handler, logged_user_handler, login_handler, logout_handler,
+    me_handler, middleware_test_handler, missing_extension_handler, missing_state_handler,
+    options_test_handler, order_handler, params_handler, preferences_handler, private_cart_handler,
+    product_json_handler, query_test_handler, remove_preferences_handler, save_profile_handler,
+    search_handler, set_preferences_handler, sign_user_handler, signed_user_handler, state_handler,
+    submit_handler, teapot_handler, this_should_not_be_reached_handler, trace_handler,
+    user_handler, users_handler, visits_handler, weekly_report_handler, AppConfig,
+};
+use super::middlewares::{
+    async_block_middleware, block_middleware, block_middleware_group, logging_middleware,
+    not_found_page_middleware, request_id_middleware, timing_middleware, TraceMiddleware,
+};
+use super::templates::{
+    about_handler, conditional_handler, dashboard_handler, escaping_handler, filters_handler,
+    home_handler, include_handler, list_handler, product_context_handler,
+};
+use cargoal::routes::http::{HttpMethod, Request, Response, StatusCode};
+use cargoal::routes::middlewares::{AccessLog, Cors, Csrf, CsrfExempt, LogFormat, SecurityHeaders};
+use cargoal::routes::rate_limit::{Quota, RateLimit};
+use cargoal::routes::server::{ServerHandle, TestClient};
+use cargoal::routes::session::{MemoryStore, SessionStore, Sessions};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Start the test server on a port chosen by the operating system
+/// ## Returns
+/// - u16 (the port, once the server accepts connections)
+#[cfg(test)]
+pub async fn start_test_server() -> u16 {
+    let app = test().await;
+    let server = app.clone();
+    tokio::spawn(async move {
+        server.run().await;
+    });
+    app.ready().await.port()
+}
+
+/// Build the test server without running it, to send requests in-process
+/// ## Returns
+/// - TestClient
+#[cfg(test)]
+pub async fn test_client() -> TestClient {
+    TestClient::new(test().await)
+}
+
+#[cfg(test)]
+async fn test() -> ServerHandle {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+
+    // Template dir configuration
+    app.with_template_dirs(vec!["tests/templates"]).await;
+    app.with_static_dir("tests/static").await;
+    app.with_max_static_file_size(5 * 1024 * 1024).await;
+
+    // Middleware configuration
+    app.add_middleware(logging_middleware).await;
+
+    app.add_middleware(block_middleware).await;
+
+    app.add_middleware(TraceMiddleware("global")).await;
+
+    app.add_middleware(
+        Cors::new()
+            .with_origin("https://app.example.com")
+            .with_methods(vec![HttpMethod::GET, HttpMethod::POST, HttpMethod::PUT])
+            .with_headers(vec!["Content-Type", "X-Requested-With"])
+            .with_credentials(true)
+            .with_max_age(Duration::from_secs(600)),
+    )
+    .await;
+
+    // Define routes
+    app.route("/", HttpMethod::GET)
+        .with_subdomain("www")
+        .with_template("home.html")
+        .with_context(home_handler)
+        .register()
+        .await;
+
+    app.route("/conditional", HttpMethod::GET)
+        .with_template("conditional.html")
+        .with_context(conditional_handler)
+        .register()
+        .await;
+
+    app.route("/list", HttpMethod::GET)
+        .with_template("list.html")
+        .with_context(list_handler)
+        .register()
+        .await;
+
+    app.route("/filters", HttpMethod::GET)
+        .with_template("filters.html")
+        .with_context(filters_handler)
+        .register()
+        .await;
+
+    app.route("/include", HttpMethod::GET)
+        .with_template("include.html")
+        .with_context(include_handler)
+        .register()
+        .await;
+
+    app.route("/missing", HttpMethod::GET)
+        .with_template("missing.html")
+        .register()
+        .await;
+
+    app.route("/escaping", HttpMethod::GET)
+        .with_template("escaping.html")
+        .with_context(escaping_handler)
+        .register()
+        .await;
+
+    app.route("/not_allowed", HttpMethod::GET)
+        .with_template("not_allowed.txt")
+        .register()
+        .await;
+
+    app.route("/injection", HttpMethod::GET)
+        .with_template("injection.html")
+        .register()
+        .await;
+
+    app.route("/about", HttpMethod::GET)
+        .with_template("about.html")
+        .with_context(about_handler)
+        .register()
+        .await;
+
+    app.route("/query-test", HttpMethod::GET)
+        .with_handler(query_test_handler)
+        .register()
+        .await;
+
+    app.route("/submit", HttpMethod::POST)
+        .with_subdomain("api")
+        .with_handler(submit_handler)
+        .register()
+        .await;
+
+    app.route("/about/:id", HttpMethod::GET)
+        .with_subdomain("api")
+        .with_handler(user_handler)
+        .register()
+        .await;
+
+    // Grouped routes example
+    app.with_group("/v1", |group| async move {
+        let mut group = group.lock().await;
+
+        group
+            .route("/users", HttpMethod::GET)
+            .with_subdomain("api")
+            .with_handler(users_handler)
+            .register()
+            .await;
+
+        group
+            .route("/users/:id", HttpMethod::GET)
+            .with_regex(r"^/v1/users/(?P<id>\d+)$")
+            .with_subdomain("api")
+            .with_handler(user_handler)
+            .register()
+            .await;
+
+        group
+            .route("/orders/:order_id", HttpMethod::GET)
+            .with_regex(r"^/v1/orders/(?P<order_id>[a-zA-Z0-9_-]+)$")
+            .with_handler(order_handler)
+            .register()
+            .await;
+
+        group
+            .route("/orders/legacy/:order_id", HttpMethod::GET)
+            .with_regex(r"^/v1/(orders/legacy|purchases)/(?P<order_id>\d+)$")
+            .with_handler(order_handler)
+            .register()
+            .await;
+
+        group
+            .route("/items/:name", HttpMethod::GET)
+            .with_regex(r"^/v1/items/(?P<name>[a-zA-Z]+)$")
+            .with_handler(item_handler)
+            .register()
+            .await;
+    })
+    .await;
+
+    // Route with OPTIONS method
+    app.route("/options-test", HttpMethod::OPTIONS)
+        .with_subdomain("api")
+        .with_handler(options_test_handler)
+        .register()
+        .await;
+
+    // Middleware test
+    app.route("/middleware-test/log", HttpMethod::GET)
+        .with_handler(middleware_test_handler)
+        .register()
+        .await;
+
+    app.route("/middleware-block-2", HttpMethod::GET)
+        .with_middleware(block_middleware_group)
+        .register()
+        .await;
+
+    app.with_group("/middleware-block-3", |group| async move {
+        let mut group = group.lock().await;
+
+        group.add_middleware(block_middleware_group);
+
+        group
+            .route("/block", HttpMethod::GET)
+            .with_handler(this_should_not_be_reached_handler)
+            .register()
+            .await;
+    })
+    .await;
+
+    // Around-style middlewares test
+    app.with_group("/ordering", |group| async move {
+        let mut group = group.lock().await;
+
+        group.add_middleware(TraceMiddleware("group"));
+
+        group
+            .route("/trace", HttpMethod::GET)
+            .with_middleware(TraceMiddleware("route-1"))
+            .with_middleware(TraceMiddleware("route-2"))
+            .with_handler(trace_handler)
+            .register()
+            .await;
+
+        group
+            .route("/timed", HttpMethod::GET)
+            .with_middleware(timing_middleware)
+            .with_middleware(not_found_page_middleware)
+            .with_handler(|_req: Request| Response::new(StatusCode::NOT_FOUND, "Not Found"))
+            .register()
+            .await;
+    })
+    .await;
+
+    // Request parsing test
+    app.route("/echo-header", HttpMethod::GET)
+        .with_handler(echo_header_handler)
+        .register()
+        .await;
+
+    app.route("/binary", HttpMethod::POST)
+        .with_handler(binary_body_handler)
+        .register()
+        .await;
+
+    // Status line test
+    app.route("/teapot", HttpMethod::GET)
+        .with_handler(teapot_handler)
+        .register()
+        .await;
+
+    // Streaming response test
+    app.route("/export.csv", HttpMethod::GET)
+        .with_handler(csv_export_handler)
+        .register()
+        .await;
+
+    // JSON responses and content negotiation test
+    app.route("/products/1", HttpMethod::GET)
+        .with_template("product.html")
+        .with_context(product_context_handler)
+        .with_handler(product_json_handler)
+        .register()
+        .await;
+
+    app.route("/json-created", HttpMethod::POST)
+        .with_handler(created_json_handler)
+        .register()
+        .await;
+
+    // Typed, optional and catch-all path parameters test
+    app.route("/articles/:id<int>", HttpMethod::GET)
+        .with_name("article.show")
+        .with_handler(params_handler)
+        .register()
+        .await;
+
+    app.route("/articles/:slug<[a-z-]+>", HttpMethod::GET)
+        .with_handler(params_handler)
+        .register()
+        .await;
+
+    app.route("/archive/:year<int>?/:month<int>?", HttpMethod::GET)
+        .with_name("archive")
+        .with_handler(archive_handler)
+        .register()
+        .await;
+
+    app.route("/assets/*rest", HttpMethod::GET)
+        .with_handler(params_handler)
+        .register()
+        .await;
+
+    // Named routes test
+    app.route("/links", HttpMethod::GET)
+        .with_template("links.html")
+        .register()
+        .await;
+
+    app.route("/broken-link", HttpMethod::GET)
+        .with_template("broken_link.html")
+        .register()
+        .await;
+
+    // Extractors test
+    app.route("/extract/users/:user_id/posts/:post_id", HttpMethod::GET)
+        .with_handler(extract_path_handler)
+        .register()
+        .await;
+
+    app.route("/extract/items/:id", HttpMethod::GET)
+        .with_handler(extract_path_scalar_handler)
+        .register()
+        .await;
+
+    app.route("/extract/teams/:team/members/:id", HttpMethod::GET)
+        .with_handler(extract_path_tuple_handler)
+        .register()
+        .await;
+
+    app.route("/extract/posts", HttpMethod::GET)
+        .with_handler(extract_query_handler)
+        .register()
+        .await;
+
+    app.route("/extract/users", HttpMethod::POST)
+        .with_handler(extract_json_handler)
+        .register()
+        .await;
+
+    app.route("/extract/form", HttpMethod::POST)
+        .with_handler(extract_form_handler)
+        .register()
+        .await;
+
+    app.route("/extract/header", HttpMethod::GET)
+        .with_handler(extract_header_handler)
+        .register()
+        .await;
+
+    // Shared state and request extensions test
+    app.with_state(AppConfig {
+        name: "cargoal-test".to_string(),
+    })
+    .await;
+
+    app.with_group("/state", |group| async move {
+        let mut group = group.lock().await;
+
+        group.add_middleware(request_id_middleware);
+
+        group
+            .route("/config", HttpMethod::GET)
+            .with_handler(state_handler)
+            .register()
+            .await;
+
+        group
+            .route("/missing-state", HttpMethod::GET)
+            .with_handler(missing_state_handler)
+            .register()
+            .await;
+    })
+    .await;
+
+    app.route("/missing-extension", HttpMethod::GET)
+        .with_handler(missing_extension_handler)
+        .register()
+        .await;
+
+    // Nested groups test
+    app.with_group("/admin", |group| async move {
+        let mut group = group.lock().await;
+
+        group
+            .with_subdomain("admin")
+            .with_template_dir("tests/templates/admin")
+            .with_layout("layout.html")
+            .with_state(AppConfig {
+                name: "admin".to_string(),
+            })
+            .add_middleware(request_id_middleware);
+
+        group
+            .route("/dashboard", HttpMethod::GET)
+            .with_template("dashboard.html")
+            .with_context(dashboard_handler)
+            .register()
+            .await;
+
+        group
+            .group("/v2", |group| async move {
+                let mut group = group.lock().await;
+
+                group
+                    .route("/config", HttpMethod::GET)
+                    .with_handler(state_handler)
+                    .register()
+                    .await;
+            })
+            .await;
+    })
+    .await;
+
+    // Async handler and middleware test
+    app.route("/async", HttpMethod::GET)
+        .with_handler(async_handler)
+        .register()
+        .await;
+
+    app.with_group("/async-middleware", |group| async move {
+        let mut group = group.lock().await;
+
+        group.add_middleware(async_block_middleware);
+
+        group
+            .route("/allowed", HttpMethod::GET)
+            .with_handler(|req: Request| async move {
+                Response::new(StatusCode::OK, Some(format!("Allowed: {}", req.path)))
+            })
+            .register()
+            .await;
+
+        group
+            .route("/blocked", HttpMethod::GET)
+            .with_handler(this_should_not_be_reached_handler)
+            .register()
+            .await;
+    })
+    .await;
+
+    println!("Server set up with all routes.");
+    app
+}
+
+/// Build a server with the test templates and no routes, for the tests of a single feature
+/// ## Returns
+/// - ServerHandle
+#[cfg(test)]
+async fn empty_app() -> ServerHandle {
+    let mut app = ServerHandle::new("127.0.0.1:0");
+    app.with_template_dirs(vec!["tests/templates"]).await;
+    app
+}
+
+/// A writer keeping the access log lines in memory
+#[derive(Clone, Default)]
+pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /// Get the lines written so far
+    /// ## Returns
+    /// - Vec<String>
+    pub fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Build a server writing an access log in memory
+/// ## Args
+/// - format: LogFormat
+/// ## Returns
+/// - (TestClient, SharedBuffer) (the buffer holds the access log)
+#[cfg(test)]
+pub async fn access_log_client(format: LogFormat) -> (TestClient, SharedBuffer) {
+    let buffer = SharedBuffer::default();
+    let app = empty_app().await;
+    app.add_middleware(AccessLog::new(format).with_writer(buffer.clone()))
+        .await;
+
+    app.route("/users/:id", HttpMethod::GET)
+        .with_handler(logged_user_handler)
+        .register()
+        .await;
+
+    (TestClient::new(app), buffer)
+}
+
+/// Build a server setting and reading plain, signed and private cookies
+/// ## Args
+/// - secret: Option<&[u8]> (the secret key of the signed and private cookies)
+/// ## Returns
+/// - ServerHandle
+#[cfg(test)]
+pub async fn cookie_server(secret: Option<&[u8]>) -> ServerHandle {
+    let mut app = empty_app().await;
+    if let Some(secret) = secret {
+        app.with_secret_key(secret).await.unwrap();
+    }
+
+    app.route("/preferences", HttpMethod::POST)
+        .with_handler(set_preferences_handler)
+        .register()
+        .await;
+
+    app.route("/preferences", HttpMethod::GET)
+        .with_handler(preferences_handler)
+        .register()
+        .await;
+
+    app.route("/preferences", HttpMethod::DELETE)
+        .with_handler(remove_preferences_handler)
+        .register()
+        .await;
+
+    app.route("/signed", HttpMethod::POST)
+        .with_handler(sign_user_handler)
+        .register()
+        .await;
+
+    app.route("/signed", HttpMethod::GET)
+        .with_handler(signed_user_handler)
+        .register()
+        .await;
+
+    app.route("/private", HttpMethod::POST)
+        .with_handler(encrypt_cart_handler)
+        .register()
+        .await;
+
+    app.route("/private", HttpMethod::GET)
+        .with_handler(private_cart_handler)
+        .register()
+        .await;
+
+    app
+}
+
+/// Build a server logging users in and counting their visits with sessions
+/// ## Args
+/// - sessions: Sessions<S>
+/// ## Where
+/// - S: SessionStore
+/// ## Returns
+/// - TestClient
+#[cfg(test)]
+pub async fn session_client<S: SessionStore>(sessions: Sessions<S>) -> TestClient {
+    let app = empty_app().await;
+    app.add_middleware(sessions).await;
+
+    app.route("/login", HttpMethod::POST)
+        .with_handler(login_handler)
+        .register()
+        .await;
+
+    app.route("/me", HttpMethod::GET)
+        .with_handler(me_handler)
+        .register()
+        .await;
+
+    app.route("/visits", HttpMethod::POST)
+        .with_handler(visits_handler)
+        .register()
+        .await;
+
+    app.route("/logout", HttpMethod::POST)
+        .with_handler(logout_handler)
+        .register()
+        .await;
+
+    TestClient::new(app)
+}
+
+/// Build a server with a form protected by the Csrf middleware
+/// ## Args
+/// - with_sessions: bool (add the Sessions middleware the Csrf middleware requires)
+/// ## Returns
+/// - TestClient
+#[cfg(test)]
+pub async fn csrf_client(with_sessions: bool) -> TestClient {
+    let app = empty_app().await;
+    if with_sessions {
+        app.add_middleware(Sessions::new(MemoryStore::new()).with_secure(false))
+            .await;
+    }
+    app.add_middleware(Csrf::new().with_exempt_path("/api/"))
+        .await;
+
+    app.route("/profile", HttpMethod::GET)
+        .with_template("csrf_form.html")
+        .register()
+        .await;
+
+    app.route("/profile", HttpMethod::POST)
+        .with_handler(save_profile_handler)
+        .register()
+        .await;
+
+    app.route("/profile", HttpMethod::DELETE)
+        .with_handler(delete_profile_handler)
+        .register()
+        .await;
+
+    app.route("/token", HttpMethod::GET)
+        .with_handler(csrf_token_handler)
+        .register()
+        .await;
+
+    app.with_group("/api", |group| async move {
+        let mut group = group.lock().await;
+
+        group
+            .route("/items", HttpMethod::POST)
+            .with_handler(created_handler)
+            .register()
+            .await;
+    })
+    .await;
+
+    app.route("/apis", HttpMethod::POST)
+        .with_handler(created_handler)
+        .register()
+        .await;
+
+    app.with_group("", |group| async move {
+        let mut group = group.lock().await;
+        group.with_subdomain("api").add_middleware(CsrfExempt);
+
+        group
+            .route("/profile", HttpMethod::POST)
+            .with_handler(save_profile_handler)
+            .register()
+            .await;
+    })
+    .await;
+
+    TestClient::new(app)
+}
+
+/// Build a server with a template page, an embeddable page and static files behind
+/// the SecurityHeaders middleware
+/// ## Args
+/// - headers: SecurityHeaders
+/// ## Returns
+/// - TestClient
+#[cfg(test)]
+pub async fn security_client(headers: SecurityHeaders) -> TestClient {
+    let mut app = empty_app().await;
+    app.with_static_dir("tests/static").await;
+    app.add_middleware(headers).await;
+
+    app.route("/dashboard", HttpMethod::GET)
+        .with_template("csp_page.html")
+        .register()
+        .await;
+
+    app.route("/embed", HttpMethod::GET)
+        .with_handler(embed_handler)
+        .register()
+        .await;
+
+    app.route("/nonce", HttpMethod::GET)
+        .with_handler(csp_nonce_handler)
+        .register()
+        .await;
+
+    TestClient::new(app)
+}
+
+/// Build a server with a route and a group behind rate limits
+/// ## Returns
+/// - ServerHandle
+#[cfg(test)]
+pub async fn rate_limit_server() -> ServerHandle {
+    let app = empty_app().await;
+
+    app.route("/search", HttpMethod::GET)
+        .with_middleware(
+            RateLimit::new(Quota::token_bucket(2, Duration::from_secs(60))).by_header("X-API-Key"),
+        )
+        .with_handler(search_handler)
+        .register()
+        .await;
+
+    app.with_group("/reports", |group| async move {
+        let mut group = group.lock().await;
+        group.add_middleware(
+            RateLimit::new(Quota::sliding_window(1, Duration::from_secs(60)))
+                .by_key(|req| req.params.get("account").cloned()),
+        );
+
+        group
+            .route("/daily", HttpMethod::GET)
+            .with_handler(daily_report_handler)
+            .register()
+            .await;
+
+        group
+            .route("/weekly", HttpMethod::GET)
+            .with_handler(weekly_report_handler)
+            .register()
+            .await;
+    })
+    .await;
+
+    app
+}
+
+/// Build a server answering with the address, scheme and host of the client
+/// ## Returns
+/// - ServerHandle
+#[cfg(test)]
+pub async fn proxy_server() -> ServerHandle {
+    let app = empty_app().await;
+
+    app.route("/client", HttpMethod::GET)
+        .with_handler(client_info_handler)
+        .register()
+        .await;
+
+    app
+}
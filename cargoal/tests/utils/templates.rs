@@ -61,3 +61,18 @@ pub fn escaping_handler(_req: &Request) -> Context {
 
     context
 }
+
+#[cfg(test)]
+pub fn product_context_handler(_req: &Request) -> Context {
+    let mut context = HashMap::new();
+    context.insert("name".to_string(), "Keyboard".into());
+    context.insert("price".to_string(), 49.into());
+    context
+}
+
+#[cfg(test)]
+pub fn dashboard_handler(_req: &Request) -> Context {
+    let mut context = HashMap::new();
+    context.insert("title".to_string(), "Dashboard".into());
+    context
+}